@@ -0,0 +1,153 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_vanity_address::matcher::{DEFAULT_FLEX_LEVEL, MatchType, matches_pattern};
+use solana_vanity_address::search::{Matcher, STOP_CHECK_BATCH, SearchControl, search_each};
+use solana_vanity_address::source::KeypairSource;
+use std::hint::black_box;
+
+// Fixed keypairs so runs are comparable across machines and commits
+fn sample_pubkeys() -> Vec<String> {
+    (1..=16u8)
+        .map(|i| Keypair::new_from_array([i; 32]).pubkey().to_string())
+        .collect()
+}
+
+// Short, typical and long patterns; none of them are expected to match the samples, so every character path is exercised
+const PATTERNS: &[&str] = &["Sol", "PunkX", "SoLanaVanity"];
+
+// (label, case_sensitive, flex_level)
+const FLAG_COMBOS: &[(&str, bool, u8)] = &[("exact", true, 0), ("ignore_case", false, 0)];
+
+fn bench_match_type(c: &mut Criterion, group_name: &str, match_type: MatchType) {
+    let pubkeys = sample_pubkeys();
+    let mut group = c.benchmark_group(group_name);
+    for pattern in PATTERNS {
+        for (label, case_sensitive, flex_level) in FLAG_COMBOS {
+            group.bench_with_input(BenchmarkId::new(*label, pattern), pattern, |b, pattern| {
+                b.iter(|| {
+                    for pubkey in &pubkeys {
+                        black_box(matches_pattern(
+                            black_box(pubkey.as_bytes()),
+                            black_box(pattern.as_bytes()),
+                            match_type,
+                            *case_sensitive,
+                            *flex_level,
+                        ));
+                    }
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_prefix(c: &mut Criterion) {
+    bench_match_type(c, "prefix", MatchType::Prefix);
+}
+
+fn bench_suffix(c: &mut Criterion) {
+    bench_match_type(c, "suffix", MatchType::Suffix);
+}
+
+fn bench_either(c: &mut Criterion) {
+    bench_match_type(c, "either", MatchType::Either);
+}
+
+fn bench_flexible(c: &mut Criterion) {
+    let pubkeys = sample_pubkeys();
+    let mut group = c.benchmark_group("flexible");
+    for pattern in PATTERNS {
+        for (label, match_type) in [
+            ("prefix", MatchType::Prefix),
+            ("suffix", MatchType::Suffix),
+            ("either", MatchType::Either),
+        ] {
+            group.bench_with_input(BenchmarkId::new(label, pattern), pattern, |b, pattern| {
+                b.iter(|| {
+                    for pubkey in &pubkeys {
+                        black_box(matches_pattern(
+                            black_box(pubkey.as_bytes()),
+                            black_box(pattern.as_bytes()),
+                            match_type,
+                            false,
+                            DEFAULT_FLEX_LEVEL,
+                        ));
+                    }
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+// The per-candidate base58 encoding cost that precedes every match check
+fn bench_base58_encode(c: &mut Criterion) {
+    let keypairs: Vec<Keypair> = (1..=16u8)
+        .map(|i| Keypair::new_from_array([i; 32]))
+        .collect();
+    let mut group = c.benchmark_group("base58_encode");
+    group.bench_function("pubkey_to_string", |b| {
+        b.iter(|| {
+            for keypair in &keypairs {
+                black_box(black_box(keypair).pubkey().to_string());
+            }
+        })
+    });
+    group.finish();
+}
+
+// Hands out copies of one keypair, so the search loop itself is what gets measured rather than key generation
+struct FixedSource(Keypair);
+
+impl KeypairSource for FixedSource {
+    fn next(&mut self) -> Keypair {
+        self.0.insecure_clone()
+    }
+}
+
+// Rejects every candidate, so a search runs until its attempt limit
+struct NoAddress;
+
+impl Matcher for NoAddress {
+    fn is_match(&self, _pubkey_bytes: &[u8]) -> bool {
+        false
+    }
+}
+
+// The single worker search loop checking the stop flag after every candidate against once per STOP_CHECK_BATCH
+fn bench_search_batch(c: &mut Criterion) {
+    const ATTEMPTS: u64 = 4096;
+    let mut group = c.benchmark_group("search_batch");
+    for batch in [1, STOP_CHECK_BATCH] {
+        group.bench_with_input(
+            BenchmarkId::new("check_every", batch),
+            &batch,
+            |b, &batch| {
+                b.iter(|| {
+                    let control =
+                        SearchControl::with_attempt_limit(ATTEMPTS).with_check_batch(batch);
+                    black_box(search_each(
+                        &NoAddress,
+                        1,
+                        |_| FixedSource(Keypair::new_from_array([1; 32])),
+                        &control,
+                        |_, _, _| true,
+                    ))
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_prefix,
+    bench_suffix,
+    bench_either,
+    bench_flexible,
+    bench_base58_encode,
+    bench_search_batch
+);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+use clap::ValueEnum;
+use solana_client::rpc_client::RpcClient;
+use solana_commitment_config::CommitmentConfig;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+// Clusters that hand out free SOL; mainnet has no faucet, so it can't be picked
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Cluster {
+    Devnet,
+    Testnet,
+}
+
+impl Cluster {
+    // Name shown while the airdrop is requested
+    pub fn moniker(self) -> &'static str {
+        match self {
+            Cluster::Devnet => "devnet",
+            Cluster::Testnet => "testnet",
+        }
+    }
+
+    // Public RPC endpoint of the cluster
+    pub fn url(self) -> &'static str {
+        match self {
+            Cluster::Devnet => "https://api.devnet.solana.com",
+            Cluster::Testnet => "https://api.testnet.solana.com",
+        }
+    }
+}
+
+// Asks the cluster's faucet for sol SOL to the keypair's address, waits until the cluster confirms the transfer,
+// then returns the balance the cluster reports afterwards (e.g. "1 SOL").
+pub fn request_airdrop(keypair: &Keypair, sol: f64, cluster: Cluster) -> Result<String, String> {
+    let client = RpcClient::new_with_commitment(cluster.url(), CommitmentConfig::confirmed());
+    let pubkey = keypair.pubkey();
+    let lamports = (sol * LAMPORTS_PER_SOL).round() as u64;
+    let signature = client
+        .request_airdrop(&pubkey, lamports)
+        .map_err(|e| e.to_string())?;
+    client
+        .poll_for_signature(&signature)
+        .map_err(|e| format!("airdrop {} not confirmed: {}", signature, e))?;
+    let balance = client.get_balance(&pubkey).map_err(|e| e.to_string())?;
+    Ok(format!("{} SOL", balance as f64 / LAMPORTS_PER_SOL))
+}
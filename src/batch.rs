@@ -0,0 +1,282 @@
+use crate::matcher::{MatchType, PatternMatcher, encode_base58};
+use crate::search::Matcher;
+use five8::BASE58_ENCODED_32_MAX_LEN;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Splits a patterns file line into the pattern and the output path that may follow it after a tab, e.g.
+// "Sol\twallets/sol.json". Whitespace around either part is dropped; an empty path counts as none.
+pub fn split_pattern_line(line: &str) -> (&str, Option<&str>) {
+    match line.split_once('\t') {
+        Some((pattern, path)) => {
+            let path = path.trim();
+            (pattern.trim(), (!path.is_empty()).then_some(path))
+        }
+        None => (line.trim(), None),
+    }
+}
+
+// Unique patterns of a batch, plus what was dropped or overlaps so it can be reported
+pub struct PatternSet {
+    pub patterns: Vec<String>,
+    // match type of each unique pattern, in the same order
+    pub match_types: Vec<MatchType>,
+    pub duplicates: Vec<String>,
+    // (shorter, longer): any address matching the longer pattern also satisfies the shorter one
+    pub overlaps: Vec<(String, String)>,
+}
+
+// Drops repeated patterns and finds pairs where one pattern is contained in another at the matched end.
+// match_types holds each pattern's match type; a pattern only repeats or overlaps another of the same type.
+// Patterns are compared ignoring case unless the search is case sensitive.
+pub fn dedupe_patterns(
+    patterns: Vec<String>,
+    match_types: Vec<MatchType>,
+    case_sensitive: bool,
+) -> PatternSet {
+    let normalize = |pattern: &str| {
+        if case_sensitive {
+            pattern.to_string()
+        } else {
+            pattern.to_ascii_lowercase()
+        }
+    };
+
+    // an anagram repeats another with the same characters in any order
+    let key = |pattern: &str, match_type: MatchType| {
+        let mut key = normalize(pattern).into_bytes();
+        if match_type == MatchType::Anagram {
+            key.sort_unstable();
+        }
+        key
+    };
+
+    let mut unique: Vec<(String, MatchType)> = Vec::new();
+    let mut duplicates = Vec::new();
+    for (pattern, match_type) in patterns.into_iter().zip(match_types) {
+        if unique
+            .iter()
+            .any(|(u, t)| *t == match_type && key(u, *t) == key(&pattern, match_type))
+        {
+            duplicates.push(pattern);
+        } else {
+            unique.push((pattern, match_type));
+        }
+    }
+
+    let mut overlaps = Vec::new();
+    for (shorter, match_type) in &unique {
+        for (longer, other_type) in &unique {
+            if shorter.len() >= longer.len() || match_type != other_type {
+                continue;
+            }
+            let (s, l) = (normalize(shorter), normalize(longer));
+            let overlapping = match match_type {
+                MatchType::Prefix | MatchType::PrefixAfterOnes => l.starts_with(&s),
+                MatchType::Suffix => l.ends_with(&s),
+                MatchType::Either => l.starts_with(&s) || l.ends_with(&s),
+                // a window holding the longer pattern holds the shorter one too
+                MatchType::Fingerprint | MatchType::Window => l.contains(&s),
+                // the shorter pattern is only covered when both start at the same position
+                MatchType::Range | MatchType::Word => l.starts_with(&s),
+                // the longer pattern's characters in some order need not hold the shorter one's in a row
+                MatchType::Anagram => false,
+            };
+            if overlapping {
+                overlaps.push((shorter.clone(), longer.clone()));
+            }
+        }
+    }
+
+    let (patterns, match_types) = unique.into_iter().unzip();
+    PatternSet {
+        patterns,
+        match_types,
+        duplicates,
+        overlaps,
+    }
+}
+
+// Splits a batch into the patterns still to search and those an address kept from an earlier run already satisfies,
+// so a re-run only searches what's missing. stored[i] holds the base58 addresses that count for pattern i.
+// Returns the (remaining, done) pattern indices.
+pub fn resume_patterns(
+    matcher: &PatternMatcher,
+    stored: &[Vec<String>],
+) -> (Vec<usize>, Vec<usize>) {
+    (0..matcher.patterns.len()).partition(|&i| {
+        !stored[i]
+            .iter()
+            .any(|address| matcher.matches_index(address.as_bytes(), i))
+    })
+}
+
+// Looks for one address per pattern; satisfied patterns are skipped so workers only spend effort on what's missing
+pub struct BatchMatcher {
+    pub matcher: PatternMatcher,
+    satisfied: Vec<AtomicBool>,
+}
+
+impl BatchMatcher {
+    pub fn new(matcher: PatternMatcher) -> Self {
+        let satisfied = matcher
+            .patterns
+            .iter()
+            .map(|_| AtomicBool::new(false))
+            .collect();
+        BatchMatcher { matcher, satisfied }
+    }
+
+    // Marks every still-unsatisfied pattern the base58 address matches and returns their indices.
+    // Safe to call from several workers at once: each pattern is claimed by exactly one address.
+    pub fn claim(&self, pubkey: &[u8]) -> Vec<usize> {
+        (0..self.matcher.patterns.len())
+            .filter(|&i| {
+                !self.satisfied[i].load(Ordering::Relaxed)
+                    && self.matcher.matches_index(pubkey, i)
+                    && self.satisfied[i]
+                        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Relaxed)
+                        .is_ok()
+            })
+            .collect()
+    }
+
+    pub fn all_satisfied(&self) -> bool {
+        self.satisfied.iter().all(|s| s.load(Ordering::Relaxed))
+    }
+}
+
+impl Matcher for BatchMatcher {
+    fn is_match(&self, pubkey_bytes: &[u8]) -> bool {
+        if !self.matcher.accepts_bytes(pubkey_bytes) {
+            return false;
+        }
+        let Ok(pubkey_bytes) = pubkey_bytes.try_into() else {
+            return false;
+        };
+        let mut buf = [0u8; BASE58_ENCODED_32_MAX_LEN];
+        let encoded = encode_base58(pubkey_bytes, &mut buf);
+        if self.matcher.rejects_leading_one(encoded) {
+            return false;
+        }
+        (0..self.matcher.patterns.len()).any(|i| {
+            !self.satisfied[i].load(Ordering::Relaxed) && self.matcher.matches_index(encoded, i)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vectors::{USDC_MINT, WSOL_MINT};
+
+    fn strings(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn duplicates_are_searched_once() {
+        let patterns = strings(&["Sol", "Ab", "sol", "Sol"]);
+        let set = dedupe_patterns(patterns.clone(), vec![MatchType::Prefix; 4], false);
+        assert_eq!(set.patterns, ["Sol", "Ab"]);
+        assert_eq!(set.duplicates, ["sol", "Sol"]);
+
+        // a case sensitive search tells them apart
+        let set = dedupe_patterns(patterns, vec![MatchType::Prefix; 4], true);
+        assert_eq!(set.patterns, ["Sol", "Ab", "sol"]);
+        assert_eq!(set.duplicates, ["Sol"]);
+
+        // as does a different match type
+        let set = dedupe_patterns(
+            strings(&["Sol", "Sol"]),
+            vec![MatchType::Prefix, MatchType::Suffix],
+            false,
+        );
+        assert_eq!(set.patterns, ["Sol", "Sol"]);
+        assert!(set.duplicates.is_empty());
+    }
+
+    #[test]
+    fn overlaps_depend_on_the_matched_end() {
+        let patterns = strings(&["Abh", "Ab", "hr", "6hr"]);
+        let set = dedupe_patterns(patterns.clone(), vec![MatchType::Prefix; 4], true);
+        assert_eq!(set.overlaps, [("Ab".to_string(), "Abh".to_string())]);
+        let set = dedupe_patterns(patterns, vec![MatchType::Suffix; 4], true);
+        assert_eq!(set.overlaps, [("hr".to_string(), "6hr".to_string())]);
+    }
+
+    #[test]
+    fn one_match_satisfies_every_overlapping_pattern() {
+        let address = "AbhUUsTGfbTQAiD2sEsbsQCSHvrgDAsieAGeSc1Jq6hr";
+        let batch = BatchMatcher::new(PatternMatcher::new(
+            strings(&["Ab", "Zq", "Abh"]),
+            MatchType::Prefix,
+            true,
+            false,
+        ));
+        assert_eq!(batch.claim(address.as_bytes()), [0, 2]);
+        // each pattern is claimed by one address only
+        assert!(batch.claim(address.as_bytes()).is_empty());
+        assert!(!batch.all_satisfied());
+    }
+
+    // Patterns file lines and the pattern and output file they name
+    const PATTERN_LINE_VECTORS: &[(&str, &str, Option<&str>)] = &[
+        ("Sol", "Sol", None),
+        ("Sol\twallets/sol.json", "Sol", Some("wallets/sol.json")),
+        (
+            "Sol \t wallets/my sol.json ",
+            "Sol",
+            Some("wallets/my sol.json"),
+        ),
+        ("Sol\t", "Sol", None),
+        ("Sol wallets/sol.json", "Sol wallets/sol.json", None),
+    ];
+
+    // Addresses an earlier run stored, one list per pattern
+    type StoredAddresses = &'static [&'static [&'static str]];
+
+    // Batch patterns (prefix matched, case sensitive), the addresses stored for each by an earlier run, and the patterns
+    // a resumed run still has to search
+    const RESUME_VECTORS: &[(&[&str], StoredAddresses, &[usize])] = &[
+        (&["EPjF", "So1", "zzz"], &[&[], &[], &[]], &[0, 1, 2]),
+        (
+            &["EPjF", "So1", "zzz"],
+            &[&[USDC_MINT], &[USDC_MINT], &[USDC_MINT]],
+            &[1, 2],
+        ),
+        (
+            &["EPjF", "So1", "zzz"],
+            &[&[USDC_MINT, WSOL_MINT], &[USDC_MINT, WSOL_MINT], &[]],
+            &[2],
+        ),
+        // an address only counts for the patterns it was stored for
+        (&["EPjF", "So1"], &[&[WSOL_MINT], &[USDC_MINT]], &[0, 1]),
+    ];
+
+    #[test]
+    fn pattern_lines_name_an_output_file() {
+        for &(line, pattern, path) in PATTERN_LINE_VECTORS {
+            assert_eq!(
+                split_pattern_line(line),
+                (pattern, path),
+                "patterns file line {:?}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn resume_skips_patterns_already_found() {
+        for &(patterns, stored, expected) in RESUME_VECTORS {
+            let matcher = PatternMatcher::new(strings(patterns), MatchType::Prefix, true, false);
+            let stored: Vec<Vec<String>> =
+                stored.iter().map(|addresses| strings(addresses)).collect();
+            let (remaining, _) = resume_patterns(&matcher, &stored);
+            assert_eq!(
+                remaining, expected,
+                "resuming {:?} with {:?} stored",
+                patterns, stored
+            );
+        }
+    }
+}
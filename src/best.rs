@@ -0,0 +1,243 @@
+use crate::matcher::{PatternMatcher, encode_base58};
+use crate::search::{Matcher, SearchControl, SearchOutcome, search_each};
+use crate::source::KeypairSource;
+use bip39::Mnemonic;
+use five8::BASE58_ENCODED_32_MAX_LEN;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+// Highest scoring keypair seen so far
+pub struct Best {
+    pub keypair: Keypair,
+    pub mnemonic: Option<Mnemonic>,
+    pub score: usize,
+    pub attempts: u64,
+}
+
+// Keeps the highest scoring address across all workers. As a Matcher it only lets through candidates that beat
+// the current best, so the lock in offer is taken rarely once a decent address has been seen.
+pub struct BestTracker<F> {
+    score: F,
+    best_score: AtomicUsize,
+    has_best: AtomicBool,
+    best: Mutex<Option<Best>>,
+}
+
+impl<F: Fn(&[u8]) -> usize> BestTracker<F> {
+    // score receives the base58 address; higher is better
+    pub fn new(score: F) -> Self {
+        BestTracker {
+            score,
+            best_score: AtomicUsize::new(0),
+            has_best: AtomicBool::new(false),
+            best: Mutex::new(None),
+        }
+    }
+
+    // Keeps the keypair if it scores higher than the current best; ties keep the earlier one
+    pub fn offer(&self, keypair: Keypair, mnemonic: Option<Mnemonic>, attempts: u64) {
+        let score = (self.score)(keypair.pubkey().to_string().as_bytes());
+        let mut best = self.best.lock().unwrap();
+        if best.as_ref().is_none_or(|best| score > best.score) {
+            self.best_score.store(score, Ordering::Relaxed);
+            self.has_best.store(true, Ordering::Relaxed);
+            *best = Some(Best {
+                keypair,
+                mnemonic,
+                score,
+                attempts,
+            });
+        }
+    }
+
+    pub fn best_score(&self) -> usize {
+        self.best_score.load(Ordering::Relaxed)
+    }
+
+    // Address of the current best, if there is one yet
+    pub fn best_address(&self) -> Option<String> {
+        let best = self.best.lock().unwrap();
+        best.as_ref().map(|best| best.keypair.pubkey().to_string())
+    }
+
+    pub fn into_best(self) -> Option<Best> {
+        self.best.into_inner().unwrap()
+    }
+}
+
+impl<F: Fn(&[u8]) -> usize> Matcher for BestTracker<F> {
+    fn is_match(&self, pubkey_bytes: &[u8]) -> bool {
+        let Ok(pubkey_bytes) = pubkey_bytes.try_into() else {
+            return false;
+        };
+        let mut buf = [0u8; BASE58_ENCODED_32_MAX_LEN];
+        let encoded = encode_base58(pubkey_bytes, &mut buf);
+        // the very first candidate always gets through so there is a result even if everything scores 0
+        (self.score)(encoded) > self.best_score() || !self.has_best.load(Ordering::Relaxed)
+    }
+}
+
+// Lets through a full match of the patterns as well as anything the tracker would keep
+struct KeepingClosest<'a, G> {
+    matcher: &'a PatternMatcher,
+    closest: &'a BestTracker<G>,
+}
+
+impl<G: Fn(&[u8]) -> usize> Matcher for KeepingClosest<'_, G> {
+    fn is_match(&self, pubkey_bytes: &[u8]) -> bool {
+        self.matcher.is_match(pubkey_bytes) || self.closest.is_match(pubkey_bytes)
+    }
+}
+
+// search for the matcher's patterns that also offers the tracker each candidate it would keep, so a search that
+// runs out of time still has its closest miss to show. Ends at the first full match, like search.
+pub fn search_keeping_closest<S, F, G>(
+    matcher: &PatternMatcher,
+    closest: &BestTracker<G>,
+    threads: usize,
+    make_source: F,
+    control: &SearchControl,
+) -> SearchOutcome
+where
+    S: KeypairSource,
+    F: Fn(usize) -> S + Sync,
+    G: Fn(&[u8]) -> usize + Sync,
+{
+    let found = Mutex::new(None);
+    let attempts = search_each(
+        &KeepingClosest { matcher, closest },
+        threads,
+        make_source,
+        control,
+        |keypair, source, attempts| {
+            let pubkey = keypair.pubkey();
+            if !matcher.accepts_bytes(pubkey.as_ref())
+                || matcher
+                    .matched_pattern(pubkey.to_string().as_bytes())
+                    .is_none()
+            {
+                closest.offer(keypair, source.last_mnemonic().cloned(), attempts);
+                return false;
+            }
+            found
+                .lock()
+                .unwrap()
+                .get_or_insert_with(|| (keypair, source.last_mnemonic().cloned()));
+            true
+        },
+    );
+
+    SearchOutcome {
+        found: found.into_inner().unwrap(),
+        attempts,
+    }
+}
+
+// Runs until the control's attempt limit, keeping the candidate with the most leading characters of a pattern in a row
+// where its match type looks (see PatternMatcher::longest_partial), so the run time is bounded and there is always a
+// result. A full match scores the whole pattern but doesn't end the search; ties keep the earlier candidate. Excludes
+// and the other extra conditions are not considered. Returns the best and the attempts made.
+pub fn search_best_effort<S, F>(
+    matcher: &PatternMatcher,
+    threads: usize,
+    make_source: F,
+    control: &SearchControl,
+) -> (Option<Best>, u64)
+where
+    S: KeypairSource,
+    F: Fn(usize) -> S + Sync,
+{
+    let tracker = BestTracker::new(|address: &[u8]| {
+        matcher
+            .longest_partial(address)
+            .map_or(0, |(_, matched)| matched)
+    });
+    let attempts = search_each(
+        &tracker,
+        threads,
+        make_source,
+        control,
+        |keypair, source, attempts| {
+            tracker.offer(keypair, source.last_mnemonic().cloned(), attempts);
+            false
+        },
+    );
+    (tracker.into_best(), attempts)
+}
+
+// Number of identical characters the address starts with, e.g. 4 for "zzzzA..."
+pub fn leading_run(address: &[u8]) -> usize {
+    match address.first() {
+        Some(&first) => address.iter().take_while(|&&c| c == first).count(),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::MatchType;
+    use crate::source::SequenceSource;
+
+    // Prefix patterns, worker count and attempt budget for a best effort search over the keypairs of the secret seeds
+    // [1; 32] to [16; 32], none of which matches fully, with the address it must keep and its leading characters right.
+    // Every worker cycles through the seeds from the first, so the best must come within each worker's share.
+    const BEST_EFFORT_VECTORS: &[(&str, usize, u64, &str, usize)] = &[
+        // [6; 32] starts "AKkz", [1; 32] only "AK"
+        (
+            "AKkzX",
+            1,
+            16,
+            "AKkzLhjhyFtM9j7WAhbaqYpFe49cXeJBg2kzLRC2PnNa",
+            4,
+        ),
+        (
+            "AKkzX",
+            1,
+            5,
+            "AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9",
+            2,
+        ),
+        (
+            "GyGKQ",
+            3,
+            16,
+            "GyGKxMyg1p9SsHfm15MkNUu1u9TN2JtTspcdmrtGUdse",
+            4,
+        ),
+        // nothing starts right, so the first candidate is kept
+        (
+            "zzz",
+            1,
+            16,
+            "AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9",
+            0,
+        ),
+    ];
+
+    #[test]
+    fn best_effort_keeps_the_longest_partial_match() {
+        let seeds: Vec<[u8; 32]> = (1..=16).map(|i| [i; 32]).collect();
+        for &(pattern, threads, budget, address, matched) in BEST_EFFORT_VECTORS {
+            let matcher =
+                PatternMatcher::new(vec![pattern.to_string()], MatchType::Prefix, true, false);
+            let (best, attempts) = search_best_effort(
+                &matcher,
+                threads,
+                |_| SequenceSource::new(seeds.clone()),
+                &SearchControl::with_attempt_limit(budget),
+            );
+            let got = best.map(|best| (best.keypair.pubkey().to_string(), best.score));
+            assert_eq!(
+                (got, attempts),
+                (Some((address.to_string(), matched)), budget),
+                "best effort for '{}' with {} worker(s) and {} attempts",
+                pattern,
+                threads,
+                budget
+            );
+        }
+    }
+}
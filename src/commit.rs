@@ -0,0 +1,87 @@
+use crate::matcher::to_hex;
+use crate::source::fill_os_random;
+use sha2::{Digest, Sha256};
+use solana_keypair::Keypair;
+
+// Length of the random nonce mixed into a commitment. It keeps the commitment from being checked against guessed
+// secrets, and makes two commitments to the same keypair look unrelated.
+pub const NONCE_LEN: usize = 32;
+
+// A fresh nonce from the OS random number generator
+pub fn new_nonce() -> Result<[u8; NONCE_LEN], String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    fill_os_random(&mut nonce)?;
+    Ok(nonce)
+}
+
+// SHA-256 over the nonce followed by the 32 byte secret key (the first half of a keypair file), as lower case hex.
+// Publishing it binds the finder to the keypair without revealing anything; showing the nonce and the secret later
+// opens it.
+pub fn commitment(keypair: &Keypair, nonce: &[u8; NONCE_LEN]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(nonce);
+    hasher.update(&keypair.to_bytes()[..32]);
+    to_hex(&hasher.finalize())
+}
+
+// Whether the nonce (hex) and keypair open the commitment (hex); hex is accepted in either case
+pub fn opens(commitment_hex: &str, nonce_hex: &str, keypair: &Keypair) -> Result<bool, String> {
+    let nonce: [u8; NONCE_LEN] = from_hex(nonce_hex)?
+        .try_into()
+        .map_err(|_| format!("the nonce must be {} bytes", NONCE_LEN))?;
+    Ok(commitment(keypair, &nonce).eq_ignore_ascii_case(commitment_hex.trim()))
+}
+
+// Bytes of a hex string with two digits per byte
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let hex = hex.trim();
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("'{}' is not hex", hex));
+    }
+    if !hex.len().is_multiple_of(2) {
+        return Err(format!("'{}' has an odd number of hex digits", hex));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|e| e.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Keypair seed byte, nonce byte and the --commit commitment: SHA-256 over [nonce; 32] followed by [seed; 32], as
+    // computed independently. Each must open with its own nonce and not with the other's.
+    const COMMIT_VECTORS: &[(u8, u8, &str)] = &[
+        (
+            1,
+            2,
+            "c57d4f59c961b13e406cd991b0f342ec79e571dc2c1415ff72c6550645a3b198",
+        ),
+        (
+            7,
+            0,
+            "daf6d3e6ad66990aba2fae6e6c61f18b2d48f0ca6c29d2cfa19ab41f5a865231",
+        ),
+    ];
+
+    #[test]
+    fn commitment_opens_with_its_own_nonce_only() {
+        for &(seed, nonce, expected) in COMMIT_VECTORS {
+            let keypair = Keypair::new_from_array([seed; 32]);
+            assert_eq!(
+                commitment(&keypair, &[nonce; 32]),
+                expected,
+                "commitment to seed {} with nonce {}",
+                seed,
+                nonce
+            );
+            assert_eq!(opens(expected, &to_hex(&[nonce; 32]), &keypair), Ok(true));
+            assert_eq!(
+                opens(expected, &to_hex(&[nonce ^ 1; 32]), &keypair),
+                Ok(false)
+            );
+        }
+    }
+}
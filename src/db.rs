@@ -0,0 +1,92 @@
+use crate::output::{FoundKeypair, open_private};
+use rusqlite::{Connection, params};
+use solana_signer::Signer;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Table found keypairs are inserted into, created the first time a database is used
+pub const DB_TABLE: &str = "keypairs";
+
+// One row of the table: the pattern the address matched, the address, the secret key in the base58 form the human
+// output prints, when it was found (unix seconds) and the attempts it took
+#[derive(Debug, PartialEq)]
+pub struct StoredKeypair {
+    pub pattern: String,
+    pub pubkey: String,
+    pub secret: String,
+    pub found_at: u64,
+    pub attempts: u64,
+}
+
+// Opens the database, creating the file with owner-only permissions if it doesn't exist yet since it will hold
+// secret keys, and creates the table if it is missing
+pub fn open_db(path: &Path) -> Result<Connection, String> {
+    if !path.exists() {
+        open_private(OpenOptions::new().write(true).create_new(true), path)
+            .map_err(|e| e.to_string())?;
+    }
+    let conn = Connection::open(path).map_err(|e| e.to_string())?;
+    create_table(&conn).map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+// Creates the table unless a previous run already did
+pub fn create_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {DB_TABLE} (
+            id INTEGER PRIMARY KEY,
+            pattern TEXT NOT NULL,
+            pubkey TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            found_at INTEGER NOT NULL,
+            attempts INTEGER NOT NULL
+        )"
+    ))
+}
+
+// Inserts one keypair in a transaction of its own, so a crash or a full disk leaves either the whole row or nothing
+pub fn insert_keypair(
+    conn: &mut Connection,
+    found: &FoundKeypair,
+    found_at: u64,
+) -> rusqlite::Result<()> {
+    let transaction = conn.transaction()?;
+    transaction.execute(
+        &format!(
+            "INSERT INTO {DB_TABLE} (pattern, pubkey, secret, found_at, attempts) VALUES (?1, ?2, ?3, ?4, ?5)"
+        ),
+        params![
+            found.pattern,
+            found.keypair.pubkey().to_string(),
+            found.keypair.to_base58_string(),
+            found_at as i64,
+            found.attempts as i64,
+        ],
+    )?;
+    transaction.commit()
+}
+
+// Every row of the table, oldest first
+pub fn stored_keypairs(conn: &Connection) -> rusqlite::Result<Vec<StoredKeypair>> {
+    let mut statement = conn.prepare(&format!(
+        "SELECT pattern, pubkey, secret, found_at, attempts FROM {DB_TABLE} ORDER BY id"
+    ))?;
+    let rows = statement.query_map([], |row| {
+        Ok(StoredKeypair {
+            pattern: row.get(0)?,
+            pubkey: row.get(1)?,
+            secret: row.get(2)?,
+            found_at: row.get::<_, i64>(3)? as u64,
+            attempts: row.get::<_, i64>(4)? as u64,
+        })
+    })?;
+    rows.collect()
+}
+
+// Seconds since the unix epoch, the timestamp rows are stored with
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
@@ -0,0 +1,175 @@
+use crate::search::SearchControl;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// Line protocol: a joiner sends "FOUND <base58 keypair> <attempts>" when it finds a match, and the coordinator sends
+// "STOP" to every joiner once any match (local or remote) has been accepted
+const FOUND: &str = "FOUND";
+const STOP: &str = "STOP";
+
+// A keypair reported by a joiner, with the attempts that joiner made
+pub struct RemoteFind {
+    pub keypair: Keypair,
+    pub attempts: u64,
+    pub peer: String,
+}
+
+// Accepts joiners, keeps the first valid keypair any of them reports and tells everyone to stop
+pub struct Coordinator {
+    joiners: Arc<Mutex<Vec<TcpStream>>>,
+    remote: Arc<Mutex<Option<RemoteFind>>>,
+}
+
+impl Coordinator {
+    // Listens on addr in the background. Reported keypairs are only trusted if accept (the coordinator's own
+    // pattern check) passes; an accepted report stops the local search through control.
+    pub fn start<A>(addr: &str, control: Arc<SearchControl>, accept: A) -> io::Result<Self>
+    where
+        A: Fn(&Keypair) -> bool + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind(addr)?;
+        let joiners = Arc::new(Mutex::new(Vec::new()));
+        let remote = Arc::new(Mutex::new(None));
+        let accept = Arc::new(accept);
+
+        let coordinator = Coordinator {
+            joiners: joiners.clone(),
+            remote: remote.clone(),
+        };
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                // late joiners are turned away straight away
+                if control.is_stopped() {
+                    let _ = writeln!(stream, "{}", STOP);
+                    continue;
+                }
+                let Ok(writer) = stream.try_clone() else {
+                    continue;
+                };
+                joiners.lock().unwrap().push(writer);
+
+                let (control, joiners, remote, accept) = (
+                    control.clone(),
+                    joiners.clone(),
+                    remote.clone(),
+                    accept.clone(),
+                );
+                thread::spawn(move || {
+                    handle_joiner(stream, &control, &joiners, &remote, accept.as_ref())
+                });
+            }
+        });
+
+        Ok(coordinator)
+    }
+
+    // Tells every connected joiner to stop searching
+    pub fn broadcast_stop(&self) {
+        send_stop(&self.joiners);
+    }
+
+    // The keypair a joiner found, if the search was won remotely
+    pub fn take_remote(&self) -> Option<RemoteFind> {
+        self.remote.lock().unwrap().take()
+    }
+}
+
+fn handle_joiner(
+    stream: TcpStream,
+    control: &SearchControl,
+    joiners: &Mutex<Vec<TcpStream>>,
+    remote: &Mutex<Option<RemoteFind>>,
+    accept: &(dyn Fn(&Keypair) -> bool + Send + Sync),
+) {
+    let peer = stream
+        .peer_addr()
+        .map(|addr| addr.to_string())
+        .unwrap_or_else(|_| "unknown peer".to_string());
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        match parse_found(&line) {
+            Some((keypair, attempts)) if accept(&keypair) => {
+                {
+                    let mut remote = remote.lock().unwrap();
+                    if remote.is_none() && !control.is_stopped() {
+                        *remote = Some(RemoteFind {
+                            keypair,
+                            attempts,
+                            peer: peer.clone(),
+                        });
+                    }
+                }
+                control.stop();
+                send_stop(joiners);
+            }
+            _ => eprintln!("Warning: ignoring invalid report from {}", peer),
+        }
+    }
+}
+
+fn send_stop(joiners: &Mutex<Vec<TcpStream>>) {
+    for joiner in joiners.lock().unwrap().iter_mut() {
+        let _ = writeln!(joiner, "{}", STOP);
+    }
+}
+
+// Parses a FOUND line, rejecting keypairs whose public half doesn't belong to the secret half
+fn parse_found(line: &str) -> Option<(Keypair, u64)> {
+    let mut parts = line.split_whitespace();
+    if parts.next()? != FOUND {
+        return None;
+    }
+    let mut bytes = [0u8; 64];
+    five8::decode_64(parts.next()?, &mut bytes).ok()?;
+    let attempts = parts.next()?.parse().ok()?;
+
+    let keypair = Keypair::new_from_array(bytes[..32].try_into().ok()?);
+    if keypair.pubkey().as_ref() != &bytes[32..] {
+        return None;
+    }
+    Some((keypair, attempts))
+}
+
+// Connection from a searching process to a coordinator
+pub struct Joiner {
+    stream: TcpStream,
+}
+
+impl Joiner {
+    // Connects to the coordinator; the local search is stopped when it says STOP or the connection drops
+    pub fn connect(addr: &str, control: Arc<SearchControl>) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        let reader = stream.try_clone()?;
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                match line {
+                    Ok(line) if line.trim() == STOP => break,
+                    Ok(_) => continue,
+                    Err(_) => break,
+                }
+            }
+            control.stop();
+        });
+        Ok(Joiner { stream })
+    }
+
+    // Sends a found keypair to the coordinator
+    pub fn report(&mut self, keypair: &Keypair, attempts: u64) -> io::Result<()> {
+        writeln!(
+            self.stream,
+            "{} {} {}",
+            FOUND,
+            keypair.to_base58_string(),
+            attempts
+        )
+    }
+}
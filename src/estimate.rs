@@ -0,0 +1,546 @@
+use crate::matcher::{
+    BASE58_ALPHABET, ByteSum, MAX_BYTE_SUM, MAX_FLEX_LEVEL, MatchType, PatternMatcher,
+    accepted_chars, is_word_boundary,
+};
+use crate::search::{Matcher, SearchControl, search_each};
+use crate::source::KeypairSource;
+use std::time::{Duration, Instant};
+
+// Typical base58 address length, used for the number of positions a fingerprint pattern can start at
+const ADDRESS_LEN: usize = 44;
+
+// Chance that one address character satisfies the pattern character, assuming characters are uniform over base58.
+// With canonical_case the other case of a letter no longer counts.
+fn char_probability(target: u8, case_sensitive: bool, flex_level: u8, canonical_case: bool) -> f64 {
+    let accepted = accepted_chars(target, case_sensitive, flex_level);
+    let count = accepted
+        .iter()
+        .filter(|&&c| !canonical_case || c == target || !c.eq_ignore_ascii_case(&target))
+        .count();
+    count as f64 / BASE58_ALPHABET.len() as f64
+}
+
+// Chance that the first character after an address's leading '1's satisfies the pattern character. That character
+// is never a '1' itself, so it is spread over the other 57; how many '1's came before doesn't matter.
+fn after_ones_char_probability(
+    target: u8,
+    case_sensitive: bool,
+    flex_level: u8,
+    canonical_case: bool,
+) -> f64 {
+    let accepts_one = accepted_chars(target, case_sensitive, flex_level).contains(&b'1');
+    let accepted = char_probability(target, case_sensitive, flex_level, canonical_case)
+        * BASE58_ALPHABET.len() as f64
+        - if accepts_one { 1.0 } else { 0.0 };
+    accepted / (BASE58_ALPHABET.len() - 1) as f64
+}
+
+// Chance that a pattern is satisfied at one fixed position with at most max_distance of its characters wrong.
+// With after_ones the position is the one right after the leading '1's.
+fn position_probability(
+    pattern: &str,
+    case_sensitive: bool,
+    flex_level: u8,
+    canonical_case: bool,
+    max_distance: usize,
+    after_ones: bool,
+) -> f64 {
+    let odds = pattern.bytes().enumerate().map(|(i, c)| {
+        if after_ones && i == 0 {
+            after_ones_char_probability(c, case_sensitive, flex_level, canonical_case)
+        } else {
+            char_probability(c, case_sensitive, flex_level, canonical_case)
+        }
+    });
+    at_most_wrong(odds, max_distance)
+}
+
+// Chance that at most max_distance of a run of characters are wrong, given each one's odds of being right. Each
+// character is a separate trial with its own odds, so this adds up the first max_distance + 1 terms of a Poisson
+// binomial distribution; with 0 it is the product of the odds.
+fn at_most_wrong(odds: impl Iterator<Item = f64>, max_distance: usize) -> f64 {
+    // chance of exactly j wrong characters among those seen so far, for j up to max_distance
+    let mut wrong = vec![0.0; max_distance + 1];
+    wrong[0] = 1.0;
+    for p in odds {
+        for j in (0..=max_distance).rev() {
+            let one_more = if j > 0 { wrong[j - 1] * (1.0 - p) } else { 0.0 };
+            wrong[j] = wrong[j] * p + one_more;
+        }
+    }
+    wrong.iter().sum()
+}
+
+// Chance that the stretch of address at one fixed position holds the pattern's characters in any order with at most
+// max_distance of them missing. Its characters are a multinomial draw over the pattern's distinct characters and
+// everything else, so this adds up the chance of every count of each character that leaves few enough missing.
+fn anagram_probability(pattern: &str, case_sensitive: bool, max_distance: usize) -> f64 {
+    let fold = |c: u8| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_ascii_lowercase()
+        }
+    };
+    let mut wanted: Vec<(u8, usize)> = Vec::new();
+    for c in pattern.bytes().map(fold) {
+        match wanted.iter_mut().find(|(w, _)| *w == c) {
+            Some((_, count)) => *count += 1,
+            None => wanted.push((c, 1)),
+        }
+    }
+    let odds = |c: u8| {
+        let accepted = BASE58_ALPHABET.iter().filter(|&&b| fold(b) == c).count();
+        accepted as f64 / BASE58_ALPHABET.len() as f64
+    };
+    let len = pattern.len();
+    let factorial = |n: usize| (1..=n).map(|k| k as f64).product::<f64>();
+
+    // by[used][missing]: sum of p^x / x! over the counts x of the characters so far that use this many positions
+    // and leave this many pattern characters missing
+    let mut by = vec![vec![0.0; max_distance + 1]; len + 1];
+    by[0][0] = 1.0;
+    let mut other = 1.0;
+    for &(c, count) in &wanted {
+        let p = odds(c);
+        other -= p;
+        let mut next = vec![vec![0.0; max_distance + 1]; len + 1];
+        for (used, row) in by.iter().enumerate() {
+            for (missing, &sum) in row.iter().enumerate() {
+                if sum == 0.0 {
+                    continue;
+                }
+                for x in 0..=len - used {
+                    let now_missing = missing + count.saturating_sub(x);
+                    if now_missing <= max_distance {
+                        next[used + x][now_missing] += sum * p.powi(x as i32) / factorial(x);
+                    }
+                }
+            }
+        }
+        by = next;
+    }
+    // the positions left over hold characters outside the pattern
+    (0..=len)
+        .map(|used| {
+            let rest = len - used;
+            by[used].iter().sum::<f64>() * factorial(len) * other.max(0.0).powi(rest as i32)
+                / factorial(rest)
+        })
+        .sum()
+}
+
+// Chance that a position past the start is a word boundary, given its character matched the pattern's first
+// character: the share of preceding characters that would set it off, averaged over the accepted characters
+fn boundary_probability(first: u8, case_sensitive: bool, flex_level: u8) -> f64 {
+    let accepted = accepted_chars(first, case_sensitive, flex_level);
+    let boundaries = accepted
+        .iter()
+        .flat_map(|&c| {
+            BASE58_ALPHABET
+                .iter()
+                .filter(move |&&previous| is_word_boundary(&[previous, c], 1))
+        })
+        .count();
+    boundaries as f64 / (accepted.len() * BASE58_ALPHABET.len()).max(1) as f64
+}
+
+// Chance that a single random address matches, under the matcher's match type and character rules.
+// Treats address characters as independent and uniform, which is close for everything but a leading '1'.
+pub fn attempt_probability(matcher: &PatternMatcher) -> f64 {
+    let miss_all: f64 = matcher
+        .patterns
+        .iter()
+        .zip(&matcher.match_types)
+        .map(|(pattern, &match_type)| {
+            let p = position_probability(
+                pattern,
+                matcher.case_sensitive,
+                matcher.flex_level,
+                matcher.canonical_case,
+                matcher.max_distance,
+                match_type == MatchType::PrefixAfterOnes,
+            );
+            let hit = match match_type {
+                MatchType::Prefix | MatchType::Suffix | MatchType::PrefixAfterOnes => p,
+                MatchType::Either => p + p - p * p,
+                MatchType::Fingerprint => {
+                    let window = matcher.fingerprint_len.min(ADDRESS_LEN);
+                    let starts = window.saturating_sub(pattern.len()) + 1;
+                    1.0 - (1.0 - p).powi(starts as i32)
+                }
+                MatchType::Range => 1.0 - (1.0 - p).powi(matcher.at_range.len() as i32),
+                MatchType::Window => {
+                    let window = matcher.window.end.min(ADDRESS_LEN) - matcher.window.start;
+                    let starts = (window + 1).saturating_sub(pattern.len());
+                    1.0 - (1.0 - p).powi(starts as i32)
+                }
+                // the start of the address is always a boundary, later positions only sometimes
+                MatchType::Word => {
+                    let later = ADDRESS_LEN.saturating_sub(pattern.len()) as i32;
+                    let b = boundary_probability(
+                        pattern.as_bytes()[0],
+                        matcher.case_sensitive,
+                        matcher.flex_level,
+                    );
+                    1.0 - (1.0 - p) * (1.0 - p * b).powi(later)
+                }
+                MatchType::Anagram => {
+                    let p = anagram_probability(
+                        pattern,
+                        matcher.case_sensitive || matcher.canonical_case,
+                        matcher.max_distance,
+                    );
+                    let starts = (ADDRESS_LEN + 1).saturating_sub(pattern.len());
+                    1.0 - (1.0 - p).powi(starts as i32)
+                }
+            };
+            1.0 - hit
+        })
+        .product();
+    (1.0 - miss_all)
+        * matcher
+            .byte_sum
+            .map_or(1.0, |sum| byte_sum_probability(&sum))
+}
+
+// Chance a public key's bytes meet the byte sum, treating the 32 bytes as independent and uniform. The sum's
+// distribution is built exactly, one byte at a time, so values near the middle (about 1 in 1000 each) and at the
+// tails are both right; with a modulus of 256 every remainder is close to 1 in 256.
+pub fn byte_sum_probability(byte_sum: &ByteSum) -> f64 {
+    let mut sums = vec![1.0];
+    for _ in 0..32 {
+        // the chance of each total after one more byte is the average over the 256 totals it can come from
+        let mut prefix = vec![0.0; sums.len() + 1];
+        for (i, p) in sums.iter().enumerate() {
+            prefix[i + 1] = prefix[i] + p;
+        }
+        sums = (0..sums.len() + 255)
+            .map(|total| {
+                let high = (total + 1).min(prefix.len() - 1);
+                let low = total.saturating_sub(255).min(high);
+                (prefix[high] - prefix[low]) / 256.0
+            })
+            .collect();
+    }
+    (0..=MAX_BYTE_SUM)
+        .filter(|&total| match byte_sum.modulus {
+            Some(modulus) => total % modulus == byte_sum.target,
+            None => total == byte_sum.target,
+        })
+        .map(|total| sums[total as usize])
+        .sum()
+}
+
+// Expected number of candidates before the first match (the mean of a geometric distribution)
+pub fn estimate_attempts(matcher: &PatternMatcher) -> f64 {
+    1.0 / attempt_probability(matcher)
+}
+
+// Chance that at least one of this many candidates matches: 1 - (1 - p)^attempts, computed through logarithms so
+// tiny per-attempt probabilities don't round away
+pub fn match_probability(matcher: &PatternMatcher, attempts: u64) -> f64 {
+    let p = attempt_probability(matcher);
+    -(attempts as f64 * (-p).ln_1p()).exp_m1()
+}
+
+// Attempts spent so far as a share of the expected attempts, e.g. 1.5 once a search has run half again as long as a
+// match takes on average. None when the expected attempts are unknown or no match is possible.
+pub fn luck_ratio(attempts: u64, expected_attempts: f64) -> Option<f64> {
+    (expected_attempts.is_finite() && expected_attempts > 0.0)
+        .then(|| attempts as f64 / expected_attempts)
+}
+
+// How far the attempts a match took lie from the expected attempts, in standard deviations of the geometric
+// distribution of attempts to a first match (mean 1/p, standard deviation sqrt(1 - p)/p); below 0 when early. The
+// deviation is about as large as the mean, so any match within roughly twice the expected attempts is within one.
+// None when the expected attempts are unknown or below 1.
+pub fn attempt_deviations(attempts: u64, expected_attempts: f64) -> Option<f64> {
+    (expected_attempts.is_finite() && expected_attempts >= 1.0).then(|| {
+        let p = 1.0 / expected_attempts;
+        let deviation = (1.0 - p).sqrt() * expected_attempts;
+        // a sure match takes exactly one attempt, so it never deviates
+        if deviation == 0.0 {
+            0.0
+        } else {
+            (attempts as f64 - expected_attempts) / deviation
+        }
+    })
+}
+
+// Chance that a search expecting this many attempts matches within this many, i.e. the share of searches that are
+// done by then. Past the expected attempts about 37% of searches are still going.
+pub fn chance_within(expected_attempts: f64, attempts: u64) -> f64 {
+    let p = 1.0 / expected_attempts;
+    -(attempts as f64 * (-p).ln_1p()).exp_m1()
+}
+
+// An easier variant of a search: the command-line change that gets there and how many attempts it expects
+pub struct Relaxation {
+    pub change: String,
+    pub expected_attempts: f64,
+}
+
+// The same search with other patterns, match types or character rules, keeping the fingerprint and range windows
+fn relaxed(
+    matcher: &PatternMatcher,
+    patterns: Vec<String>,
+    match_types: Vec<MatchType>,
+    case_sensitive: bool,
+    flex_level: u8,
+) -> PatternMatcher {
+    PatternMatcher::new(patterns, matcher.match_type, case_sensitive, false)
+        .with_match_types(match_types)
+        .with_flex_level(flex_level)
+        .with_canonical_case(matcher.canonical_case)
+        .with_fingerprint_len(matcher.fingerprint_len)
+        .with_at_range(matcher.at_range.clone())
+        .with_window(matcher.window.clone())
+        .with_max_distance(matcher.max_distance)
+}
+
+// Every pattern with `drop` characters removed, from the front for suffix matching and from the back otherwise;
+// None once a pattern would have no characters left
+fn shortened(matcher: &PatternMatcher, drop: usize) -> Option<Vec<String>> {
+    matcher
+        .patterns
+        .iter()
+        .zip(&matcher.match_types)
+        .map(|(pattern, match_type)| {
+            let keep = pattern.len().checked_sub(drop).filter(|&keep| keep > 0)?;
+            Some(match match_type {
+                MatchType::Suffix => pattern[pattern.len() - keep..].to_string(),
+                _ => pattern[..keep].to_string(),
+            })
+        })
+        .collect()
+}
+
+// Variants of the search that each give up one thing (a character or two of the pattern, exact case, strict
+// lookalikes, a fixed end of the address, one more wrong character), keeping those that are easier than the original. With an attempt budget
+// (e.g. the measured rate times the time the user was willing to wait), returns up to max of the variants expected
+// to finish within it, the least relaxed first; when none would, returns the max easiest instead.
+pub fn suggest_relaxations(
+    matcher: &PatternMatcher,
+    attempt_budget: f64,
+    max: usize,
+) -> Vec<Relaxation> {
+    let mut candidates = Vec::new();
+    for drop in 1..=2 {
+        if let Some(patterns) = shortened(matcher, drop) {
+            let change = match (patterns.as_slice(), matcher.match_types.as_slice()) {
+                ([pattern], [MatchType::Anagram]) => format!("--anagram \"{}\"", pattern),
+                ([pattern], _) => format!("-f \"{}\"", pattern),
+                _ => format!("every pattern {} character(s) shorter", drop),
+            };
+            candidates.push((
+                change,
+                relaxed(
+                    matcher,
+                    patterns,
+                    matcher.match_types.clone(),
+                    matcher.case_sensitive,
+                    matcher.flex_level,
+                ),
+            ));
+        }
+    }
+    if matcher.case_sensitive {
+        candidates.push((
+            "-s false".to_string(),
+            relaxed(
+                matcher,
+                matcher.patterns.clone(),
+                matcher.match_types.clone(),
+                false,
+                matcher.flex_level,
+            ),
+        ));
+    }
+    if matcher.case_sensitive || matcher.flex_level < MAX_FLEX_LEVEL {
+        let change = if matcher.case_sensitive {
+            format!("-s false -l true --flex-level {}", MAX_FLEX_LEVEL)
+        } else if matcher.collapse_case {
+            format!(
+                "-l true --flex-level {} instead of --collapse-case",
+                MAX_FLEX_LEVEL
+            )
+        } else {
+            format!("-l true --flex-level {}", MAX_FLEX_LEVEL)
+        };
+        candidates.push((
+            change,
+            relaxed(
+                matcher,
+                matcher.patterns.clone(),
+                matcher.match_types.clone(),
+                false,
+                MAX_FLEX_LEVEL,
+            ),
+        ));
+    }
+    let fixed_end = |t: &MatchType| matches!(t, MatchType::Prefix | MatchType::Suffix);
+    if matcher.match_types.iter().any(fixed_end) {
+        candidates.push((
+            "-m either".to_string(),
+            relaxed(
+                matcher,
+                matcher.patterns.clone(),
+                matcher
+                    .match_types
+                    .iter()
+                    .map(|t| if fixed_end(t) { MatchType::Either } else { *t })
+                    .collect(),
+                matcher.case_sensitive,
+                matcher.flex_level,
+            ),
+        ));
+    }
+
+    let distance = matcher.max_distance + 1;
+    // a match has to get at least one character right, or every address would do
+    if matcher
+        .patterns
+        .iter()
+        .all(|pattern| pattern.len() > distance)
+    {
+        candidates.push((
+            format!("--max-distance {}", distance),
+            relaxed(
+                matcher,
+                matcher.patterns.clone(),
+                matcher.match_types.clone(),
+                matcher.case_sensitive,
+                matcher.flex_level,
+            )
+            .with_max_distance(distance),
+        ));
+    }
+
+    let original = estimate_attempts(matcher);
+    let mut relaxations: Vec<Relaxation> = candidates
+        .into_iter()
+        .map(|(change, variant)| Relaxation {
+            change,
+            expected_attempts: estimate_attempts(&variant),
+        })
+        .filter(|r| r.expected_attempts < original)
+        .collect();
+    relaxations.sort_by(|a, b| a.expected_attempts.total_cmp(&b.expected_attempts));
+    let feasible = relaxations
+        .iter()
+        .filter(|r| r.expected_attempts <= attempt_budget)
+        .count();
+    if feasible > 0 {
+        // the hardest variants that still fit are the ones closest to what was asked for
+        relaxations.truncate(feasible);
+        relaxations.reverse();
+    }
+    relaxations.truncate(max);
+    relaxations
+}
+
+// Rough label for how hard a search is, by expected attempts
+pub fn difficulty_label(expected_attempts: f64) -> &'static str {
+    match expected_attempts {
+        a if a < 1e6 => "trivial",
+        a if a < 1e8 => "easy",
+        a if a < 1e10 => "moderate",
+        a if a < 1e12 => "hard",
+        _ => "extreme",
+    }
+}
+
+// Measures candidates per second by running the real search loop against the matcher for the given duration
+// on the current rayon pool. Matches are ignored, so the rate includes generation, encoding and matching.
+pub fn benchmark_rate<M, S, F>(
+    matcher: &M,
+    threads: usize,
+    make_source: F,
+    duration: Duration,
+) -> f64
+where
+    M: Matcher + Sync,
+    S: KeypairSource,
+    F: Fn(usize) -> S + Sync,
+{
+    let control = SearchControl::new();
+    let start = Instant::now();
+    let attempts = std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(duration);
+            control.stop();
+        });
+        search_each(matcher, threads, make_source, &control, |_, _, _| false)
+    });
+    attempts as f64 / start.elapsed().as_secs_f64()
+}
+
+// Thread counts --autotune benchmarks: one, half and all of the logical cores, without repeats
+pub fn autotune_thread_counts(logical: usize) -> Vec<usize> {
+    let mut counts = vec![1, (logical / 2).max(1), logical.max(1)];
+    counts.dedup();
+    counts
+}
+
+// A thread count whose rate is within this share of the fastest one counts as just as fast. Short benchmarks vary by
+// a few percent from run to run, so smaller differences say nothing about which count is better.
+pub const AUTOTUNE_TOLERANCE: f64 = 0.05;
+
+// Fewest threads whose measured rate is within AUTOTUNE_TOLERANCE of the best, given (threads, addresses/second)
+// pairs. Past that count extra threads only compete for the same cores, memory bandwidth or scheduler time.
+pub fn recommend_threads(rates: &[(usize, f64)]) -> usize {
+    let best = rates.iter().map(|&(_, rate)| rate).fold(0.0, f64::max);
+    rates
+        .iter()
+        .filter(|&&(_, rate)| rate >= best * (1.0 - AUTOTUNE_TOLERANCE))
+        .map(|&(threads, _)| threads)
+        .min()
+        .unwrap_or(1)
+}
+
+// How much slower a search with flexible matching ran than the same search matching exactly, in percent of the
+// exact rate: positive when the lookalike tables cost throughput, negative when the flexible run came out faster
+pub fn flexible_overhead(exact_rate: f64, flexible_rate: f64) -> f64 {
+    if exact_rate <= 0.0 {
+        return 0.0;
+    }
+    (exact_rate - flexible_rate) / exact_rate * 100.0
+}
+
+// Energy in kilowatt-hours a machine drawing this many watts uses over the expected time of a search at this rate;
+// None without a measured rate
+pub fn expected_energy_kwh(expected_attempts: f64, rate: f64, watts: f64) -> Option<f64> {
+    if !rate.is_finite() || rate <= 0.0 {
+        return None;
+    }
+    Some(watts * expected_attempts / rate / 3_600_000.0)
+}
+
+// Formats an amount of energy using the largest sensible unit, e.g. "4.2 kWh"
+pub fn format_energy(kwh: f64) -> String {
+    const UNITS: [(&str, f64); 3] = [("GWh", 1e6), ("MWh", 1e3), ("kWh", 1.0)];
+    for (unit, size) in UNITS {
+        if kwh >= size {
+            return format!("{:.1} {}", kwh / size, unit);
+        }
+    }
+    format!("{:.1} Wh", kwh * 1000.0)
+}
+
+// Formats a duration in seconds using the largest sensible unit, e.g. "3.2 hours"
+pub fn format_duration(secs: f64) -> String {
+    const UNITS: [(&str, f64); 5] = [
+        ("years", 365.25 * 86400.0),
+        ("days", 86400.0),
+        ("hours", 3600.0),
+        ("minutes", 60.0),
+        ("seconds", 1.0),
+    ];
+    for (unit, size) in UNITS {
+        if secs >= size {
+            return format!("{:.1} {}", secs / size, unit);
+        }
+    }
+    format!("{:.1} seconds", secs)
+}
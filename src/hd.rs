@@ -0,0 +1,49 @@
+use crate::source::{KeypairSource, fill_os_random};
+use bip39::Mnemonic;
+use solana_derivation_path::DerivationPath;
+use solana_keypair::{Keypair, seed_derivable::keypair_from_seed_and_derivation_path};
+
+// Path used by wallets such as Phantom and Solflare for the first account of a seed phrase
+pub const HD_DERIVATION_PATH: &str = "m/44'/501'/0'/0'";
+
+// Generates a fresh 12 word seed phrase and derives the keypair a wallet would show as its first account.
+// The Solana address is the raw ed25519 public key of that child key, so no further conversion is needed.
+pub fn generate_hd_keypair() -> (Keypair, Mnemonic) {
+    try_generate_hd_keypair().unwrap_or_else(|e| panic!("{}", e))
+}
+
+// generate_hd_keypair, reporting a failure of the OS random number generator after retrying it
+pub fn try_generate_hd_keypair() -> Result<(Keypair, Mnemonic), String> {
+    let mut entropy = [0u8; 16];
+    fill_os_random(&mut entropy)?;
+    let mnemonic = Mnemonic::from_entropy(&entropy).expect("16 bytes is a valid entropy length");
+    let seed = mnemonic.to_seed("");
+    let keypair = keypair_from_seed_and_derivation_path(
+        &seed,
+        Some(DerivationPath::new_bip44(Some(0), Some(0))),
+    )
+    .expect("bip44 derivation from a 64 byte seed cannot fail");
+    Ok((keypair, mnemonic))
+}
+
+// Source for --hd searches; remembers the phrase behind the last keypair so a match can be reported with it
+#[derive(Default)]
+pub struct HdSource {
+    mnemonic: Option<Mnemonic>,
+}
+
+impl KeypairSource for HdSource {
+    fn next(&mut self) -> Keypair {
+        self.try_next().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_next(&mut self) -> Result<Keypair, String> {
+        let (keypair, mnemonic) = try_generate_hd_keypair()?;
+        self.mnemonic = Some(mnemonic);
+        Ok(keypair)
+    }
+
+    fn last_mnemonic(&self) -> Option<&Mnemonic> {
+        self.mnemonic.as_ref()
+    }
+}
@@ -0,0 +1,206 @@
+use crate::output::{FoundKeypair, json_record, open_private};
+use serde_json::json;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// Layout of a job directory shared by processes searching for the same thing, e.g. on a network drive:
+//   claimed-<ID>  one per process that joined the job, holding its id and patterns
+//   result.json   the keypair of the first match, as a json record with its secret
+//   done          written right after result.json, holding the id of the process that wrote it
+// Processes poll for done while they search and stop once it appears.
+pub const CLAIMED_PREFIX: &str = "claimed-";
+pub const RESULT_FILE: &str = "result.json";
+pub const DONE_MARKER: &str = "done";
+
+// One process's place in a job directory
+pub struct JobDir {
+    dir: PathBuf,
+    id: String,
+}
+
+impl JobDir {
+    // Joins the job in dir under id by writing its claimed marker. A job that is already done, or an id that has
+    // already claimed a place, is refused.
+    pub fn join(dir: &Path, id: &str, patterns: &[String]) -> Result<Self, String> {
+        if !dir.is_dir() {
+            return Err(format!("Job directory {} does not exist", dir.display()));
+        }
+        let job = JobDir {
+            dir: dir.to_path_buf(),
+            id: id.to_string(),
+        };
+        if job.is_done() {
+            return Err(format!(
+                "The job in {} is already done; remove {} and {} to search again",
+                dir.display(),
+                RESULT_FILE,
+                DONE_MARKER
+            ));
+        }
+        let claimed = dir.join(format!("{}{}", CLAIMED_PREFIX, id));
+        let marker = json!({ "id": id, "patterns": patterns });
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&claimed)
+            .and_then(|mut file| writeln!(file, "{}", marker))
+            .map_err(|e| format!("Cannot claim a place in {}: {}", claimed.display(), e))?;
+        Ok(job)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    // The marker a search polls for (see SearchControl::with_stop_file)
+    pub fn done_path(&self) -> PathBuf {
+        self.dir.join(DONE_MARKER)
+    }
+
+    pub fn result_path(&self) -> PathBuf {
+        self.dir.join(RESULT_FILE)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done_path().exists()
+    }
+
+    // The id of the process that finished the job, once it is done
+    pub fn finished_by(&self) -> Option<String> {
+        std::fs::read_to_string(self.done_path())
+            .ok()
+            .map(|id| id.trim().to_string())
+    }
+
+    // Records a match as the job's result, then marks the job done. The result file is created only if missing, so
+    // when two processes find a match at the same time the first one keeps it; Ok(false) means another process
+    // finished first and this match wasn't recorded.
+    pub fn finish(&self, found: &FoundKeypair) -> io::Result<bool> {
+        let mut file = match open_private(
+            OpenOptions::new().write(true).create_new(true),
+            &self.result_path(),
+        ) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        writeln!(file, "{}", json_record(found, true))?;
+        file.sync_all()?;
+        std::fs::write(self.done_path(), format!("{}\n", self.id))?;
+        Ok(true)
+    }
+}
+
+// An id for this process that other machines sharing the directory won't use: the host name and the process id
+pub fn process_id() -> String {
+    format!("{}-{}", host_name(), std::process::id())
+}
+
+#[cfg(unix)]
+fn host_name() -> String {
+    let mut buf = [0u8; 256];
+    // SAFETY: the buffer is valid for its whole length, and gethostname writes at most that many bytes
+    let ok = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } == 0;
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    match std::str::from_utf8(&buf[..len]) {
+        Ok(name) if ok && !name.is_empty() => name.to_string(),
+        _ => "host".to_string(),
+    }
+}
+
+#[cfg(not(unix))]
+fn host_name() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_else(|_| "host".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::{MatchType, PatternMatcher};
+    use crate::output::stored_pubkeys;
+    use crate::search::{SearchControl, SearchOutcome, VanitySearcher};
+    use crate::source::SequenceSource;
+    use solana_keypair::Keypair;
+    use solana_signer::Signer;
+
+    // Seeds planted for the first of two searchers sharing a job directory. The second one never meets a match, so it
+    // must stop on the done marker the first one writes, leaving the first one's keypair as the job's result.
+    const JOB_DIR_VECTORS: &[u8] = &[1, 3];
+
+    // Attempts the second searcher of a job may make before the done marker is taken as ignored
+    const JOB_DIR_ATTEMPT_LIMIT: u64 = 500_000;
+
+    // Runs two searchers on pools of their own against one job directory in dir, the first with a match planted at the
+    // seed and the second with none, and checks what each of them and the directory end up with
+    fn share_job(dir: &Path, seed: u8) {
+        let address = Keypair::new_from_array([seed; 32]).pubkey().to_string();
+        let patterns = vec![address[..4].to_string()];
+        let matcher = PatternMatcher::new(patterns.clone(), MatchType::Prefix, true, false);
+        let first = JobDir::join(dir, "first", &patterns).unwrap();
+        let second = JobDir::join(dir, "second", &patterns).unwrap();
+        let claimed = std::fs::read_dir(dir)
+            .unwrap()
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(CLAIMED_PREFIX)
+            })
+            .count();
+        assert_eq!(claimed, 2, "claimed markers");
+
+        let (recorded, loser) = std::thread::scope(|scope| {
+            let loser = scope.spawn(|| -> SearchOutcome {
+                let control = SearchControl::with_attempt_limit(JOB_DIR_ATTEMPT_LIMIT)
+                    .with_stop_file(second.done_path());
+                let searcher = VanitySearcher::new(1).unwrap();
+                searcher.search(&matcher, |_| SequenceSource::new(vec![[0; 32]]), &control)
+            });
+            let searcher = VanitySearcher::new(1).unwrap();
+            let outcome = searcher.search(
+                &matcher,
+                |_| SequenceSource::new(vec![[0; 32], [seed; 32]]),
+                &SearchControl::new().with_stop_file(first.done_path()),
+            );
+            let (keypair, mnemonic) = outcome.found.expect("the first searcher found nothing");
+            let found = FoundKeypair {
+                pattern: patterns[0].clone(),
+                keypair,
+                mnemonic,
+                attempts: outcome.attempts,
+                elapsed_secs: 0.0,
+                commit_nonce: None,
+            };
+            (first.finish(&found).unwrap(), loser.join().unwrap())
+        });
+
+        assert!(recorded, "the first searcher's match wasn't recorded");
+        assert!(
+            loser.found.is_none() && loser.attempts < JOB_DIR_ATTEMPT_LIMIT,
+            "the second searcher didn't stop on the done marker ({} attempts)",
+            loser.attempts
+        );
+        assert_eq!(stored_pubkeys(&second.result_path()).unwrap(), [address]);
+        assert_eq!(second.finished_by().as_deref(), Some(first.id()));
+        assert!(
+            JobDir::join(dir, "third", &patterns).is_err(),
+            "a finished job accepted another process"
+        );
+    }
+
+    #[test]
+    fn job_directory_stops_every_searcher() {
+        for &seed in JOB_DIR_VECTORS {
+            let dir =
+                std::env::temp_dir().join(format!("vanity-job-{}-{}", std::process::id(), seed));
+            std::fs::create_dir_all(&dir).unwrap();
+            let shared = std::panic::catch_unwind(|| share_job(&dir, seed));
+            let _ = std::fs::remove_dir_all(&dir);
+            if let Err(panic) = shared {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+}
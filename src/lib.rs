@@ -0,0 +1,20 @@
+pub mod airdrop;
+pub mod batch;
+pub mod best;
+pub mod commit;
+pub mod db;
+pub mod distributed;
+pub mod estimate;
+pub mod hd;
+pub mod jobdir;
+pub mod matcher;
+pub mod output;
+pub mod progress;
+pub mod remote;
+pub mod search;
+pub mod secondary;
+pub mod selftest;
+pub mod source;
+pub mod stats;
+pub mod throttle;
+mod vectors;
@@ -1,40 +1,593 @@
-use clap::{Parser, ValueEnum};
-use rayon::{prelude::*, ThreadPoolBuilder};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use rayon::ThreadPoolBuilder;
 use solana_keypair::Keypair;
 use solana_signer::Signer;
+use solana_vanity_address::airdrop::{Cluster, request_airdrop};
+use solana_vanity_address::batch::{
+    BatchMatcher, dedupe_patterns, resume_patterns, split_pattern_line,
+};
+use solana_vanity_address::best::{
+    BestTracker, leading_run, search_best_effort, search_keeping_closest,
+};
+use solana_vanity_address::commit::{NONCE_LEN, commitment, new_nonce, opens};
+use solana_vanity_address::db::{insert_keypair, open_db, unix_now};
+use solana_vanity_address::distributed::{Coordinator, Joiner};
+use solana_vanity_address::estimate::{
+    AUTOTUNE_TOLERANCE, attempt_deviations, autotune_thread_counts, benchmark_rate, chance_within,
+    difficulty_label, estimate_attempts, expected_energy_kwh, flexible_overhead, format_duration,
+    format_energy, luck_ratio, match_probability, recommend_threads, suggest_relaxations,
+};
+use solana_vanity_address::hd::{HD_DERIVATION_PATH, HdSource};
+use solana_vanity_address::jobdir::{JobDir, process_id};
+use solana_vanity_address::matcher::{
+    ByteSum, CharMatch, DEFAULT_FINGERPRINT_LEN, DEFAULT_FLEX_LEVEL, DEFAULT_WINDOW_END,
+    HexPrefixMatcher, MAX_BYTE_SUM, MAX_FLEX_LEVEL, MatchType, NumericPrefixMatcher,
+    PatternMatcher, accepted_chars, ambiguous_chars, byte_sum, classify_match, like_patterns,
+    match_comparison, split_match_type, to_decimal, to_hex,
+};
+use solana_vanity_address::output::{
+    Derivation, DistinctAddresses, FoundKeypair, OutputFormat, RAW_KEYPAIR_LEN, RotatingJsonl,
+    TokenProgram, append_jsonl, check_mint_keypair_file, check_output_dir, check_template,
+    env_assignments, format_file_name, format_highlighted, format_highlighted_color, group_address,
+    grouped_span, json_record, mint_keypair_file_name, missing_from_jsonl, paper_wallet,
+    parse_age_recipient, read_raw_keypair, render_template, stored_pubkeys, verify_keypair_file,
+    write_age_keypair_file, write_csv, write_format_file, write_keypair_file, write_qr_png,
+    write_raw, write_raw_keypair_file,
+};
+use solana_vanity_address::progress::{
+    ExitReason, RateMonitor, THROTTLE_DROP, eta_secs, progress_json, prometheus_metrics,
+    run_summary, write_metrics_file,
+};
+use solana_vanity_address::remote::{allowed_url, fetch_pattern_list};
+use solana_vanity_address::search::{
+    Matcher, SearchControl, probe_pool, search, search_each, start_global_pool,
+};
+use solana_vanity_address::secondary::{DualMatcher, SecondaryEncoding, SecondaryMatcher};
+use solana_vanity_address::selftest::{run_vectors, vector_count};
+use solana_vanity_address::source::{
+    ChaChaSource, CounterSource, KeypairSource, OsRngSource, SeededSource, read_file_seed,
+};
+use solana_vanity_address::stats::{config_key, record_run};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io::{self, IsTerminal, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::time::Instant;
+use std::sync::{Arc, Mutex, OnceLock, mpsc};
+use std::time::{Duration, Instant};
 
 #[derive(Parser, Debug)]
 #[command(name = "solana-vanity-address")]
 #[command(about = "A CLI tool for generating solana vanity addresses")]
+#[command(subcommand_negates_reqs = true)]
+// files found keypairs can be written to: --output, or the output files named in --patterns-file
+#[command(group(clap::ArgGroup::new("destination").args(["output", "patterns_file", "mint"]).multiple(true)))]
+// where a batch of patterns comes from: a local file or a URL
+#[command(group(clap::ArgGroup::new("patterns_list").args(["patterns_file", "patterns_url"])))]
 struct Args {
-    // pattern to find
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    // TOML file whose values replace the built-in defaults; flags given on the command line still win
+    #[arg(long, value_name = "PATH", global = true)]
+    config: Option<PathBuf>,
+
+    // pattern to find; separate alternatives with '|' to accept any of them, each optionally ending in ':type' to
+    // set its own match type
+    #[arg(short = 'f', long, required_unless_present_any = ["patterns_list", "mine_best", "hex_prefix", "numeric_prefix", "sysinfo", "autotune", "like", "anagram"], conflicts_with_all = ["patterns_list", "mine_best", "hex_prefix", "like", "anagram"])]
+    find: Option<String>,
+
+    // search for an address sharing its first or last characters with this existing address, per --match-type
+    #[arg(long, value_name = "ADDRESS", value_parser = validate_like, conflicts_with_all = ["patterns_list", "mine_best", "hex_prefix", "numeric_prefix", "alphabet"])]
+    like: Option<String>,
+
+    // how many characters of the --like address to share
+    #[arg(long, value_name = "N", default_value_t = 4, value_parser = validate_like_len, requires = "like")]
+    like_len: usize,
+
+    // file with one pattern per line; finds one address for every pattern
+    #[arg(long)]
+    patterns_file: Option<PathBuf>,
+
+    // fetch the batch of patterns from this https URL instead of a file, e.g. a list shared by a team
+    #[arg(long, value_name = "URL", value_parser = validate_patterns_url)]
+    patterns_url: Option<String>,
+
+    // skip batch patterns whose keypair an earlier run already stored in --output or the pattern's own output file
+    #[arg(long, default_value_t = false, requires = "patterns_list")]
+    resume_patterns: bool,
+
+    // reject addresses containing this substring anywhere; may be given several times
+    #[arg(long, value_parser = validate_exclude)]
+    exclude: Vec<String>,
+
+    // instead of searching for a pattern, try this many addresses and keep the one with the longest run of
+    // repeated leading characters
+    #[arg(long, value_name = "ATTEMPTS", conflicts_with_all = ["patterns_list", "coordinator", "join"])]
+    mine_best: Option<u64>,
+
+    // run exactly this many attempts and keep the address with the most leading characters of a pattern, even if none
+    // matches fully
+    #[arg(long, value_name = "ATTEMPTS", conflicts_with_all = ["patterns_list", "mine_best", "hex_prefix", "numeric_prefix", "coordinator", "join", "stream", "timeout", "secondary_encoding"])]
+    best_effort: Option<u64>,
+
+    // match the hex form of the raw public key bytes instead of the base58 address
+    #[arg(long, value_name = "HEX", value_parser = validate_hex_prefix, conflicts_with_all = ["patterns_list", "mine_best", "coordinator", "join", "stream"])]
+    hex_prefix: Option<String>,
+
+    // match the decimal digits of the public key read as a 256-bit big-endian number instead of the base58 address
+    #[arg(long, value_name = "DIGITS", value_parser = validate_numeric_prefix, conflicts_with_all = ["find", "patterns_list", "mine_best", "coordinator", "join", "stream", "hex_prefix", "secondary_encoding", "alphabet", "metrics_file", "timeout", "airdrop"])]
+    numeric_prefix: Option<String>,
+
+    // also require a pattern in this second encoding of the public key, e.g. the form another chain displays
+    #[arg(long, value_enum, conflicts_with_all = ["patterns_list", "stream", "hex_prefix", "mine_best", "coordinator", "join", "alphabet"])]
+    secondary_encoding: Option<SecondaryEncoding>,
+
+    // pattern the --secondary-encoding form must contain anywhere; defaults to the -f pattern
+    #[arg(long, requires = "secondary_encoding")]
+    secondary_pattern: Option<String>,
+
+    // keep searching after the first match and print every match as it is found
+    #[arg(long, default_value_t = false, conflicts_with_all = ["patterns_list", "coordinator", "join", "mine_best"])]
+    stream: bool,
+
+    // with --stream, stop after this many matches
+    #[arg(long, requires = "stream", value_parser = validate_count)]
+    count: Option<u64>,
+
+    // with --stream, how many found keypairs may wait for output before workers pause
+    #[arg(long, default_value_t = 16, value_parser = validate_buffer, requires = "stream")]
+    buffer: usize,
+
+    // only accept addresses made of at least this many different characters
+    #[arg(long, value_parser = validate_min_distinct)]
+    min_distinct: Option<usize>,
+
+    // only accept addresses whose characters have at least this much Shannon entropy, in bits per character
+    #[arg(long, value_name = "BITS", value_parser = validate_entropy)]
+    min_entropy: Option<f64>,
+
+    // only accept addresses whose characters have at most this much Shannon entropy, in bits per character
+    #[arg(long, value_name = "BITS", value_parser = validate_entropy)]
+    max_entropy: Option<f64>,
+
+    // only accept addresses that start with a letter rather than a digit
+    #[arg(long, default_value_t = false, conflicts_with_all = ["mine_best", "hex_prefix", "numeric_prefix", "alphabet"])]
+    no_leading_digit: bool,
+
+    // only accept addresses in which the matched pattern occurs at least this many times, without overlaps
+    #[arg(long, value_name = "K", value_parser = validate_min_occurrences, conflicts_with_all = ["mine_best", "hex_prefix", "numeric_prefix", "max_distance"])]
+    min_occurrences: Option<usize>,
+
+    // only accept public keys whose 32 raw bytes add up to this value, or leave it as remainder with --byte-sum-mod
+    #[arg(long, value_name = "VALUE", value_parser = validate_byte_sum, conflicts_with_all = ["mine_best", "hex_prefix", "numeric_prefix"])]
+    byte_sum: Option<u32>,
+
+    // compare the remainder of the byte sum divided by this with --byte-sum, e.g. 256
+    #[arg(long, value_name = "M", value_parser = validate_byte_sum_mod, requires = "byte_sum")]
+    byte_sum_mod: Option<u32>,
+
+    // where on the ed25519 curve found addresses must lie; generated keypairs are always on it
+    #[arg(long, value_enum, value_name = "CURVE")]
+    require: Option<Curve>,
+
+    // 58 character alphabet patterns are written in and found addresses are shown in, instead of Solana's base58
+    #[arg(long, value_parser = validate_alphabet, conflicts_with_all = ["hex_prefix", "mine_best"])]
+    alphabet: Option<String>,
+
+    // -v prints how many candidates each worker thread tried once the search ends; -vv also every few seconds while
+    // searching, to spot a thread that falls behind
+    #[arg(short = 'v', long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    // print the attempt total and search rate every few seconds while searching
+    #[arg(long, default_value_t = false, conflicts_with = "mine_best")]
+    progress: bool,
+
+    // write the attempt total, rate, elapsed time and expected time to a match as one JSON object per line to stderr
+    // every few seconds
+    #[arg(long, default_value_t = false, conflicts_with_all = ["mine_best", "hex_prefix", "numeric_prefix"])]
+    progress_json: bool,
+
+    // write the attempt total, rate and elapsed time in Prometheus text format every few seconds; '-' is stdout
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["mine_best", "hex_prefix"])]
+    metrics_file: Option<PathBuf>,
+
+    // when the search ends, however it ends, print a one line JSON summary of the run to stderr
+    #[arg(long, default_value_t = false, conflicts_with_all = ["mine_best", "hex_prefix", "numeric_prefix", "stream"])]
+    summary: bool,
+
+    // write the run summary to this file instead of stderr
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["mine_best", "hex_prefix", "numeric_prefix", "stream"])]
+    summary_file: Option<PathBuf>,
+
+    // add this run's attempts and time to the totals kept in this file for the same patterns and settings
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["mine_best", "hex_prefix", "numeric_prefix", "stream"])]
+    stats_file: Option<PathBuf>,
+
+    // give up after this many seconds and suggest easier variants of the search
+    #[arg(long, value_name = "SECS", value_parser = validate_timeout, conflicts_with_all = ["hex_prefix", "stream", "mine_best"])]
+    timeout: Option<u64>,
+
+    // don't offer the closest miss shortly before --timeout runs out
+    #[arg(long, default_value_t = false, requires = "timeout")]
+    no_prompt: bool,
+
+    // print the detected cores and a recommended thread count; searches afterwards only if a pattern was given
+    #[arg(long, default_value_t = false)]
+    sysinfo: bool,
+
+    // benchmark 1, half and all cores before searching and recommend a thread count; auto also uses it
+    #[arg(long, value_enum, value_name = "MODE", num_args = 0..=1, default_missing_value = "suggest")]
+    autotune: Option<Autotune>,
+
+    // keep each worker thread at about this percentage of a core by pausing between chunks of candidates
+    #[arg(long, value_name = "PERCENT", value_parser = validate_cpu_limit)]
+    cpu_limit: Option<u8>,
+
+    // number of threads to create; negative leaves that many cores free
+    #[arg(short = 't', long, default_value_t = default_threads(), value_parser = validate_threads, allow_negative_numbers = true)]
+    threads: usize,
+
+    // match type of patterns without a ':type' suffix
+    #[arg(short = 'm', long, value_enum, default_value_t = MatchType::Prefix)]
+    match_type: MatchType,
+
+    // match anywhere in the address, but only where the pattern starts a word; same as --match-type word
+    #[arg(long, default_value_t = false, conflicts_with = "match_type")]
+    pattern_at_word_boundary: bool,
+
+    // EXPERIMENTAL: search for -f as given, without the length and character checks, e.g. to watch what happens with a
+    // pattern no address can have; such a search may never end
+    #[arg(long, default_value_t = false, requires = "find")]
+    no_validate: bool,
+
+    // match these characters in any order anywhere in the address; same as -f LETTERS --match-type anagram
+    #[arg(long, value_name = "LETTERS", value_parser = validate_anagram, conflicts_with_all = ["patterns_list", "mine_best", "hex_prefix", "numeric_prefix", "like", "match_type", "pattern_at_word_boundary"])]
+    anagram: Option<String>,
+
+    // number of leading characters searched in fingerprint mode
+    #[arg(long, value_parser = validate_fingerprint_len)]
+    fingerprint_len: Option<usize>,
+
+    // positions the pattern may start at in range mode, e.g. 0..3
+    #[arg(long, value_name = "START..END", value_parser = validate_at_range)]
+    at_range: Option<Range<usize>>,
+
+    // positions the whole match has to lie within in window mode, e.g. 5..15
+    #[arg(long, value_name = "START..END", value_parser = validate_window)]
+    window: Option<Range<usize>>,
+
+    // enable case sensitivity
+    #[arg(short = 's', long, default_value_t = false, action = clap::ArgAction::Set)]
+    case_sensitivity: bool,
+
+    // with case sensitivity off, only accept matches whose letters are in the case the pattern was typed in
+    #[arg(long, default_value_t = false, conflicts_with = "alphabet")]
+    canonical_case: bool,
+
+    // ignore case by comparing the address and pattern in upper case form, exactly; turns case sensitivity and lookalikes off
+    #[arg(long, default_value_t = false, conflicts_with_all = ["alphabet", "case_sensitivity", "canonical_case", "flexible_chars", "flex_level"])]
+    collapse_case: bool,
+
+    // enable flexible character set
+    #[arg(short = 'l', long, default_value_t = true, action = clap::ArgAction::Set)]
+    flexible_chars: bool,
+
+    // how loose flexible lookalikes may be, from 0 (none) to 3
+    #[arg(long, default_value_t = DEFAULT_FLEX_LEVEL, value_parser = validate_flex_level)]
+    flex_level: u8,
+
+    // accept matches with up to this many pattern characters wrong
+    #[arg(long, value_name = "K", default_value_t = 0, conflicts_with_all = ["mine_best", "hex_prefix", "numeric_prefix"])]
+    max_distance: usize,
+
+    // output formats for found keypairs, comma separated: the first goes to stdout, each further one to its own file
+    #[arg(
+        id = "format",
+        long = "format",
+        value_enum,
+        value_delimiter = ',',
+        default_value = "human"
+    )]
+    formats: Vec<OutputFormat>,
+
+    // the first of --format, which stdout and the status lines follow
+    #[arg(skip = OutputFormat::Human)]
+    format: OutputFormat,
+
+    // print each found keypair as this line instead, filling in {pubkey}, {secret}, {attempts}, {elapsed} and {pattern}
+    #[arg(long, value_name = "FORMAT", value_parser = validate_template, conflicts_with_all = ["format", "mine_best"])]
+    template: Option<String>,
+
+    // refuse to search for patterns that resemble well-known addresses
+    #[arg(long, default_value_t = false)]
+    strict: bool,
+
+    // refuse patterns with easily confused characters (e.g. '1' and 'i') unless lookalikes are accepted
+    #[arg(long, default_value_t = false, conflicts_with = "alphabet")]
+    strict_base58: bool,
+
+    // match the first account derived from a new seed phrase instead of a standalone keypair
+    #[arg(long, default_value_t = false)]
+    hd: bool,
+
+    // DEBUGGING ONLY: derive keypairs from a counter starting here instead of randomness, so a search can be
+    // reproduced exactly; the keys are predictable and must never hold funds
+    #[arg(long, value_name = "START", conflicts_with_all = ["hd", "coordinator", "join"])]
+    debug_counter: Option<u64>,
+
+    // derive keypairs from the SHA-256 of this file instead of randomness, so anyone with the file can replay the
+    // search candidate for candidate; the keys are known to everyone who has the file
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["debug_counter", "hd", "entropy", "coordinator", "join"])]
+    seed_from_file: Option<PathBuf>,
+
+    // the seed derived from --seed-from-file, read before the search starts
+    #[arg(skip)]
+    file_seed: Option<[u8; 32]>,
+
+    // random number generator keypairs are generated from
+    #[arg(long, value_enum, default_value_t = Entropy::Os, conflicts_with = "hd")]
+    entropy: Entropy,
+
+    // accept joiners on this address and stop everyone once any process finds a match
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["join", "patterns_list"])]
+    coordinator: Option<String>,
+
+    // search on behalf of the coordinator at this address
+    #[arg(long, value_name = "ADDR", conflicts_with = "patterns_list")]
+    join: Option<String>,
+
+    // share the search with other processes through this directory; the first match stops them all
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["patterns_list", "stream", "mine_best", "hex_prefix", "numeric_prefix", "best_effort", "coordinator", "join"])]
+    job_dir: Option<PathBuf>,
+
+    // write found keypairs to this file
+    #[arg(short = 'o', long)]
+    output: Option<PathBuf>,
+
+    // insert found keypairs into this SQLite database, creating it and its table if missing
+    #[arg(long, value_name = "PATH", conflicts_with = "mine_best")]
+    db: Option<PathBuf>,
+
+    // label the found keypair as a token mint and save it as <ADDRESS>-mint.json unless --output names the file
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["patterns_list", "stream", "mine_best", "hex_prefix", "numeric_prefix", "append"]
+    )]
+    mint: bool,
+
+    // make the --mint keypair a Token-2022 mint: check the saved file can sign for it and print the spl-token
+    // --program-2022 command
+    #[arg(long = "token-2022", default_value_t = false, requires = "mint")]
+    token_2022: bool,
+
+    // create the output file's directory if it doesn't exist
+    #[arg(long, default_value_t = false, requires = "destination")]
+    mkdir: bool,
+
+    // mark the matched part of the address with brackets instead of color
+    #[arg(long, default_value_t = false)]
+    no_color: bool,
+
+    // show the previewed address in groups of this many characters; matching still runs on the plain address
+    #[arg(long, value_name = "N", value_parser = validate_group)]
+    group: Option<usize>,
+
+    // leave the secret key and seed phrase out of the printed result; the output file still gets them
+    #[arg(long, default_value_t = false, conflicts_with = "mine_best")]
+    no_secret: bool,
+
+    // under a found address, print the pattern and the matched characters side by side with every substitution made
+    #[arg(long, default_value_t = false, conflicts_with = "mine_best")]
+    compare_match: bool,
+
+    // print the seed, the public key derived from it and its base58 form under each found keypair
+    #[arg(long, default_value_t = false, conflicts_with_all = ["no_secret", "mine_best"])]
+    show_derivation: bool,
+
+    // read the output file back after writing and check the keypair survived intact
+    #[arg(long, default_value_t = false, requires = "destination")]
+    verify_after_write: bool,
+
+    // append found keypairs to the output file as JSON lines instead of writing a single keypair file
+    #[arg(long, default_value_t = false, requires = "output")]
+    append: bool,
+
+    // with --stream, continue in a new numbered output file once the current one would grow past this many bytes
+    #[arg(long, value_name = "BYTES", value_parser = validate_rotate_size, requires_all = ["stream", "append"])]
+    rotate_size: Option<u64>,
+
+    // with --stream, continue in a new numbered output file once the current one holds this many keypairs
+    #[arg(long, value_name = "N", value_parser = validate_rotate_count, requires_all = ["stream", "append"])]
+    rotate_count: Option<u64>,
+
+    // encrypt the --output keypair file to this age recipient (age1...) instead of writing it in the clear
+    #[arg(long, value_name = "RECIPIENT", value_parser = validate_age_recipient, requires = "output", conflicts_with_all = ["append", "verify_after_write", "mint", "stream", "patterns_list", "mine_best"])]
+    age_recipient: Option<String>,
+
+    // print a SHA-256 commitment to each found secret key, keeping the nonce that opens it in the --append file
+    #[arg(long, default_value_t = false, requires = "append", conflicts_with_all = ["mine_best", "hex_prefix", "numeric_prefix"])]
+    commit: bool,
+
+    // after finding the address, request this many SOL for it from the --cluster faucet
+    #[arg(long, value_name = "SOL", value_parser = validate_airdrop, requires = "cluster", conflicts_with_all = ["patterns_list", "stream", "mine_best", "hex_prefix", "join"])]
+    airdrop: Option<f64>,
+
+    // cluster to request the --airdrop on
+    #[arg(long, value_enum, requires = "airdrop")]
+    cluster: Option<Cluster>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[command(about = "Estimate how hard a pattern is to find without searching for it")]
+    Estimate(EstimateArgs),
+    #[command(
+        about = "Check the matcher, base58 encoding and key derivation against known vectors"
+    )]
+    Selftest,
+    #[command(about = "Check that a nonce and keypair file open a --commit commitment")]
+    OpenCommitment(OpenCommitmentArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct OpenCommitmentArgs {
+    // the commitment that was published, in hex
+    #[arg(long)]
+    commitment: String,
+
+    // the commit_nonce kept in the --append file, in hex
+    #[arg(long)]
+    nonce: String,
+
+    // keypair file of the found address
+    #[arg(long)]
+    keypair: PathBuf,
+}
+
+#[derive(clap::Args, Debug)]
+struct EstimateArgs {
+    // pattern to estimate; separate alternatives with '|'
     #[arg(short = 'f', long, value_parser = validate_find)]
     find: String,
 
-    // number of threads to create
-    #[arg(short = 't', long, default_value_t = 2, value_parser = validate_threads)]
+    // number of threads the search would use
+    #[arg(short = 't', long, default_value_t = default_threads(), value_parser = validate_threads, allow_negative_numbers = true)]
     threads: usize,
 
-    // match type to use
+    // match type of patterns without a ':type' suffix
     #[arg(short = 'm', long, value_enum, default_value_t = MatchType::Prefix)]
     match_type: MatchType,
 
+    // number of leading characters searched in fingerprint mode
+    #[arg(long, value_parser = validate_fingerprint_len)]
+    fingerprint_len: Option<usize>,
+
+    // positions the pattern may start at in range mode, e.g. 0..3
+    #[arg(long, value_name = "START..END", value_parser = validate_at_range)]
+    at_range: Option<Range<usize>>,
+
+    // positions the whole match has to lie within in window mode, e.g. 5..15
+    #[arg(long, value_name = "START..END", value_parser = validate_window)]
+    window: Option<Range<usize>>,
+
     // enable case sensitivity
     #[arg(short = 's', long, default_value_t = false, action = clap::ArgAction::Set)]
     case_sensitivity: bool,
 
+    // with case sensitivity off, only count matches whose letters are in the case the pattern was typed in
+    #[arg(long, default_value_t = false)]
+    canonical_case: bool,
+
     // enable flexible character set
     #[arg(short = 'l', long, default_value_t = true, action = clap::ArgAction::Set)]
     flexible_chars: bool,
+
+    // how loose flexible lookalikes may be, from 0 (none) to 3
+    #[arg(long, default_value_t = DEFAULT_FLEX_LEVEL, value_parser = validate_flex_level)]
+    flex_level: u8,
+
+    // count matches with up to this many pattern characters wrong
+    #[arg(long, value_name = "K", default_value_t = 0)]
+    max_distance: usize,
+
+    // benchmark seed phrase candidates instead of standalone keypairs
+    #[arg(long, default_value_t = false)]
+    hd: bool,
+
+    // random number generator keypairs are generated from
+    #[arg(long, value_enum, default_value_t = Entropy::Os, conflicts_with = "hd")]
+    entropy: Entropy,
+
+    // how long to benchmark this machine's search rate for
+    #[arg(long, value_name = "SECS", default_value_t = 2)]
+    benchmark_secs: u64,
+
+    // also benchmark the pattern with lookalikes off and on, and report what flexible matching costs
+    #[arg(long, default_value_t = false)]
+    compare_flexible: bool,
+
+    // power the machine draws while searching, to estimate the energy the search takes
+    #[arg(long, value_name = "WATTS", value_parser = validate_watts)]
+    watts: Option<f64>,
+
+    // electricity price per kWh, to turn the energy estimate into a cost
+    #[arg(long, value_name = "PRICE", value_parser = validate_price, requires = "watts")]
+    price_per_kwh: Option<f64>,
+}
+
+// Check each '|'-separated alternative of the pattern, ignoring a ':type' suffix that sets its own match type
+fn validate_find(s: &str) -> Result<String, String> {
+    for alternative in s.split('|') {
+        validate_alternative(split_match_type(alternative).0)?;
+    }
+
+    Ok(s.to_string())
+}
+
+fn validate_age_recipient(s: &str) -> Result<String, String> {
+    parse_age_recipient(s)?;
+    Ok(s.to_string())
+}
+
+// An unknown placeholder is caught here rather than when the first keypair is found
+fn validate_template(s: &str) -> Result<String, String> {
+    check_template(s)?;
+    Ok(s.to_string())
+}
+
+// The --like reference has to be an address a keypair can have, not just any base58 text
+fn validate_like(s: &str) -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    five8::decode_32(s, &mut bytes).map_err(|_| format!("'{}' is not a base58 public key", s))?;
+    Ok(s.to_string())
+}
+
+// Checked before anything is fetched, so a mistyped scheme fails with the other argument errors
+fn validate_patterns_url(s: &str) -> Result<String, String> {
+    if !allowed_url(s) {
+        return Err("the URL must start with https:// (http:// only for localhost)".to_string());
+    }
+    Ok(s.to_string())
+}
+
+// The shared part becomes a pattern, so it follows the pattern length limit
+fn validate_like_len(s: &str) -> Result<usize, String> {
+    let len = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", s))?;
+
+    if len == 0 || len > CHAR_LIMIT {
+        return Err(format!("--like-len must be between 1 and {}", CHAR_LIMIT));
+    }
+
+    Ok(len)
+}
+
+// The letters of an anagram are a single pattern alternative, so '|' and a ':type' suffix are rejected with the
+// other characters outside the alphabet
+fn validate_anagram(s: &str) -> Result<String, String> {
+    validate_alternative(s)?;
+    Ok(s.to_string())
+}
+
+// Excludes follow the same rules as a single pattern alternative
+fn validate_exclude(s: &str) -> Result<String, String> {
+    validate_alternative(s)?;
+    Ok(s.to_string())
 }
 
 // Check if all characters are valid base58, and is an appropriate length
 const CHAR_LIMIT: usize = 18; //arbitrary number that is shorter than the pubkey char limit but also is an unreasonably long substring to search for
 const BASE58_SET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
-fn validate_find(s: &str) -> Result<String, String> {
+fn validate_alternative(s: &str) -> Result<(), String> {
+    if s.is_empty() {
+        return Err("Pattern alternatives cannot be empty".to_string());
+    }
+
     if s.len() > CHAR_LIMIT {
         return Err(format!(
             "Pattern is too long to search for; current char limit: {}",
@@ -43,255 +596,3762 @@ fn validate_find(s: &str) -> Result<String, String> {
     }
 
     for ch in s.chars() {
-        if !BASE58_SET.contains(ch) {
+        if !alphabet().contains(ch) {
             return Err(format!(
-                "Invalid character '{}' in pattern. Only base58 characters allowed: {}",
-                ch, BASE58_SET
+                "Invalid character '{}' in pattern. Only {} characters allowed: {}",
+                ch,
+                if ALPHABET.get().is_some() {
+                    "--alphabet"
+                } else {
+                    "base58"
+                },
+                alphabet()
             ));
         }
     }
 
+    Ok(())
+}
+
+// Custom alphabet from --alphabet, set before clap validates patterns against it
+static ALPHABET: OnceLock<String> = OnceLock::new();
+
+// The alphabet patterns are written in: --alphabet if given, otherwise Solana's base58 alphabet
+fn alphabet() -> &'static str {
+    ALPHABET.get().map_or(BASE58_SET, String::as_str)
+}
+
+// A custom alphabet replaces base58 character for character, so it needs 58 distinct printable characters
+fn validate_alphabet(s: &str) -> Result<String, String> {
+    if s.chars().count() != BASE58_SET.len() {
+        return Err(format!(
+            "The alphabet must have exactly {} characters, got {}",
+            BASE58_SET.len(),
+            s.chars().count()
+        ));
+    }
+    if let Some(ch) = s.chars().find(|c| !c.is_ascii_graphic() || *c == '|') {
+        return Err(format!(
+            "Invalid character '{}' in alphabet; use printable ASCII other than '|'",
+            ch
+        ));
+    }
+    if let Some((i, ch)) = s.char_indices().find(|&(i, ch)| s[..i].contains(ch)) {
+        return Err(format!(
+            "Character '{}' appears more than once in the alphabet (again at position {})",
+            ch, i
+        ));
+    }
     Ok(s.to_string())
 }
 
-// Check if number of threads is create is realistic
-fn validate_threads(s: &str) -> Result<usize, String> {
-    let threads = s
+// Maps every character to the one at the same position in the other alphabet; characters outside `from` are kept
+fn translate(s: &str, from: &str, to: &str) -> String {
+    s.chars()
+        .map(|c| from.find(c).map_or(c, |i| to.as_bytes()[i] as char))
+        .collect()
+}
+
+// A pattern written in the --alphabet, as the base58 text the matcher compares against
+fn to_base58(pattern: &str) -> String {
+    translate(pattern, alphabet(), BASE58_SET)
+}
+
+// A base58 address or pattern as it reads in the --alphabet
+fn in_alphabet(base58: &str) -> String {
+    translate(base58, BASE58_SET, alphabet())
+}
+
+// Patterns of a batch, and the keypair file each pattern that named one should be written to
+struct PatternsFile {
+    patterns: Vec<String>,
+    destinations: Vec<(String, PathBuf)>,
+}
+
+// Read a batch of patterns, one per line, each optionally followed by a tab and the keypair file for its match;
+// blank lines and lines starting with '#' are skipped. Patterns keep any ':type' suffix for split_match_types.
+fn load_patterns_file(path: &Path) -> Result<PatternsFile, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read patterns file {}: {}", path.display(), e))?;
+    parse_pattern_list(&contents, &path.display().to_string(), true)
+}
+
+// Fetch a batch of patterns from a URL. Its lines can't name output files: whoever controls the list must not
+// decide where secret keys are written on this machine.
+fn load_patterns_url(url: &str) -> Result<PatternsFile, String> {
+    let contents = fetch_pattern_list(url)?;
+    parse_pattern_list(&contents, url, false)
+}
+
+// Parses the lines of a pattern list read from `source`, which names the list in errors
+fn parse_pattern_list(
+    contents: &str,
+    source: &str,
+    allow_destinations: bool,
+) -> Result<PatternsFile, String> {
+    let mut patterns = Vec::new();
+    let mut destinations: Vec<(String, PathBuf)> = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let at = |e: String| format!("{}:{}: {}", source, i + 1, e);
+        let (entry, destination) = split_pattern_line(line);
+        let pattern = split_match_type(entry).0;
+        validate_alternative(pattern).map_err(at)?;
+        if destination.is_some() && !allow_destinations {
+            return Err(at(
+                "a pattern list from a URL can't name output files; use --output".to_string(),
+            ));
+        }
+        if let Some(destination) = destination.map(PathBuf::from) {
+            if let Some((other, _)) = destinations.iter().find(|(_, d)| *d == destination) {
+                return Err(at(format!(
+                    "{} is already the output file of pattern '{}'",
+                    destination.display(),
+                    other
+                )));
+            }
+            destinations.push((pattern.to_string(), destination));
+        }
+        patterns.push(entry.to_string());
+    }
+
+    if patterns.is_empty() {
+        return Err(format!("No patterns found in {}", source));
+    }
+    Ok(PatternsFile {
+        patterns,
+        destinations,
+    })
+}
+
+// Nearly every address is 43 or 44 characters long; searched windows have to fit inside the shorter one
+const SHORT_ADDRESS_LEN: usize = 43;
+fn validate_fingerprint_len(s: &str) -> Result<usize, String> {
+    let len = s
         .parse()
         .map_err(|_| format!("'{}' is not a valid number", s))?;
 
-    if threads == 0 {
-        return Err("Number of threads must be at least 1".to_string());
+    if len == 0 || len > SHORT_ADDRESS_LEN {
+        return Err(format!(
+            "Fingerprint length must be between 1 and {}",
+            SHORT_ADDRESS_LEN
+        ));
     }
 
-    let available_threads = match std::thread::available_parallelism() {
-        Ok(a) => a.get(),
-        Err(e) => {
-            return Err(format!(
-                "Cannot get number of available threads in system: {}",
-                e
-            ))
-        }
+    Ok(len)
+}
+
+// Parses START..END (end exclusive) and checks it lies within an address
+fn parse_range(s: &str) -> Result<Range<usize>, String> {
+    let (start, end) = s
+        .split_once("..")
+        .ok_or_else(|| format!("'{}' is not a range like 0..3", s))?;
+    let parse = |n: &str| {
+        n.parse::<usize>()
+            .map_err(|_| format!("'{}' is not a valid number", n))
     };
+    let range = parse(start)?..parse(end)?;
 
-    if threads > available_threads {
+    if range.is_empty() {
+        return Err("Range end must be greater than its start".to_string());
+    }
+    Ok(range)
+}
+
+// The window may reach the 44th character; in a 43 character address it simply ends with the address
+fn validate_window(s: &str) -> Result<Range<usize>, String> {
+    let window = parse_range(s)?;
+    if window.end > MAX_ADDRESS_LEN {
         return Err(format!(
-            "Requested {} threads but only {} hardware threads (logical cores) available, which may cause performance degradation",
-            threads, available_threads
+            "Window must lie within the {} characters of an address",
+            MAX_ADDRESS_LEN
         ));
     }
+    Ok(window)
+}
 
-    Ok(threads)
+fn validate_at_range(s: &str) -> Result<Range<usize>, String> {
+    let range = parse_range(s)?;
+    if range.end > SHORT_ADDRESS_LEN {
+        return Err(format!(
+            "Range must lie within the first {} characters",
+            SHORT_ADDRESS_LEN
+        ));
+    }
+
+    Ok(range)
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
-enum MatchType {
-    Prefix,
-    Suffix,
-    Either,
+// --canonical-case narrows case folding, so it needs case folding to be on
+fn check_canonical_case(case_sensitive: bool, canonical_case: bool) -> Result<(), String> {
+    if canonical_case && case_sensitive {
+        return Err(
+            "--canonical-case only applies with -s false; a case sensitive search already keeps the typed case"
+                .to_string(),
+        );
+    }
+    Ok(())
 }
 
-fn main() {
-    let args = Args::parse();
-    println!("Now searching with the following config:");
-    println!("  Pattern: {}", args.find);
-    println!("  Threads: {}", args.threads);
-    println!("  Match Type: {:?}", args.match_type);
-    println!("  Case Sensitivity: {}", args.case_sensitivity);
-    println!("  Flexible Char Set: {}", args.flexible_chars);
+// A match has to get at least one character of every pattern right, or any address would do
+fn check_max_distance(patterns: &[String], max_distance: usize) -> Result<(), String> {
+    match patterns
+        .iter()
+        .find(|pattern| pattern.len() <= max_distance)
+    {
+        Some(pattern) => Err(format!(
+            "--max-distance {} would let every address match '{}'; it must be less than the length of every pattern",
+            max_distance, pattern
+        )),
+        None => Ok(()),
+    }
+}
 
-    let start = Instant::now();
+// --byte-sum with its --byte-sum-mod; a remainder has to be smaller than the modulus to ever come up
+fn byte_sum_option(target: Option<u32>, modulus: Option<u32>) -> Result<Option<ByteSum>, String> {
+    let Some(target) = target else {
+        return Ok(None);
+    };
+    if let Some(modulus) = modulus
+        && target >= modulus
+    {
+        return Err(format!(
+            "--byte-sum {} can't be the remainder of a division by {}; use a value below it",
+            target, modulus
+        ));
+    }
+    Ok(Some(ByteSum { target, modulus }))
+}
 
-    let pattern = args.find;
-    let match_type = args.match_type;
-    let case_sensitivity = args.case_sensitivity;
-    let flexible_chars = args.flexible_chars;
-
-    ThreadPoolBuilder::new()
-        .num_threads(args.threads)
-        .build_global()
-        .unwrap();
-
-    let found = Arc::new(AtomicBool::new(false));
-    let result = (0..args.threads).into_par_iter().find_map_any(|_| {
-        while !found.load(Ordering::Relaxed) {
-            let keypair = Keypair::new();
-            let pubkey_str = keypair.pubkey().to_string();
-
-            if matches_pattern(
-                pubkey_str.as_bytes(),
-                &pattern.as_bytes(),
-                match_type,
-                case_sensitivity,
-                flexible_chars,
-            ) {
-                found.store(true, Ordering::Relaxed);
-                return Some(keypair);
-            }
-        }
-        None
-    });
+// Occurrences may not overlap, so a pattern can't occur more often than copies of it fit in the longest address
+fn check_min_occurrences(patterns: &[String], min_occurrences: usize) -> Result<(), String> {
+    match patterns
+        .iter()
+        .find(|pattern| pattern.len() * min_occurrences > MAX_ADDRESS_LEN)
+    {
+        Some(pattern) => Err(format!(
+            "'{}' can't occur {} times in a {} character address",
+            pattern, min_occurrences, MAX_ADDRESS_LEN
+        )),
+        None => Ok(()),
+    }
+}
 
-    match result {
-        Some(keypair) => {
-            println!("Found address: {}", keypair.pubkey());
-            println!("KP: {}", keypair.to_base58_string());
-        }
-        None => {
-            println!("No matching keypair found");
-        }
+// A minimum above the maximum leaves no address to accept
+fn check_entropy_limits(min: Option<f64>, max: Option<f64>) -> Result<(), String> {
+    match (min, max) {
+        (Some(min), Some(max)) if min > max => Err(format!(
+            "--min-entropy {} is above --max-entropy {}; no address can have both",
+            min, max
+        )),
+        _ => Ok(()),
     }
-    println!("Took {:.2} minutes", start.elapsed().as_secs_f64() / 60.0);
 }
 
-// Pattern finder
-fn matches_pattern(
-    pubkey: &[u8],
-    pattern: &[u8],
-    match_type: MatchType,
+// A prefix whose first character can only match digits puts a digit first in every address it matches
+fn check_leading_digit(
+    patterns: &[String],
+    match_types: &[MatchType],
     case_sensitive: bool,
-    flexible_chars: bool,
-) -> bool {
-    let pubkey_len = pubkey.len();
-    let pattern_len = pattern.len();
-    let flexible_chars = if case_sensitive {
-        false
+    flex_level: u8,
+) -> Result<(), String> {
+    match patterns
+        .iter()
+        .zip(match_types)
+        .find(|&(pattern, &match_type)| {
+            match_type == MatchType::Prefix
+                && pattern.bytes().next().is_some_and(|first| {
+                    accepted_chars(first, case_sensitive, flex_level)
+                        .iter()
+                        .all(u8::is_ascii_digit)
+                })
+        }) {
+        Some((pattern, _)) => Err(format!(
+            "'{}' is a prefix starting with a digit, so --no-leading-digit would reject every match",
+            pattern
+        )),
+        None => Ok(()),
+    }
+}
+
+// Without lookalikes a character like '1' matches only itself, though the user may have meant the 'i' or 'L' it is
+// easily mistaken for. Warns about every such character, or with --strict-base58 refuses to search until lookalikes
+// are turned on or the pattern is rewritten.
+fn check_ambiguous_chars(patterns: &[String], args: &Args) {
+    let exact = args.case_sensitivity || flex_level(args.flexible_chars, args.flex_level) == 0;
+    if !exact || args.alphabet.is_some() {
+        return;
+    }
+    let hint = if args.case_sensitivity {
+        "-s false -l true"
     } else {
-        flexible_chars
+        "-l true with --flex-level 1 or higher"
     };
+    let mut found = false;
+    for pattern in patterns {
+        for (c, confused_with) in ambiguous_chars(pattern) {
+            found = true;
+            let others: Vec<String> = confused_with
+                .iter()
+                .map(|&other| format!("'{}'", other as char))
+                .collect();
+            eprintln!(
+                "{} '{}' has '{}', easily confused with {}; only '{}' itself will match (use {} to accept both)",
+                if args.strict_base58 {
+                    "Pattern"
+                } else {
+                    "Warning: pattern"
+                },
+                pattern,
+                c as char,
+                others.join(" or "),
+                c as char,
+                hint
+            );
+        }
+    }
+    if found && args.strict_base58 {
+        eprintln!(
+            "Refusing to search with --strict-base58; turn on lookalikes or write the character you meant"
+        );
+        std::process::exit(1);
+    }
+}
 
-    match match_type {
-        MatchType::Prefix => {
-            for i in 0..pattern_len {
-                if !matches_char(pubkey[i], pattern[i], case_sensitive, flexible_chars) {
-                    return false;
-                }
+// Splits the ':type' suffix off each pattern entry, e.g. "xyz:suffix"; entries without one get default_type
+fn split_match_types(entries: &[String], default_type: MatchType) -> (Vec<String>, Vec<MatchType>) {
+    entries
+        .iter()
+        .map(|entry| {
+            let (pattern, match_type) = split_match_type(entry);
+            (pattern.to_string(), match_type.unwrap_or(default_type))
+        })
+        .unzip()
+}
+
+// Checks the fingerprint, range and window options against the patterns and their match types, filling in
+// defaults. Also rejects prefix-after-ones patterns that start with the '1' they skip.
+fn window_options(
+    patterns: &[String],
+    match_types: &[MatchType],
+    fingerprint_len: Option<usize>,
+    at_range: Option<Range<usize>>,
+    window: Option<Range<usize>>,
+) -> Result<(usize, Range<usize>, Range<usize>), String> {
+    if fingerprint_len.is_some() && !match_types.contains(&MatchType::Fingerprint) {
+        return Err("--fingerprint-len only applies to fingerprint matching".to_string());
+    }
+    if at_range.is_some() && !match_types.contains(&MatchType::Range) {
+        return Err("--at-range only applies to range matching".to_string());
+    }
+    if window.is_some() && !match_types.contains(&MatchType::Window) {
+        return Err("--window only applies to window matching".to_string());
+    }
+
+    let fingerprint_len = fingerprint_len.unwrap_or(DEFAULT_FINGERPRINT_LEN);
+    for (pattern, match_type) in patterns.iter().zip(match_types) {
+        match match_type {
+            MatchType::Fingerprint if pattern.len() > fingerprint_len => {
+                return Err(format!(
+                    "Pattern '{}' is longer than the {} character fingerprint",
+                    pattern, fingerprint_len
+                ));
             }
-            true
-        }
-        MatchType::Suffix => {
-            let start_idx = pubkey_len - pattern_len;
-            for i in 0..pattern_len {
-                if !matches_char(
-                    pubkey[start_idx + i],
-                    pattern[i],
-                    case_sensitive,
-                    flexible_chars,
-                ) {
-                    return false;
-                }
+            MatchType::PrefixAfterOnes if pattern.starts_with('1') => {
+                return Err(format!(
+                    "Pattern '{}' starts with '1', but prefix-after-ones matching starts after the leading '1's; use prefix matching for it",
+                    pattern
+                ));
             }
-            true
-        }
-        MatchType::Either => {
-            // check prefix first (early return on match)
-            let mut prefix_matches = true;
-            for i in 0..pattern_len {
-                if !matches_char(pubkey[i], pattern[i], case_sensitive, flexible_chars) {
-                    prefix_matches = false;
-                    break;
+            MatchType::Range => {
+                let Some(range) = &at_range else {
+                    return Err("Range matching needs --at-range".to_string());
+                };
+                if range.end - 1 + pattern.len() > SHORT_ADDRESS_LEN {
+                    return Err(format!(
+                        "Pattern '{}' starting at {} would run past the end of a {} character address",
+                        pattern,
+                        range.end - 1,
+                        SHORT_ADDRESS_LEN
+                    ));
                 }
             }
-            if prefix_matches {
-                return true;
-            }
-            // check suffix if prefix doesn't match
-            let start_idx = pubkey_len - pattern_len;
-            for i in 0..pattern_len {
-                if !matches_char(
-                    pubkey[start_idx + i],
-                    pattern[i],
-                    case_sensitive,
-                    flexible_chars,
-                ) {
-                    return false;
+            MatchType::Window => {
+                let Some(window) = &window else {
+                    return Err("Window matching needs --window".to_string());
+                };
+                if pattern.len() > window.len() {
+                    return Err(format!(
+                        "Pattern '{}' doesn't fit in the {} character window {:?}",
+                        pattern,
+                        window.len(),
+                        window
+                    ));
                 }
             }
-            true
+            _ => {}
         }
     }
+
+    Ok((
+        fingerprint_len,
+        at_range.unwrap_or(0..1),
+        window.unwrap_or(0..DEFAULT_WINDOW_END),
+    ))
 }
 
-// Checks which pattern finder method to use
-#[inline]
-fn matches_char(c: u8, target: u8, case_sensitive: bool, flexible: bool) -> bool {
-    // case_sensitivity is true, flexible_chars is false
-    if case_sensitive {
-        c == target
-    // flexible_chars is true, case_sensitivity is false
-    } else if flexible {
-        matches_flexible(c, target)
-    // flexible_chars is false, case_sensitivity is false
-    } else {
-        c.eq_ignore_ascii_case(&target)
-    }
-}
-
-// Flexible char pattern finder that looks for similar chars
-#[inline]
-fn matches_flexible(c: u8, target: u8) -> bool {
-    match target {
-        b'1' => matches!(c, b'1' | b'i' | b'L'),
-        b'2' => matches!(c, b'2' | b'z' | b'Z'),
-        b'3' => matches!(c, b'3' | b'E'),
-        b'4' => matches!(c, b'4' | b'A'),
-        b'5' => matches!(c, b'5' | b's' | b'S'),
-        b'6' => matches!(c, b'6' | b'b' | b'G'),
-        b'7' => matches!(c, b'7' | b'T'),
-        b'8' => matches!(c, b'8' | b'B'),
-        b'9' => matches!(c, b'9' | b'g'),
-
-        b'a' => matches!(c, b'a' | b'A' | b'4'),
-        b'b' => matches!(c, b'b' | b'B' | b'6'),
-        b'c' => matches!(c, b'c' | b'C'),
-        b'd' => matches!(c, b'd' | b'D'),
-        b'e' => matches!(c, b'e' | b'E' | b'3'),
-        b'f' => matches!(c, b'f' | b'F'),
-        b'g' => matches!(c, b'g' | b'G' | b'6' | b'9'),
-        b'h' => matches!(c, b'h' | b'H'),
-        b'i' => matches!(c, b'i' | b'1'),
-        b'j' => matches!(c, b'j' | b'J'),
-        b'k' => matches!(c, b'k' | b'K'),
-        b'm' => matches!(c, b'm' | b'M'),
-        b'n' => matches!(c, b'n' | b'N'),
-        b'o' => matches!(c, b'o'),
-        b'p' => matches!(c, b'p' | b'P'),
-        b'q' => matches!(c, b'q' | b'Q'),
-        b'r' => matches!(c, b'r' | b'R'),
-        b's' => matches!(c, b's' | b'S' | b'5'),
-        b't' => matches!(c, b't' | b'T' | b'7'),
-        b'u' => matches!(c, b'u' | b'U'),
-        b'v' => matches!(c, b'v' | b'V'),
-        b'w' => matches!(c, b'w' | b'W'),
-        b'x' => matches!(c, b'x' | b'X'),
-        b'y' => matches!(c, b'y' | b'Y'),
-        b'z' => matches!(c, b'z' | b'Z' | b'2'),
-
-        b'A' => matches!(c, b'a' | b'A' | b'4'),
-        b'B' => matches!(c, b'b' | b'B' | b'6' | b'8'),
-        b'C' => matches!(c, b'c' | b'C'),
-        b'D' => matches!(c, b'd' | b'D'),
-        b'E' => matches!(c, b'e' | b'E' | b'3'),
-        b'F' => matches!(c, b'f' | b'F'),
-        b'G' => matches!(c, b'g' | b'G' | b'6' | b'9'),
-        b'H' => matches!(c, b'h' | b'H'),
-        b'J' => matches!(c, b'j' | b'J'),
-        b'K' => matches!(c, b'k' | b'K'),
-        b'L' => matches!(c, b'L' | b'1'),
-        b'M' => matches!(c, b'm' | b'M'),
-        b'N' => matches!(c, b'n' | b'N'),
-        b'P' => matches!(c, b'p' | b'P'),
-        b'Q' => matches!(c, b'q' | b'Q'),
-        b'R' => matches!(c, b'r' | b'R'),
-        b'S' => matches!(c, b's' | b'S' | b'5'),
-        b'T' => matches!(c, b't' | b'T' | b'7'),
-        b'U' => matches!(c, b'u' | b'U'),
-        b'V' => matches!(c, b'v' | b'V'),
-        b'W' => matches!(c, b'w' | b'W'),
-        b'X' => matches!(c, b'x' | b'X'),
-        b'Y' => matches!(c, b'y' | b'Y'),
-        b'Z' => matches!(c, b'z' | b'Z' | b'2'),
-
-        _ => c.eq_ignore_ascii_case(&target),
+// A public key is 32 bytes, so 64 hex digits at most
+const HEX_PREFIX_LIMIT: usize = 64;
+fn validate_hex_prefix(s: &str) -> Result<String, String> {
+    if s.is_empty() {
+        return Err("Hex prefix cannot be empty".to_string());
+    }
+
+    if s.len() > HEX_PREFIX_LIMIT {
+        return Err(format!(
+            "Hex prefix is too long; a public key has {} hex digits",
+            HEX_PREFIX_LIMIT
+        ));
+    }
+
+    if let Some(ch) = s.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid character '{}' in hex prefix", ch));
+    }
+
+    Ok(s.to_string())
+}
+
+// 2^256 - 1 has 78 decimal digits
+const NUMERIC_PREFIX_LIMIT: usize = 78;
+fn validate_numeric_prefix(s: &str) -> Result<String, String> {
+    if s.is_empty() {
+        return Err("Numeric prefix cannot be empty".to_string());
+    }
+
+    if let Some(ch) = s.chars().find(|c| !c.is_ascii_digit()) {
+        return Err(format!("Invalid character '{}' in numeric prefix", ch));
+    }
+
+    // only the number 0 itself is written with a leading zero
+    if s.starts_with('0') {
+        return Err("Numeric prefix can't start with 0".to_string());
+    }
+
+    if s.len() > NUMERIC_PREFIX_LIMIT
+        || !NumericPrefixMatcher::new(s).expected_attempts().is_finite()
+    {
+        return Err(format!(
+            "No 256-bit public key starts with {}; the largest is {} ({} digits)",
+            s,
+            to_decimal(&[0xff; 32]),
+            NUMERIC_PREFIX_LIMIT
+        ));
+    }
+
+    Ok(s.to_string())
+}
+
+fn validate_group(s: &str) -> Result<usize, String> {
+    let group = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", s))?;
+
+    if group == 0 || group > MAX_ADDRESS_LEN {
+        return Err(format!(
+            "Group size must be between 1 and {}",
+            MAX_ADDRESS_LEN
+        ));
+    }
+
+    Ok(group)
+}
+
+// An address has at most 44 characters, so it can't contain more distinct ones
+const MAX_ADDRESS_LEN: usize = 44;
+fn validate_min_distinct(s: &str) -> Result<usize, String> {
+    let min = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", s))?;
+
+    if min == 0 || min > MAX_ADDRESS_LEN {
+        return Err(format!(
+            "Minimum distinct characters must be between 1 and {}",
+            MAX_ADDRESS_LEN
+        ));
+    }
+
+    Ok(min)
+}
+
+// Shannon entropy in bits per character; an address of MAX_ADDRESS_LEN characters, all different, has the most
+fn validate_entropy(s: &str) -> Result<f64, String> {
+    let bits: f64 = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", s))?;
+    let max = (MAX_ADDRESS_LEN as f64).log2();
+
+    if !(0.0..=max).contains(&bits) {
+        return Err(format!(
+            "Entropy must be between 0 and {:.2} bits per character",
+            max
+        ));
+    }
+
+    Ok(bits)
+}
+
+// 32 bytes of at most 255 each
+fn validate_byte_sum(s: &str) -> Result<u32, String> {
+    let sum = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", s))?;
+
+    if sum > MAX_BYTE_SUM {
+        return Err(format!("Byte sum must be between 0 and {}", MAX_BYTE_SUM));
+    }
+
+    Ok(sum)
+}
+
+// A modulus of 1 would accept every key
+fn validate_byte_sum_mod(s: &str) -> Result<u32, String> {
+    let modulus = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", s))?;
+
+    if !(2..=MAX_BYTE_SUM).contains(&modulus) {
+        return Err(format!(
+            "Byte sum modulus must be between 2 and {}",
+            MAX_BYTE_SUM
+        ));
+    }
+
+    Ok(modulus)
+}
+
+fn validate_min_occurrences(s: &str) -> Result<usize, String> {
+    let min = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", s))?;
+
+    if min == 0 || min > MAX_ADDRESS_LEN {
+        return Err(format!(
+            "Minimum occurrences must be between 1 and {}",
+            MAX_ADDRESS_LEN
+        ));
+    }
+
+    Ok(min)
+}
+
+// Flex levels select cumulative lookalike tables, see matcher::lookalikes
+fn validate_flex_level(s: &str) -> Result<u8, String> {
+    match s.parse() {
+        Ok(level) if level <= MAX_FLEX_LEVEL => Ok(level),
+        _ => Err(format!(
+            "Flex level must be between 0 and {}",
+            MAX_FLEX_LEVEL
+        )),
+    }
+}
+
+// --flexible-chars false switches lookalikes off whatever --flex-level says
+fn flex_level(flexible_chars: bool, flex_level: u8) -> u8 {
+    if flexible_chars { flex_level } else { 0 }
+}
+
+// e.g. "true (level 2)" for the banner
+fn flexible_label(flexible_chars: bool, level: u8) -> String {
+    if flexible_chars {
+        format!("true (level {})", level)
+    } else {
+        "false".to_string()
+    }
+}
+
+// Faucets hand out small positive amounts; the cluster decides the actual ceiling
+fn validate_airdrop(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(sol) if sol.is_finite() && sol > 0.0 => Ok(sol),
+        _ => Err(format!("'{}' is not a positive amount of SOL", s)),
+    }
+}
+
+fn validate_watts(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(watts) if watts.is_finite() && watts > 0.0 => Ok(watts),
+        _ => Err(format!("'{}' is not a positive number of watts", s)),
+    }
+}
+
+fn validate_price(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(price) if price.is_finite() && price >= 0.0 => Ok(price),
+        _ => Err(format!("'{}' is not a valid price", s)),
+    }
+}
+
+// Any count up to u64::MAX is accepted: stream mode holds at most --buffer keypairs at a time, plus the 32 byte address
+// of every match so far to keep the count distinct, so memory grows with the matches actually found, not the count
+fn validate_count(s: &str) -> Result<u64, String> {
+    let count = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number up to {}", s, u64::MAX))?;
+
+    if count == 0 {
+        return Err("Count must be at least 1".to_string());
+    }
+
+    Ok(count)
+}
+
+// A file must have room for something; a keypair larger than the limit still gets a file of its own
+fn validate_rotate_size(s: &str) -> Result<u64, String> {
+    let size = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number of bytes", s))?;
+
+    if size == 0 {
+        return Err("Rotate size must be at least 1 byte".to_string());
+    }
+
+    Ok(size)
+}
+
+fn validate_rotate_count(s: &str) -> Result<u64, String> {
+    let count = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", s))?;
+
+    if count == 0 {
+        return Err("Rotate count must be at least 1".to_string());
+    }
+
+    Ok(count)
+}
+
+// The stream buffer must hold at least one keypair
+fn validate_buffer(s: &str) -> Result<usize, String> {
+    let buffer = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", s))?;
+
+    if buffer == 0 {
+        return Err("Buffer must hold at least 1 keypair".to_string());
+    }
+
+    Ok(buffer)
+}
+
+// A worker needs some share of a core to make progress; 100% is the same as no limit
+fn validate_cpu_limit(s: &str) -> Result<u8, String> {
+    let percent = s
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid percentage", s))?;
+
+    if !(1..=100).contains(&percent) {
+        return Err("CPU limit must be between 1 and 100 percent".to_string());
+    }
+
+    Ok(percent)
+}
+
+// A timeout of 0 would end the search before it starts
+fn validate_timeout(s: &str) -> Result<u64, String> {
+    let secs = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number of seconds", s))?;
+
+    if secs == 0 {
+        return Err("Timeout must be at least 1 second".to_string());
+    }
+
+    Ok(secs)
+}
+
+// Check if number of threads is create is realistic
+// A negative count leaves that many cores free, e.g. -2 on an 8 core machine means 6 threads
+fn validate_threads(s: &str) -> Result<usize, String> {
+    let requested: i64 = s
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", s))?;
+
+    if requested == 0 {
+        return Err("Number of threads must be at least 1".to_string());
+    }
+
+    let available_threads = match std::thread::available_parallelism() {
+        Ok(a) => a.get(),
+        Err(e) => {
+            return Err(format!(
+                "Cannot get number of available threads in system: {}",
+                e
+            ))
+        }
+    };
+
+    let threads = resolve_threads(requested, available_threads)?;
+    if threads > available_threads {
+        return Err(format!(
+            "Requested {} threads but only {} hardware threads (logical cores) available, which may cause performance degradation",
+            threads, available_threads
+        ));
+    }
+
+    Ok(threads)
+}
+
+// Two threads, or one on a machine with a single logical core, where validate_threads would reject two
+fn default_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |available| available.get().min(2))
+}
+
+// Turns a requested thread count into an absolute one; negative counts are subtracted from the available cores
+fn resolve_threads(requested: i64, available: usize) -> Result<usize, String> {
+    if requested > 0 {
+        return Ok(requested as usize);
+    }
+
+    let reserved = requested.unsigned_abs();
+    match (available as u64).checked_sub(reserved) {
+        Some(threads) if threads >= 1 => Ok(threads as usize),
+        _ => Err(format!(
+            "Leaving {} of {} hardware threads free leaves none to search with",
+            reserved, available
+        )),
+    }
+}
+
+// Well-known addresses a vanity address could be passed off as
+const NOTABLE_ADDRESSES: &[(&str, &str)] = &[
+    ("System Program", "11111111111111111111111111111111"),
+    (
+        "Token Program",
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    ),
+    (
+        "Token-2022 Program",
+        "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+    ),
+    (
+        "Associated Token Program",
+        "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+    ),
+    (
+        "Memo Program",
+        "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+    ),
+    (
+        "Compute Budget Program",
+        "ComputeBudget111111111111111111111111111111",
+    ),
+    (
+        "Vote Program",
+        "Vote111111111111111111111111111111111111111",
+    ),
+    (
+        "Stake Program",
+        "Stake11111111111111111111111111111111111111",
+    ),
+    (
+        "Config Program",
+        "Config1111111111111111111111111111111111111",
+    ),
+    (
+        "BPF Upgradeable Loader",
+        "BPFLoaderUpgradeab1e11111111111111111111111",
+    ),
+    (
+        "Address Lookup Table Program",
+        "AddressLookupTab1e1111111111111111111111111",
+    ),
+    (
+        "Clock Sysvar",
+        "SysvarC1ock11111111111111111111111111111111",
+    ),
+    ("Rent Sysvar", "SysvarRent111111111111111111111111111111111"),
+    (
+        "Wrapped SOL Mint",
+        "So11111111111111111111111111111111111111112",
+    ),
+    ("USDC Mint", "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"),
+];
+
+// Shorter patterns collide with notable addresses by chance too often to be a meaningful signal
+const CONFUSABLE_MIN_LEN: usize = 4;
+
+// Returns the notable addresses that would themselves satisfy the search under the same matching rules
+fn confusable_addresses(matcher: &PatternMatcher) -> Vec<(&'static str, &'static str)> {
+    NOTABLE_ADDRESSES
+        .iter()
+        .filter(|(_, address)| {
+            matcher.patterns.iter().enumerate().any(|(i, pattern)| {
+                pattern.len() >= CONFUSABLE_MIN_LEN
+                    && address.len() >= pattern.len()
+                    && matcher.matches_index(address.as_bytes(), i)
+            })
+        })
+        .copied()
+        .collect()
+}
+
+// Where on the ed25519 curve --require wants found addresses to lie
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Curve {
+    // a real ed25519 public key, which every keypair the tool generates has; nothing is filtered
+    OnCurve,
+    // a program derived address; these have no secret key, so keypair generation can never produce one
+    OffCurve,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Autotune {
+    // print the measured rates and the recommended thread count; the search keeps --threads
+    Suggest,
+    // search with the recommended thread count instead of --threads
+    Auto,
+}
+
+// os asks the kernel for every keypair; chacha expands an OS seed per thread in userspace
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Entropy {
+    Os,
+    Chacha,
+}
+
+// Prints human-oriented status lines; in machine-readable formats these go to stderr so stdout stays parseable
+macro_rules! status {
+    ($format:expr, $($arg:tt)*) => {
+        if $format.is_human() {
+            println!($($arg)*);
+        } else {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+// Physical cores from the distinct (physical id, core id) pairs in /proc/cpuinfo; None where that isn't available
+fn physical_cores() -> Option<usize> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+    let mut cores = std::collections::HashSet::new();
+    let mut physical_id = "";
+    for line in cpuinfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key.trim() {
+            "physical id" => physical_id = value.trim(),
+            "core id" => {
+                cores.insert((physical_id, value.trim()));
+            }
+            _ => {}
+        }
+    }
+    (!cores.is_empty()).then_some(cores.len())
+}
+
+// Report for --sysinfo. Key generation keeps an execution unit busy, so a hyper-thread sharing a core with another
+// worker adds little; one thread per physical core is the recommendation when the physical count is known.
+fn print_sysinfo(format: OutputFormat, threads: usize) {
+    let logical = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let physical = physical_cores().map(|cores| cores.min(logical));
+    status!(format, "System info:");
+    status!(format, "  Logical cores: {}", logical);
+    match physical {
+        Some(cores) => status!(format, "  Physical cores: {}", cores),
+        None => status!(format, "  Physical cores: unknown"),
+    }
+    status!(
+        format,
+        "  Recommended threads: {}",
+        physical.unwrap_or(logical)
+    );
+    status!(format, "  Threads requested: {}", threads);
+}
+
+// How long --autotune benchmarks each thread count
+const AUTOTUNE_DURATION: Duration = Duration::from_secs(1);
+
+// Starts the global worker pool with this many threads. When they can't all be started, e.g. under a ulimit on
+// processes, warns and settles for fewer (see fallback_thread_counts); returns the number the pool got.
+fn start_thread_pool(threads: usize) -> usize {
+    let mut first_error = None;
+    let started = start_global_pool(threads, |count| {
+        probe_pool(count).inspect_err(|e| {
+            first_error.get_or_insert_with(|| e.to_string());
+        })
+    });
+    match started {
+        Ok(started) => {
+            if started < threads {
+                eprintln!(
+                    "Warning: cannot start {} threads ({}); continuing with {}",
+                    threads,
+                    first_error.unwrap_or_default(),
+                    started
+                );
+            }
+            started
+        }
+        Err(e) => {
+            eprintln!("Cannot start any worker thread: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Benchmarks the search at the thread counts from autotune_thread_counts, each on a pool of its own, prints the rate
+// of each and returns the recommended count. Matches are ignored, so the pattern doesn't affect the result.
+fn autotune(format: OutputFormat, hd: bool, entropy: Entropy) -> usize {
+    let logical = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let matcher = PatternMatcher::new(vec!["zzzz".to_string()], MatchType::Prefix, true, false);
+    status!(
+        format,
+        "Autotuning: benchmarking each thread count for {} second(s)...",
+        AUTOTUNE_DURATION.as_secs()
+    );
+    let mut rates = Vec::new();
+    for threads in autotune_thread_counts(logical) {
+        let pool = match ThreadPoolBuilder::new().num_threads(threads).build() {
+            Ok(pool) => pool,
+            Err(e) => {
+                eprintln!(
+                    "Warning: cannot start {} threads ({}); not benchmarking it",
+                    threads, e
+                );
+                continue;
+            }
+        };
+        let rate = pool.install(|| {
+            benchmark_rate(
+                &matcher,
+                threads,
+                |_| keypair_source(hd, entropy),
+                AUTOTUNE_DURATION,
+            )
+        });
+        status!(
+            format,
+            "  {} thread(s): {:.0} addresses/second ({:.0} per thread)",
+            threads,
+            rate,
+            rate / threads as f64
+        );
+        rates.push((threads, rate));
+    }
+
+    let recommended = recommend_threads(&rates);
+    if rates.iter().any(|&(threads, _)| threads > recommended) {
+        status!(
+            format,
+            "  Adding threads past {} stopped helping on this machine",
+            recommended
+        );
+    }
+    status!(format, "  Recommended threads: {}", recommended);
+    recommended
+}
+
+// Options a config file may set, by their long names
+const CONFIG_KEYS: &[&str] = &[
+    "threads",
+    "match-type",
+    "fingerprint-len",
+    "case-sensitivity",
+    "flexible-chars",
+    "flex-level",
+    "exclude",
+    "min-distinct",
+    "format",
+    "entropy",
+    "hd",
+    "strict",
+    "strict-base58",
+    "no-secret",
+    "mkdir",
+    "verify-after-write",
+];
+
+// Reads a config file into (argument id, values) pairs, e.g. `match-type = "suffix"` or `exclude = ["abc", "xyz"]`
+fn load_config(path: &Path) -> Result<Vec<(String, Vec<String>)>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read config file {}: {}", path.display(), e))?;
+    let table: toml::Table = contents
+        .parse()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let scalar = |key: &str, value: &toml::Value| match value {
+        toml::Value::String(s) => Ok(s.clone()),
+        toml::Value::Integer(i) => Ok(i.to_string()),
+        toml::Value::Boolean(b) => Ok(b.to_string()),
+        _ => Err(format!(
+            "{}: unsupported value for '{}'",
+            path.display(),
+            key
+        )),
+    };
+    table
+        .iter()
+        .map(|(key, value)| {
+            if !CONFIG_KEYS.contains(&key.as_str()) {
+                return Err(format!("{}: unknown option '{}'", path.display(), key));
+            }
+            let values = match value {
+                toml::Value::Array(items) => items
+                    .iter()
+                    .map(|item| scalar(key, item))
+                    .collect::<Result<_, _>>()?,
+                _ => vec![scalar(key, value)?],
+            };
+            Ok((key.replace('-', "_"), values))
+        })
+        .collect()
+}
+
+// Finds the value of a flag on the command line before clap runs, for the flags that change how clap parses the
+// rest: --config supplies defaults and --alphabet the characters patterns are checked against
+fn arg_from_argv(flag: &str) -> Option<OsString> {
+    let mut argv = std::env::args_os().skip(1);
+    while let Some(arg) = argv.next() {
+        if arg == flag {
+            return argv.next();
+        }
+        if let Some(value) = arg
+            .to_str()
+            .and_then(|a| a.strip_prefix(flag))
+            .and_then(|a| a.strip_prefix('='))
+        {
+            return Some(value.into());
+        }
+    }
+    None
+}
+
+// Parses the command line. Config file values become the defaults of their arguments, giving the precedence
+// command line > config file > built-in default; they go through the same validation as flags.
+fn parse_args() -> Args {
+    if let Some(alphabet) = arg_from_argv("--alphabet")
+        && let Some(alphabet) = alphabet.to_str().and_then(|a| validate_alphabet(a).ok())
+    {
+        let _ = ALPHABET.set(alphabet);
+    }
+    let mut command = Args::command();
+    if let Some(path) = arg_from_argv("--config").map(PathBuf::from) {
+        let entries = load_config(&path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+        for (id, values) in entries {
+            command = command
+                .mut_arg(&id, |arg| arg.default_values(values.clone()))
+                .mut_subcommand("estimate", |estimate| {
+                    if estimate
+                        .get_arguments()
+                        .any(|arg| arg.get_id() == id.as_str())
+                    {
+                        estimate.mut_arg(&id, |arg| arg.default_values(values.clone()))
+                    } else {
+                        estimate
+                    }
+                });
+        }
+    }
+    let matches = command.get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // -f is checked here rather than by clap, so that --no-validate can skip it
+    if let Some(find) = &args.find
+        && !args.no_validate
+        && let Err(e) = validate_find(find)
+    {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::ValueValidation,
+                format!("invalid value '{}' for '--find <FIND>': {}", find, e),
+            )
+            .exit();
+    }
+    args.format = args.formats[0];
+    if args.template.is_some() {
+        args.format = OutputFormat::Template;
+    }
+    // case folding and lookalikes are defined on base58 letters and mean nothing in another alphabet
+    if args.alphabet.is_some() {
+        args.case_sensitivity = true;
+        args.flexible_chars = false;
+    }
+    if args.collapse_case {
+        args.case_sensitivity = false;
+        args.flexible_chars = false;
+    }
+    if args.pattern_at_word_boundary {
+        args.match_type = MatchType::Word;
+    }
+    if let Some(letters) = args.anagram.take() {
+        args.find = Some(letters);
+        args.match_type = MatchType::Anagram;
+    }
+    args
+}
+
+// Candidate generator for one worker, as selected by --hd and --entropy
+fn keypair_source(hd: bool, entropy: Entropy) -> Box<dyn KeypairSource> {
+    if hd {
+        Box::new(HdSource::default())
+    } else {
+        match entropy {
+            Entropy::Os => Box::new(OsRngSource),
+            Entropy::Chacha => Box::new(ChaChaSource::new()),
+        }
+    }
+}
+
+// A search worker's source: counter seeds with --debug-counter, the file's seed with --seed-from-file, otherwise
+// the generator --hd and --entropy choose
+fn worker_source(args: &Args, worker: usize, threads: usize) -> Box<dyn KeypairSource> {
+    match (args.debug_counter, args.file_seed) {
+        (Some(start), _) => Box::new(CounterSource::new(start, worker, threads)),
+        (None, Some(seed)) => Box::new(SeededSource::new(seed, worker)),
+        (None, None) => keypair_source(args.hd, args.entropy),
+    }
+}
+
+fn main() {
+    let mut args = parse_args();
+    let format = args.format;
+
+    match &args.command {
+        Some(Command::Estimate(estimate)) => {
+            run_estimate(estimate);
+            return;
+        }
+        Some(Command::OpenCommitment(open)) => {
+            run_open_commitment(open);
+            return;
+        }
+        Some(Command::Selftest) => {
+            let failures = run_vectors();
+            for failure in &failures {
+                println!("FAIL {}", failure);
+            }
+            println!(
+                "{} of {} vectors passed",
+                vector_count() - failures.len(),
+                vector_count()
+            );
+            std::process::exit(if failures.is_empty() { 0 } else { 1 });
+        }
+        None => {}
+    }
+
+    let search_requested = args.find.is_some()
+        || is_batch(&args)
+        || args.mine_best.is_some()
+        || args.hex_prefix.is_some()
+        || args.numeric_prefix.is_some();
+    if args.sysinfo {
+        print_sysinfo(format, args.threads);
+        if !search_requested && args.autotune.is_none() {
+            return;
+        }
+    }
+
+    if let Some(mode) = args.autotune {
+        let recommended = autotune(format, args.hd, args.entropy);
+        if !search_requested {
+            return;
+        }
+        if mode == Autotune::Auto {
+            status!(format, "Using {} thread(s)", recommended);
+            args.threads = recommended;
+        }
+    }
+
+    if let Some(start) = args.debug_counter {
+        eprintln!(
+            "WARNING: --debug-counter derives every keypair from a counter starting at {}, for debugging only.",
+            start
+        );
+        eprintln!(
+            "WARNING: anyone can regenerate these keys. NEVER send funds to an address found this way."
+        );
+    }
+    if args.no_validate {
+        eprintln!(
+            "WARNING: --no-validate searches for the pattern without checking it; a search for a pattern no address can have never ends."
+        );
+        eprintln!(
+            "WARNING: characters outside base58 never match, and a pattern longer than an address is never found."
+        );
+    }
+    if let Some(path) = &args.seed_from_file {
+        let seed = read_file_seed(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+        eprintln!(
+            "WARNING: --seed-from-file derives every keypair from {} (SHA-256 {}).",
+            path.display(),
+            to_hex(&seed)
+        );
+        eprintln!(
+            "WARNING: anyone with this file can regenerate these keys. NEVER send funds to an address found this way."
+        );
+        args.file_seed = Some(seed);
+    }
+
+    // the public key of a generated keypair is derived from its secret, which always lands on the curve, so
+    // on-curve needs no check per candidate and off-curve would search forever
+    if args.require == Some(Curve::OffCurve) {
+        eprintln!(
+            "--require off-curve can't be met: every generated keypair's public key is on the ed25519 curve. Off-curve \
+             addresses are program derived addresses (PDAs), which have no secret key and are found by searching \
+             seeds for a program id, not by generating keypairs; this tool has no PDA search."
+        );
+        std::process::exit(1);
+    }
+
+    if args.formats.len() > 1
+        && let Err(e) = check_extra_formats(&args)
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if args.format == OutputFormat::QrPng
+        && let Err(e) = check_qr_png(&args)
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if args.format == OutputFormat::Raw
+        && let Err(e) = check_raw(&args)
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if args.show_derivation && !args.format.is_human() {
+        eprintln!(
+            "--show-derivation is part of the human output; it can only be combined with --format human or qr-png"
+        );
+        std::process::exit(1);
+    }
+    // the commitment is shown in the human output and carried in json records
+    if args.commit && !matches!(args.format, OutputFormat::Human | OutputFormat::Json) {
+        eprintln!("--commit can only be combined with --format human or json");
+        std::process::exit(1);
+    }
+    if args.mint
+        && !matches!(
+            args.format,
+            OutputFormat::Human | OutputFormat::Csv | OutputFormat::Json
+        )
+    {
+        eprintln!(
+            "--mint saves a keypair file for spl-token; it can only be combined with --format human, csv or json"
+        );
+        std::process::exit(1);
+    }
+    if args.format == OutputFormat::Paper && (args.no_secret || args.mine_best.is_some()) {
+        eprintln!(
+            "--format paper prints the secret of a found keypair; it can't be combined with --no-secret or --mine-best"
+        );
+        std::process::exit(1);
+    }
+    if args.age_recipient.is_some()
+        && matches!(args.format, OutputFormat::QrPng | OutputFormat::Raw)
+    {
+        eprintln!(
+            "--age-recipient encrypts the keypair file --output names; with --format qr-png or raw that file holds something else"
+        );
+        std::process::exit(1);
+    }
+    if let Some(template) = &args.template
+        && args.no_secret
+        && check_template(template).is_ok_and(|used| used.iter().any(|p| p == "secret"))
+    {
+        eprintln!("--template uses {{secret}}, which --no-secret leaves out");
+        std::process::exit(1);
+    }
+    // a second keypair would overwrite the variables of the first
+    if args.format == OutputFormat::Env
+        && (is_batch(&args) || args.stream || args.mine_best.is_some())
+    {
+        eprintln!(
+            "--format env sets variables for a single keypair; it can't be combined with --patterns-file, --stream or --mine-best"
+        );
+        std::process::exit(1);
+    }
+
+    install_pause_signals();
+
+    if let Some(budget) = args.mine_best {
+        mine_best(&args, budget);
+        return;
+    }
+    if args.best_effort == Some(0) {
+        eprintln!("--best-effort needs at least one attempt");
+        std::process::exit(1);
+    }
+
+    if let Some(prefix) = &args.hex_prefix {
+        let matcher = HexPrefixMatcher::new(prefix);
+        view_search(
+            &args,
+            &matcher,
+            KeyView::Hex,
+            &matcher.prefix,
+            matcher.expected_attempts(),
+        );
+        return;
+    }
+    if let Some(prefix) = &args.numeric_prefix {
+        let matcher = NumericPrefixMatcher::new(prefix);
+        view_search(
+            &args,
+            &matcher,
+            KeyView::Decimal,
+            prefix,
+            matcher.expected_attempts(),
+        );
+        return;
+    }
+
+    let batch = is_batch(&args);
+    let PatternsFile {
+        patterns: entries,
+        destinations,
+    } = match (&args.find, &args.patterns_file) {
+        (Some(find), _) => PatternsFile {
+            patterns: find.split('|').map(str::to_string).collect(),
+            destinations: Vec::new(),
+        },
+        (None, Some(path)) => match load_patterns_file(path) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        (None, None) if args.patterns_url.is_some() => {
+            let url = args.patterns_url.as_deref().unwrap_or_default();
+            match load_patterns_url(url) {
+                Ok(list) => list,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        (None, None) if args.like.is_some() => {
+            let reference = args.like.as_deref().unwrap_or_default();
+            match like_patterns(reference, args.like_len, args.match_type) {
+                Ok(patterns) => PatternsFile {
+                    patterns,
+                    destinations: Vec::new(),
+                },
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        (None, None) => {
+            unreachable!(
+                "--sysinfo or --autotune alone returns before this; otherwise clap requires a pattern"
+            )
+        }
+    };
+
+    let (patterns, match_types) = split_match_types(&entries, args.match_type);
+
+    let (fingerprint_len, at_range, window) = window_options(
+        &patterns,
+        &match_types,
+        args.fingerprint_len,
+        args.at_range.clone(),
+        args.window.clone(),
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    for exclude in &args.exclude {
+        if let Some(pattern) = patterns.iter().find(|p| {
+            p.to_ascii_lowercase()
+                .contains(&exclude.to_ascii_lowercase())
+        }) {
+            eprintln!(
+                "Warning: pattern '{}' contains excluded substring '{}'; only lookalike or differently cased matches can be accepted",
+                pattern, exclude
+            );
+        }
+    }
+
+    if let Err(e) = check_canonical_case(args.case_sensitivity, args.canonical_case) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    if let Err(e) = check_max_distance(&patterns, args.max_distance) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    if let Some(min) = args.min_occurrences
+        && let Err(e) = check_min_occurrences(&patterns, min)
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    if let Err(e) = check_entropy_limits(args.min_entropy, args.max_entropy) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    if args.no_leading_digit
+        && let Err(e) = check_leading_digit(
+            &patterns,
+            &match_types,
+            args.case_sensitivity,
+            flex_level(args.flexible_chars, args.flex_level),
+        )
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    let byte_sum = byte_sum_option(args.byte_sum, args.byte_sum_mod).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    check_ambiguous_chars(&patterns, &args);
+
+    if args.stream && format == OutputFormat::Csv {
+        eprintln!("--stream supports human, json and paper output");
+        std::process::exit(1);
+    }
+
+    status!(format, "Now searching with the following config:");
+    match (&args.patterns_file, &args.patterns_url) {
+        (Some(path), _) => status!(
+            format,
+            "  Patterns File: {} ({} patterns)",
+            path.display(),
+            patterns.len()
+        ),
+        (None, Some(url)) => status!(
+            format,
+            "  Patterns URL: {} ({} patterns)",
+            url,
+            patterns.len()
+        ),
+        (None, None) => status!(format, "  Pattern: {}", entries.join("|")),
+    }
+    if let Some(reference) = &args.like {
+        status!(
+            format,
+            "  Like: {} ({} characters)",
+            reference,
+            args.like_len
+        );
+    }
+    if let Some(budget) = args.best_effort {
+        status!(
+            format,
+            "  Best Effort: {} attempts, keeping the closest",
+            budget
+        );
+    }
+    status!(format, "  Threads: {}", args.threads);
+    if let Some(percent) = args.cpu_limit {
+        status!(format, "  CPU Limit: {}% per thread", percent);
+    }
+    status!(format, "  Match Type: {:?}", args.match_type);
+    if match_types.contains(&MatchType::Fingerprint) {
+        status!(format, "  Fingerprint Length: {}", fingerprint_len);
+    }
+    if match_types.contains(&MatchType::Range) {
+        status!(format, "  Start Positions: {:?}", at_range);
+    }
+    if match_types.contains(&MatchType::Window) {
+        status!(format, "  Window: {:?}", window);
+        if window.end > SHORT_ADDRESS_LEN {
+            eprintln!(
+                "Warning: the window reaches position {}, which only 44 character addresses have; in 43 character addresses it ends at {}",
+                window.end - 1,
+                SHORT_ADDRESS_LEN
+            );
+        }
+    }
+    if !args.exclude.is_empty() {
+        status!(format, "  Excluding: {}", args.exclude.join(", "));
+    }
+    if let Some(min) = args.min_distinct {
+        status!(format, "  Minimum Distinct Characters: {}", min);
+    }
+    match (args.min_entropy, args.max_entropy) {
+        (None, None) => {}
+        (min, max) => status!(
+            format,
+            "  Address Entropy: {} to {} bits per character",
+            min.unwrap_or(0.0),
+            max.unwrap_or((MAX_ADDRESS_LEN as f64).log2())
+        ),
+    }
+    if args.no_leading_digit {
+        status!(format, "  No Leading Digit: true");
+        // a prefix decides the first character itself; any other pattern leaves it to chance
+        if match_types.iter().any(|&t| t != MatchType::Prefix) {
+            status!(
+                format,
+                "  About half of all addresses start with a digit, so patterns that aren't prefixes take about twice the attempts"
+            );
+        }
+    }
+    if let Some(min) = args.min_occurrences {
+        status!(format, "  Minimum Occurrences: {}", min);
+    }
+    match byte_sum {
+        Some(ByteSum {
+            target,
+            modulus: Some(modulus),
+        }) => status!(format, "  Byte Sum: {} mod {}", target, modulus),
+        Some(ByteSum { target, .. }) => status!(format, "  Byte Sum: {}", target),
+        None => {}
+    }
+    status!(format, "  Case Sensitivity: {}", args.case_sensitivity);
+    if args.canonical_case {
+        status!(format, "  Canonical Case: true");
+    }
+    if args.collapse_case {
+        status!(format, "  Collapsed Case: true");
+    }
+    status!(
+        format,
+        "  Flexible Char Set: {}",
+        flexible_label(args.flexible_chars, args.flex_level)
+    );
+    if args.max_distance > 0 {
+        status!(
+            format,
+            "  Max Distance: {} wrong character(s)",
+            args.max_distance
+        );
+    }
+    if args.hd {
+        status!(format, "  HD Derivation Path: {}", HD_DERIVATION_PATH);
+    } else if let (Some(path), Some(seed)) = (&args.seed_from_file, &args.file_seed) {
+        status!(
+            format,
+            "  Seed File: {} (SHA-256 {})",
+            path.display(),
+            to_hex(seed)
+        );
+    } else {
+        status!(format, "  Entropy: {:?}", args.entropy);
+    }
+    if let Some(encoding) = args.secondary_encoding {
+        status!(
+            format,
+            "  Secondary Encoding: {:?} containing {}",
+            encoding,
+            args.secondary_pattern
+                .as_deref()
+                .or(args.find.as_deref())
+                .unwrap_or_default()
+        );
+    }
+    if let Some(secs) = args.timeout {
+        status!(format, "  Timeout: {} seconds", secs);
+    }
+    if let Some(addr) = &args.coordinator {
+        status!(format, "  Coordinating joiners on: {}", addr);
+    }
+    if let Some(addr) = &args.join {
+        status!(format, "  Joining coordinator at: {}", addr);
+    }
+    if let Some(dir) = &args.job_dir {
+        status!(format, "  Job Directory: {}", dir.display());
+    }
+
+    let (patterns, match_types) = if batch {
+        let set = dedupe_patterns(patterns, match_types, args.case_sensitivity);
+        for duplicate in &set.duplicates {
+            status!(format, "Note: duplicate pattern '{}' ignored", duplicate);
+        }
+        // only one copy of a duplicate is searched, so it's unclear which output file its match belongs in
+        if let Some((pattern, path)) = destinations.iter().find(|(pattern, _)| {
+            set.duplicates.iter().any(|duplicate| {
+                if args.case_sensitivity {
+                    duplicate == pattern
+                } else {
+                    duplicate.eq_ignore_ascii_case(pattern)
+                }
+            })
+        }) {
+            eprintln!(
+                "Pattern '{}' has an output file ({}) but appears more than once in the patterns file",
+                pattern,
+                path.display()
+            );
+            std::process::exit(1);
+        }
+        for (shorter, longer) in &set.overlaps {
+            status!(
+                format,
+                "Note: '{}' overlaps '{}'; an address found for '{}' also satisfies '{}'",
+                shorter,
+                longer,
+                longer,
+                shorter
+            );
+        }
+        (set.patterns, set.match_types)
+    } else {
+        (patterns, match_types)
+    };
+
+    if let Some(path) = &args.output
+        && let Err(e) = check_output_dir(path, args.mkdir)
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    check_db(&args);
+    if let Some(path) = &args.output
+        && !args.append
+    {
+        if batch || args.stream {
+            eprintln!(
+                "{} finds several keypairs; use --append to collect them in one file",
+                if batch { "Batch mode" } else { "--stream" }
+            );
+            std::process::exit(1);
+        }
+        if path.exists() {
+            eprintln!(
+                "Output file {} already exists; use --append to add to it",
+                path.display()
+            );
+            std::process::exit(1);
+        }
+    }
+
+    for (_, path) in &destinations {
+        if let Err(e) = check_output_dir(path, args.mkdir) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        // with --resume-patterns an existing file is the earlier run's result, checked below
+        if path.exists() && !args.resume_patterns {
+            eprintln!("Output file {} already exists", path.display());
+            std::process::exit(1);
+        }
+    }
+
+    let build_matcher = |patterns: &[String], match_types: Vec<MatchType>| {
+        PatternMatcher::new(
+            patterns.iter().map(|p| to_base58(p)).collect(),
+            args.match_type,
+            args.case_sensitivity,
+            args.flexible_chars,
+        )
+        .with_match_types(match_types)
+        .with_flex_level(flex_level(args.flexible_chars, args.flex_level))
+        .with_fingerprint_len(fingerprint_len)
+        .with_at_range(at_range.clone())
+        .with_window(window.clone())
+        .with_excludes(args.exclude.iter().map(|e| to_base58(e)).collect())
+        .with_min_distinct(args.min_distinct.unwrap_or(0))
+        .with_entropy_limits(args.min_entropy, args.max_entropy)
+        .with_no_leading_digit(args.no_leading_digit)
+        .with_min_occurrences(args.min_occurrences.unwrap_or(0))
+        .with_byte_sum(byte_sum)
+        .with_canonical_case(args.canonical_case)
+        .with_collapse_case(args.collapse_case)
+        .with_max_distance(args.max_distance)
+    };
+    let mut matcher = build_matcher(&patterns, match_types.clone());
+
+    // only --no-validate lets a pattern long enough to be impossible through, so there it is a warning
+    let impossible = matcher.impossible_prefixes();
+    for pattern in &impossible {
+        eprintln!(
+            "{}No address can start with '{}': every 32 byte key encodes to a shorter or smaller base58 string after its leading '1's",
+            if args.no_validate { "Warning: " } else { "" },
+            in_alphabet(pattern)
+        );
+    }
+    if !impossible.is_empty() && !args.no_validate {
+        std::process::exit(1);
+    }
+
+    if args.resume_patterns {
+        let stored = stored_addresses(&patterns, &destinations, args.output.as_deref())
+            .unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            });
+        let (remaining, done) = resume_patterns(&matcher, &stored);
+        // a pattern's own file that doesn't satisfy it isn't this batch's output, and must not be overwritten
+        if let Some((pattern, path)) = destinations
+            .iter()
+            .find(|(pattern, path)| path.exists() && !done.iter().any(|&i| patterns[i] == *pattern))
+        {
+            eprintln!(
+                "Output file {} already exists and doesn't hold a keypair matching '{}'",
+                path.display(),
+                pattern
+            );
+            std::process::exit(1);
+        }
+        for &i in &done {
+            status!(
+                format,
+                "Note: '{}' already has a keypair from an earlier run; skipped",
+                patterns[i]
+            );
+        }
+        if remaining.is_empty() {
+            status!(
+                format,
+                "Every pattern already has a keypair; nothing to search"
+            );
+            return;
+        }
+        let (remaining_patterns, remaining_types): (Vec<String>, Vec<MatchType>) = remaining
+            .iter()
+            .map(|&i| (patterns[i].clone(), match_types[i]))
+            .unzip();
+        matcher = build_matcher(&remaining_patterns, remaining_types);
+    }
+
+    let secondary = args.secondary_encoding.map(|encoding| {
+        let pattern = match (&args.secondary_pattern, &args.find) {
+            (Some(pattern), _) => pattern,
+            (None, Some(find)) if !find.contains('|') => split_match_type(find).0,
+            _ => {
+                eprintln!(
+                    "--secondary-encoding needs --secondary-pattern when -f has alternatives"
+                );
+                std::process::exit(1);
+            }
+        };
+        SecondaryMatcher::new(encoding.encoding(), pattern, args.case_sensitivity).unwrap_or_else(
+            |e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            },
+        )
+    });
+
+    if args.no_secret
+        && args.output.is_none()
+        && args.db.is_none()
+        && !args.mint
+        && args.job_dir.is_none()
+        && destinations.is_empty()
+    {
+        eprintln!(
+            "Warning: --no-secret without --output; the secret of a found address won't be saved anywhere"
+        );
+    }
+
+    let confusable = confusable_addresses(&matcher);
+    if !confusable.is_empty() {
+        eprintln!("Warning: addresses matching this pattern may be confused with:");
+        for (name, address) in &confusable {
+            eprintln!("  {}: {}", name, address);
+        }
+        if args.strict {
+            eprintln!("Refusing to search in strict mode");
+            std::process::exit(1);
+        }
+    }
+
+    let start = Instant::now();
+
+    args.threads = start_thread_pool(args.threads);
+
+    if args.stream {
+        stream_matches(&args, &matcher, start);
+        return;
+    }
+
+    let make_source = |worker| worker_source(&args, worker, args.threads);
+
+    let job = args.job_dir.as_ref().map(|dir| {
+        let patterns: Vec<String> = matcher.patterns.iter().map(|p| in_alphabet(p)).collect();
+        JobDir::join(dir, &process_id(), &patterns).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        })
+    });
+    let control = args
+        .best_effort
+        .map_or_else(SearchControl::new, SearchControl::with_attempt_limit);
+    // every process of a job stops once one of them marks it done
+    let control = match &job {
+        Some(job) => control.with_stop_file(job.done_path()),
+        None => control,
+    };
+    let control = Arc::new(configure(control, &args));
+    // Ctrl-C normally ends the process at once; with a summary or lifetime stats wanted it stops the search instead,
+    // so the run still gets reported
+    let interrupt_watch = (wants_summary(&args) || args.stats_file.is_some()).then(|| {
+        install_interrupt_signal();
+        let control = control.clone();
+        std::thread::spawn(move || {
+            while !control.is_stopped() {
+                if INTERRUPTED.load(Ordering::Relaxed) {
+                    control.stop();
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+        })
+    });
+    // the secondary encoding makes a match rarer by an amount the estimate doesn't model, so no ETA is given then
+    let expected_attempts = if secondary.is_some() {
+        f64::NAN
+    } else {
+        estimate_attempts(&matcher)
+    };
+    // a batch or best effort search goes on after its first match, and a distributed one only counts this machine's
+    // attempts, so none of them can say how its attempts compare with the expected attempts
+    let single_match = !batch
+        && args.best_effort.is_none()
+        && args.coordinator.is_none()
+        && args.join.is_none()
+        && args.job_dir.is_none();
+    let reporter = reports_progress(&args).then(|| {
+        let control = control.clone();
+        let report = progress_report(&args, expected_attempts, single_match);
+        std::thread::spawn(move || report_progress(&control, start, &report))
+    });
+    // someone at a terminal is asked whether to take the closest miss before a timed search gives up
+    let closest = (args
+        .timeout
+        .is_some_and(|secs| secs > PROMPT_LEAD_MIN.as_secs())
+        && !args.no_prompt
+        && single_match
+        && secondary.is_none()
+        && io::stdin().is_terminal()
+        && io::stderr().is_terminal())
+    .then(|| {
+        let scorer = matcher.clone();
+        Arc::new(BestTracker::new(move |address: &[u8]| {
+            scorer
+                .closest_pattern(address)
+                .map_or(0, |(_, right)| right)
+        }))
+    });
+    let watchdog = args.timeout.map(|secs| {
+        let control = control.clone();
+        let deadline = start + Duration::from_secs(secs);
+        let prompt = closest.clone().map(|closest| (closest, matcher.clone()));
+        std::thread::spawn(move || match prompt {
+            Some((closest, matcher)) => {
+                offer_closest_before(&control, &closest, &matcher, start, deadline)
+            }
+            None => (stop_at_timeout(&control, deadline), false),
+        })
+    });
+    let coordinator = args.coordinator.as_ref().map(|addr| {
+        // reports from joiners are checked against our own patterns before they are trusted
+        let accept_matcher = matcher.clone();
+        Coordinator::start(addr, control.clone(), move |keypair| {
+            accept_matcher
+                .matched_pattern(keypair.pubkey().to_string().as_bytes())
+                .is_some()
+        })
+        .unwrap_or_else(|e| {
+            eprintln!("Cannot listen on {}: {}", addr, e);
+            std::process::exit(1);
+        })
+    });
+    let mut joiner = args.join.as_ref().map(|addr| {
+        Joiner::connect(addr, control.clone()).unwrap_or_else(|e| {
+            eprintln!("Cannot connect to coordinator at {}: {}", addr, e);
+            std::process::exit(1);
+        })
+    });
+
+    let (mut results, matcher, attempts) = if batch {
+        let batch_matcher = BatchMatcher::new(matcher);
+        let results = Mutex::new(Vec::new());
+        let attempts = search_each(
+            &batch_matcher,
+            args.threads,
+            make_source,
+            &control,
+            |keypair, source, attempts| {
+                let claimed = batch_matcher.claim(keypair.pubkey().to_string().as_bytes());
+                if !claimed.is_empty() {
+                    let patterns: Vec<&str> = claimed
+                        .iter()
+                        .map(|&i| batch_matcher.matcher.patterns[i].as_str())
+                        .collect();
+                    results.lock().unwrap().push(FoundKeypair {
+                        pattern: patterns.join("|"),
+                        keypair,
+                        mnemonic: source.last_mnemonic().cloned(),
+                        attempts,
+                        elapsed_secs: start.elapsed().as_secs_f64(),
+                        commit_nonce: None,
+                    });
+                }
+                batch_matcher.all_satisfied()
+            },
+        );
+        (
+            results.into_inner().unwrap(),
+            batch_matcher.matcher,
+            attempts,
+        )
+    } else if args.best_effort.is_some() {
+        let (best, attempts) = search_best_effort(&matcher, args.threads, make_source, &control);
+        let results = best
+            .map(|best| {
+                let address = best.keypair.pubkey().to_string();
+                let (pattern, matched) = matcher
+                    .longest_partial(address.as_bytes())
+                    .unwrap_or_default();
+                if matcher.matched_pattern(address.as_bytes()).is_some() {
+                    status!(format, "Best effort: full match for '{}'", in_alphabet(pattern));
+                } else {
+                    status!(
+                        format,
+                        "Best effort: no full match; the closest has {} of {} leading characters of '{}' right",
+                        matched,
+                        pattern.len(),
+                        in_alphabet(pattern)
+                    );
+                }
+                FoundKeypair {
+                    pattern: pattern.to_string(),
+                    keypair: best.keypair,
+                    mnemonic: best.mnemonic,
+                    attempts: best.attempts,
+                    elapsed_secs: start.elapsed().as_secs_f64(),
+                    commit_nonce: None,
+                }
+            })
+            .into_iter()
+            .collect();
+        (results, matcher, attempts)
+    } else {
+        let outcome = match &secondary {
+            Some(secondary) => search(
+                &DualMatcher {
+                    primary: &matcher,
+                    secondary,
+                },
+                args.threads,
+                make_source,
+                &control,
+            ),
+            None => match &closest {
+                Some(closest) => {
+                    search_keeping_closest(&matcher, closest, args.threads, make_source, &control)
+                }
+                None => search(&matcher, args.threads, make_source, &control),
+            },
+        };
+        let mut found = outcome.found;
+        let mut attempts = outcome.attempts;
+        if let Some(coordinator) = &coordinator {
+            coordinator.broadcast_stop();
+            if found.is_none()
+                && let Some(remote) = coordinator.take_remote()
+            {
+                status!(format, "Match reported by joiner {}", remote.peer);
+                attempts += remote.attempts;
+                found = Some((remote.keypair, None));
+            }
+        }
+        if let Some(joiner) = &mut joiner {
+            match &found {
+                Some((keypair, _)) => {
+                    if let Err(e) = joiner.report(keypair, attempts) {
+                        eprintln!(
+                            "Warning: could not report the match to the coordinator: {}",
+                            e
+                        );
+                    }
+                }
+                None => status!(format, "Search stopped by the coordinator"),
+            }
+        }
+        let results = found
+            .map(|(keypair, mnemonic)| FoundKeypair {
+                pattern: matcher
+                    .matched_pattern(keypair.pubkey().to_string().as_bytes())
+                    .unwrap_or_default()
+                    .to_string(),
+                keypair,
+                mnemonic,
+                attempts,
+                elapsed_secs: start.elapsed().as_secs_f64(),
+                commit_nonce: None,
+            })
+            .into_iter()
+            .collect::<Vec<_>>();
+        if let Some(job) = &job {
+            match results.first() {
+                Some(found) => match job.finish(found) {
+                    Ok(true) => status!(
+                        format,
+                        "Recorded the match in {} and marked the job done",
+                        job.result_path().display()
+                    ),
+                    Ok(false) => eprintln!(
+                        "Warning: another process finished the job first; this match is not in {}",
+                        job.result_path().display()
+                    ),
+                    Err(e) => eprintln!(
+                        "Warning: could not record the match in {}: {}",
+                        job.result_path().display(),
+                        e
+                    ),
+                },
+                None => {
+                    if let Some(id) = job.finished_by() {
+                        status!(format, "Search stopped: {} finished the job", id);
+                    }
+                }
+            }
+        }
+        (results, matcher, attempts)
+    };
+    if control.failure().is_some() {
+        let unfound = matcher.patterns.iter().map(|p| in_alphabet(p)).collect();
+        write_run_summary(
+            &args,
+            attempts,
+            start.elapsed(),
+            Vec::new(),
+            unfound,
+            ExitReason::Failed,
+        );
+    }
+    exit_if_failed(&control);
+    print_worker_totals(&control);
+    if args.commit {
+        for found in &mut results {
+            found.commit_nonce = Some(commit_nonce());
+        }
+    }
+    let elapsed = start.elapsed();
+    if let Some(reporter) = reporter {
+        control.stop();
+        reporter.join().unwrap();
+    }
+    let (timed_out, accepted_closest) = watchdog.map_or((false, false), |watchdog| {
+        control.stop();
+        watchdog.join().unwrap()
+    });
+    if let Some(interrupt_watch) = interrupt_watch {
+        control.stop();
+        interrupt_watch.join().unwrap();
+    }
+    let interrupted = INTERRUPTED.load(Ordering::Relaxed);
+    if accepted_closest
+        && let Some(best) = closest
+            .and_then(Arc::into_inner)
+            .and_then(BestTracker::into_best)
+    {
+        let address = best.keypair.pubkey().to_string();
+        let (pattern, right) = matcher
+            .closest_pattern(address.as_bytes())
+            .unwrap_or_default();
+        status!(
+            format,
+            "Accepted a partial match: {} of {} characters of '{}' right",
+            right,
+            pattern.len(),
+            in_alphabet(pattern)
+        );
+        results.push(FoundKeypair {
+            pattern: pattern.to_string(),
+            keypair: best.keypair,
+            mnemonic: best.mnemonic,
+            attempts,
+            elapsed_secs: elapsed.as_secs_f64(),
+            commit_nonce: args.commit.then(commit_nonce),
+        });
+    }
+    // only full matches count as found; an accepted or best effort partial leaves its pattern unfound
+    let full_matches: std::collections::HashSet<&str> = results
+        .iter()
+        .filter(|found| {
+            matcher
+                .matched_pattern(found.keypair.pubkey().to_string().as_bytes())
+                .is_some()
+        })
+        .flat_map(|found| found.pattern.split('|'))
+        .collect();
+    let (found_patterns, unfound_patterns): (Vec<&String>, Vec<&String>) = matcher
+        .patterns
+        .iter()
+        .partition(|pattern| full_matches.contains(pattern.as_str()));
+    let exit_reason = if accepted_closest {
+        ExitReason::AcceptedPartial
+    } else if args.best_effort.is_some() {
+        ExitReason::AttemptLimit
+    } else if unfound_patterns.is_empty() {
+        ExitReason::Found
+    } else if interrupted {
+        ExitReason::Interrupted
+    } else if timed_out {
+        ExitReason::Timeout
+    } else {
+        ExitReason::Stopped
+    };
+    let lifetime = args.stats_file.as_ref().and_then(|path| {
+        record_run(
+            path,
+            &config_key(&matcher),
+            &matcher.patterns,
+            attempts,
+            elapsed.as_secs_f64(),
+        )
+        .inspect_err(|e| {
+            eprintln!(
+                "Warning: could not update the stats in {}: {}",
+                path.display(),
+                e
+            )
+        })
+        .ok()
+    });
+    let summarize = || {
+        write_run_summary(
+            &args,
+            attempts,
+            elapsed,
+            found_patterns.iter().map(|p| in_alphabet(p)).collect(),
+            unfound_patterns.iter().map(|p| in_alphabet(p)).collect(),
+            exit_reason,
+        )
+    };
+
+    if results.is_empty() {
+        status!(format, "No matching keypair found");
+    } else {
+        match format {
+            OutputFormat::Human | OutputFormat::QrPng => {
+                for found in &results {
+                    print_found(found, &matcher, &args);
+                }
+            }
+            OutputFormat::Csv => {
+                if let Err(e) = write_csv(io::stdout(), &results, !args.no_secret) {
+                    eprintln!("Failed to write CSV output: {}", e);
+                    summarize();
+                    std::process::exit(1);
+                }
+            }
+            OutputFormat::Json => {
+                for found in &results {
+                    println!("{}", json_record(found, !args.no_secret));
+                }
+            }
+            OutputFormat::Paper => print_paper_wallets(&results),
+            OutputFormat::Env => {
+                for found in &results {
+                    println!("{}", env_assignments(found, !args.no_secret));
+                }
+            }
+            OutputFormat::Template => print_templates(&results, &args),
+            OutputFormat::Raw => {
+                if args.output.is_none() {
+                    print_raw(&results[0]);
+                }
+            }
+        }
+
+        // a mint keypair is always saved, since spl-token reads it from a file
+        let mint_file = (args.mint && args.output.is_none()).then(|| {
+            PathBuf::from(mint_keypair_file_name(
+                &results[0].keypair.pubkey().to_string(),
+            ))
+        });
+        if let Some(path) = args.output.as_ref().or(mint_file.as_ref()) {
+            match write_output(path, &args, &results) {
+                Ok(message) => status!(format, "{}", message),
+                Err(e) => {
+                    eprintln!("Failed to write {}: {}", path.display(), e);
+                    summarize();
+                    std::process::exit(1);
+                }
+            }
+            if args.mint {
+                if args.token_2022 {
+                    match check_mint_keypair_file(path, &results[0].keypair) {
+                        Ok(()) => status!(
+                            format,
+                            "Checked {}: it signs for the address, so it can create a Token-2022 mint",
+                            path.display()
+                        ),
+                        Err(e) => {
+                            eprintln!("{} can't create the mint: {}", path.display(), e);
+                            summarize();
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                status!(
+                    format,
+                    "Create the token with this address as its mint: {}",
+                    token_program(&args).create_command(path)
+                );
+            }
+        }
+        if let Some(path) = &args.db {
+            match save_to_db(path, &results) {
+                Ok(message) => status!(format, "{}", message),
+                Err(e) => {
+                    eprintln!("Failed to insert into {}: {}", path.display(), e);
+                    summarize();
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        for &extra in &args.formats[1..] {
+            let path = PathBuf::from(format_file_name(
+                &results[0].keypair.pubkey().to_string(),
+                extra,
+            ));
+            match write_format_file(&path, extra, &results[0]) {
+                Ok(()) => status!(
+                    format,
+                    "Wrote {} output to {}",
+                    format_name(extra),
+                    path.display()
+                ),
+                Err(e) => {
+                    eprintln!("Failed to write {}: {}", path.display(), e);
+                    summarize();
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        for found in &results {
+            let claimed: Vec<&str> = found.pattern.split('|').collect();
+            for (pattern, path) in &destinations {
+                if !claimed.contains(&to_base58(pattern).as_str()) {
+                    continue;
+                }
+                match save_results(
+                    path,
+                    false,
+                    args.verify_after_write,
+                    std::slice::from_ref(found),
+                ) {
+                    Ok(()) => status!(
+                        format,
+                        "Wrote the keypair for '{}' to {}",
+                        pattern,
+                        path.display()
+                    ),
+                    Err(e) => {
+                        eprintln!("Failed to write {}: {}", path.display(), e);
+                        summarize();
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+
+        // the keypair is already printed and saved, so a failed airdrop only costs the airdrop
+        if let (Some(sol), Some(cluster)) = (args.airdrop, args.cluster) {
+            status!(format, "Requesting {} SOL on {}...", sol, cluster.moniker());
+            match request_airdrop(&results[0].keypair, sol, cluster) {
+                Ok(balance) => status!(format, "Balance: {}", balance),
+                Err(e) => eprintln!("Warning: airdrop failed: {}", e),
+            }
+        }
+    }
+    status!(format, "Took {:.2} minutes", elapsed.as_secs_f64() / 60.0);
+    status!(format, "Attempts: {}", attempts);
+    if let Some(lifetime) = lifetime {
+        status!(
+            format,
+            "Lifetime: {} attempts in {} over {} run(s) of this search",
+            lifetime.attempts,
+            format_duration(lifetime.elapsed_secs),
+            lifetime.runs
+        );
+    }
+    if single_match
+        && let Some(luck) = luck_summary(
+            attempts,
+            expected_attempts,
+            !results.is_empty() && !accepted_closest,
+        )
+    {
+        status!(format, "Luck: {}", luck);
+    }
+    if single_match
+        && !results.is_empty()
+        && !accepted_closest
+        && let Some(variance) = variance_summary(attempts, expected_attempts)
+    {
+        status!(format, "Variance: {}", variance);
+    }
+    if matcher.match_types.iter().all(|&t| t == MatchType::Prefix) {
+        let skips = matcher.leading_one_skips();
+        status!(
+            format,
+            "Skipped due to leading '1': {} ({:.3}% of attempts)",
+            skips,
+            skips as f64 * 100.0 / attempts.max(1) as f64
+        );
+    }
+
+    if timed_out {
+        // in batch mode the patterns that did get an address need no advice
+        let found: std::collections::HashSet<&str> = results
+            .iter()
+            .flat_map(|found| found.pattern.split('|'))
+            .collect();
+        let (missing, missing_types): (Vec<String>, Vec<MatchType>) = matcher
+            .patterns
+            .iter()
+            .zip(&matcher.match_types)
+            .filter(|(pattern, _)| !found.contains(pattern.as_str()))
+            .map(|(pattern, &match_type)| (pattern.clone(), match_type))
+            .unzip();
+        if !missing.is_empty() && (batch || results.is_empty()) {
+            eprintln!(
+                "Timed out after {} without a match for {}",
+                format_duration(elapsed.as_secs_f64()),
+                missing.join("|")
+            );
+            if args.alphabet.is_none() {
+                let rate = attempts as f64 / elapsed.as_secs_f64();
+                let missing_matcher =
+                    PatternMatcher::new(missing, matcher.match_type, matcher.case_sensitive, false)
+                        .with_match_types(missing_types)
+                        .with_flex_level(matcher.flex_level)
+                        .with_canonical_case(matcher.canonical_case)
+                        .with_collapse_case(matcher.collapse_case)
+                        .with_fingerprint_len(matcher.fingerprint_len)
+                        .with_at_range(matcher.at_range.clone())
+                        .with_window(matcher.window.clone())
+                        .with_max_distance(matcher.max_distance);
+                suggest_easier(&missing_matcher, rate, elapsed);
+            }
+        }
+    }
+
+    summarize();
+    if interrupted && results.is_empty() {
+        std::process::exit(130);
+    }
+    if timed_out && results.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+// Addresses an earlier run of the batch stored for each pattern, for resume_patterns: the address in the pattern's own
+// output file if it has one, otherwise every address in the JSON lines output file
+fn stored_addresses(
+    patterns: &[String],
+    destinations: &[(String, PathBuf)],
+    output: Option<&Path>,
+) -> Result<Vec<Vec<String>>, String> {
+    let shared = match output {
+        Some(path) if path.exists() => stored_pubkeys(path)
+            .map_err(|e| format!("Cannot read output file {}: {}", path.display(), e))?,
+        _ => Vec::new(),
+    };
+    patterns
+        .iter()
+        .map(
+            |pattern| match destinations.iter().find(|(p, _)| p == pattern) {
+                Some((_, path)) if path.exists() => {
+                    let keypair = solana_keypair::read_keypair_file(path).map_err(|e| {
+                        format!("Cannot read keypair file {}: {}", path.display(), e)
+                    })?;
+                    Ok(vec![keypair.pubkey().to_string()])
+                }
+                Some(_) => Ok(Vec::new()),
+                None => Ok(shared.clone()),
+            },
+        )
+        .collect()
+}
+
+// Most easier variants suggested after a timeout
+const MAX_SUGGESTIONS: usize = 3;
+
+// Prints to stderr the easier variants of the search that should finish at this rate within the time just spent,
+// or the easiest ones if none would
+fn suggest_easier(matcher: &PatternMatcher, rate: f64, waited: Duration) {
+    let budget = rate * waited.as_secs_f64();
+    let suggestions = suggest_relaxations(matcher, budget, MAX_SUGGESTIONS);
+    if suggestions.is_empty() {
+        return;
+    }
+    if suggestions[0].expected_attempts <= budget {
+        eprintln!("Easier searches likely to finish in that time:");
+    } else {
+        eprintln!("No single change is likely to finish in that time; the easiest are:");
+    }
+    for suggestion in suggestions {
+        eprintln!(
+            "  {}: {:.0} expected attempts ({}), about {} at {:.0} addresses/second",
+            suggestion.change,
+            suggestion.expected_attempts,
+            difficulty_label(suggestion.expected_attempts),
+            format_duration(suggestion.expected_attempts / rate),
+            rate
+        );
+    }
+}
+
+// A batch of patterns, from --patterns-file or --patterns-url, finds one keypair for each
+fn is_batch(args: &Args) -> bool {
+    args.patterns_file.is_some() || args.patterns_url.is_some()
+}
+
+// The QR image takes the place of the keypair file, and holds a single address
+fn check_qr_png(args: &Args) -> Result<(), String> {
+    if args.output.is_none() {
+        return Err("--format qr-png needs --output <file.png> for the image".to_string());
+    }
+    if args.append || is_batch(args) || args.stream || args.mine_best.is_some() {
+        return Err(
+            "--format qr-png writes a single address; it can't be combined with --append, --patterns-file, --stream or --mine-best"
+                .to_string(),
+        );
+    }
+    if args.no_secret {
+        return Err(
+            "--format qr-png stores only the address, so with --no-secret the secret would be lost"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+// Opens a commitment for the open-commitment subcommand, exiting with status 1 if it doesn't open
+fn run_open_commitment(args: &OpenCommitmentArgs) {
+    let keypair = solana_keypair::read_keypair_file(&args.keypair).unwrap_or_else(|e| {
+        eprintln!("Cannot read {}: {}", args.keypair.display(), e);
+        std::process::exit(1);
+    });
+    match opens(&args.commitment, &args.nonce, &keypair) {
+        Ok(true) => println!(
+            "The commitment opens to the keypair of {}",
+            keypair.pubkey()
+        ),
+        Ok(false) => {
+            println!(
+                "The commitment does not open to the keypair of {} with this nonce",
+                keypair.pubkey()
+            );
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Invalid nonce: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// A nonce for a --commit commitment; without one there is nothing to commit with, so a failing generator ends the run
+fn commit_nonce() -> [u8; NONCE_LEN] {
+    new_nonce().unwrap_or_else(|e| {
+        eprintln!("Cannot create a commitment nonce: {}", e);
+        std::process::exit(1);
+    })
+}
+
+// Formats after the first in --format each get a file of their own for the one found keypair
+fn check_extra_formats(args: &Args) -> Result<(), String> {
+    let extras = &args.formats[1..];
+    if extras.contains(&OutputFormat::Human) {
+        return Err(
+            "human output is for the console; it can only be the first of several formats in --format".to_string(),
+        );
+    }
+    for (i, format) in args.formats.iter().enumerate() {
+        if args.formats[..i].contains(format) {
+            return Err(format!("--format lists {} twice", format_name(*format)));
+        }
+    }
+    if is_batch(args)
+        || args.stream
+        || args.mine_best.is_some()
+        || args.hex_prefix.is_some()
+        || args.numeric_prefix.is_some()
+    {
+        return Err(
+            "several formats in --format are written for a single found keypair; they can't be combined with --patterns-file, --stream, --mine-best, --hex-prefix or --numeric-prefix"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+// The name --format takes for a format, e.g. "qr-png"
+fn format_name(format: OutputFormat) -> String {
+    format
+        .to_possible_value()
+        .map_or_else(String::new, |value| value.get_name().to_string())
+}
+
+// Raw output is a single keypair in binary, which is garbage on a terminal
+fn check_raw(args: &Args) -> Result<(), String> {
+    if args.append || is_batch(args) || args.stream || args.mine_best.is_some() {
+        return Err(
+            "--format raw writes a single keypair; it can't be combined with --append, --patterns-file, --stream or --mine-best"
+                .to_string(),
+        );
+    }
+    if args.no_secret {
+        return Err(
+            "--format raw is the secret key; it can't be combined with --no-secret".to_string(),
+        );
+    }
+    if args.output.is_none() && io::stdout().is_terminal() {
+        return Err(
+            "--format raw writes binary; give --output <file> or redirect stdout to a file or pipe"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+// Writes the raw keypair bytes to stdout, checked by check_raw not to be a terminal
+fn print_raw(found: &FoundKeypair) {
+    if let Err(e) = write_raw(io::stdout().lock(), &found.keypair) {
+        eprintln!("Failed to write raw output: {}", e);
+        std::process::exit(1);
+    }
+    status!(
+        OutputFormat::Raw,
+        "Wrote the {} byte keypair of {} to stdout",
+        RAW_KEYPAIR_LEN,
+        found.keypair.pubkey()
+    );
+}
+
+// Writes the raw keypair file, reading it back once when verify is set
+fn save_raw(path: &Path, verify: bool, keypair: &Keypair) -> Result<(), String> {
+    write_raw_keypair_file(path, keypair).map_err(|e| e.to_string())?;
+    if verify {
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+        let read = read_raw_keypair(&bytes)?;
+        if read.to_bytes() != keypair.to_bytes() {
+            return Err("file contents don't match the found keypair".to_string());
+        }
+    }
+    Ok(())
+}
+
+// Writes the found keypair(s) to --output: the QR image in qr-png format, the binary keypair in raw format,
+// otherwise the keypair file (encrypted with --age-recipient) or JSON lines file
+fn write_output(path: &Path, args: &Args, results: &[FoundKeypair]) -> Result<String, String> {
+    if args.format == OutputFormat::QrPng {
+        let (width, height) = write_qr_png(path, &results[0].keypair.pubkey().to_string())?;
+        return Ok(format!(
+            "Wrote {}x{} QR code of the address to {}",
+            width,
+            height,
+            path.display()
+        ));
+    }
+    if args.format == OutputFormat::Raw {
+        let keypair = &results[0].keypair;
+        save_raw(path, args.verify_after_write, keypair)?;
+        return Ok(format!(
+            "Wrote the {} byte keypair of {} to {}",
+            RAW_KEYPAIR_LEN,
+            keypair.pubkey(),
+            path.display()
+        ));
+    }
+    if let Some(recipient) = &args.age_recipient {
+        let keypair = &results[0].keypair;
+        write_age_keypair_file(path, keypair, &parse_age_recipient(recipient)?)
+            .map_err(|e| e.to_string())?;
+        return Ok(format!(
+            "Wrote the keypair of {} encrypted to {} to {}",
+            keypair.pubkey(),
+            recipient,
+            path.display()
+        ));
+    }
+    save_results(path, args.append, args.verify_after_write, results)?;
+    Ok(format!(
+        "Wrote {} keypair(s) to {}",
+        results.len(),
+        path.display()
+    ))
+}
+
+// Opens --db before the search starts, so a path that can't hold a database fails now rather than after the match
+fn check_db(args: &Args) {
+    if let Some(path) = &args.db
+        && let Err(e) = open_db(path)
+    {
+        eprintln!("Cannot use {} as a database: {}", path.display(), e);
+        std::process::exit(1);
+    }
+}
+
+// Inserts the found keypair(s) into the --db database, one transaction per keypair
+fn save_to_db(path: &Path, results: &[FoundKeypair]) -> Result<String, String> {
+    let mut conn = open_db(path)?;
+    let found_at = unix_now();
+    for found in results {
+        insert_keypair(&mut conn, found, found_at).map_err(|e| e.to_string())?;
+    }
+    Ok(format!(
+        "Inserted {} keypair(s) into {}",
+        results.len(),
+        path.display()
+    ))
+}
+
+// Writes the results to the output file. With verify the file is read back afterwards, and anything that didn't
+// make it intact is written once more before giving up.
+fn save_results(
+    path: &Path,
+    append: bool,
+    verify: bool,
+    results: &[FoundKeypair],
+) -> Result<(), String> {
+    if append {
+        append_jsonl(path, results).map_err(|e| e.to_string())?;
+    } else {
+        write_keypair_file(path, &results[0].keypair).map_err(|e| e.to_string())?;
+    }
+    if !verify {
+        return Ok(());
+    }
+
+    let check = || {
+        if append {
+            match missing_from_jsonl(path, results) {
+                Ok(missing) if missing.is_empty() => Ok(()),
+                Ok(missing) => Err(format!("{} keypair(s) missing or corrupt", missing.len())),
+                Err(e) => Err(e.to_string()),
+            }
+        } else {
+            verify_keypair_file(path, &results[0].keypair)
+        }
+    };
+    let Err(e) = check() else {
+        return Ok(());
+    };
+    eprintln!(
+        "Warning: verifying {} failed ({}); writing again",
+        path.display(),
+        e
+    );
+
+    if append {
+        let missing = missing_from_jsonl(path, results).unwrap_or_default();
+        append_jsonl(path, missing.iter().map(|&i| &results[i])).map_err(|e| e.to_string())?;
+    } else {
+        std::fs::remove_file(path).map_err(|e| e.to_string())?;
+        write_keypair_file(path, &results[0].keypair).map_err(|e| e.to_string())?;
+    }
+    check().map_err(|e| format!("verification failed after retrying: {}", e))
+}
+
+// Prints the expected attempts and time for a pattern, using this machine's benchmarked rate, then exits
+fn run_estimate(args: &EstimateArgs) {
+    if let Err(e) = check_canonical_case(args.case_sensitivity, args.canonical_case) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if args.compare_flexible && args.case_sensitivity {
+        eprintln!(
+            "--compare-flexible needs -s false; a case sensitive search never uses lookalikes"
+        );
+        std::process::exit(1);
+    }
+    let entries: Vec<String> = args.find.split('|').map(str::to_string).collect();
+    let (patterns, match_types) = split_match_types(&entries, args.match_type);
+    if let Err(e) = check_max_distance(&patterns, args.max_distance) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    let (fingerprint_len, at_range, window) = window_options(
+        &patterns,
+        &match_types,
+        args.fingerprint_len,
+        args.at_range.clone(),
+        args.window.clone(),
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    let build_matcher = |flex_level: u8| {
+        PatternMatcher::new(
+            patterns.clone(),
+            args.match_type,
+            args.case_sensitivity,
+            false,
+        )
+        .with_match_types(match_types.clone())
+        .with_flex_level(flex_level)
+        .with_fingerprint_len(fingerprint_len)
+        .with_at_range(at_range.clone())
+        .with_window(window.clone())
+        .with_canonical_case(args.canonical_case)
+        .with_max_distance(args.max_distance)
+    };
+    let matcher = build_matcher(flex_level(args.flexible_chars, args.flex_level));
+    let expected = estimate_attempts(&matcher);
+
+    println!("Pattern: {}", args.find);
+    println!("  Match Type: {:?}", args.match_type);
+    println!("  Case Sensitivity: {}", args.case_sensitivity);
+    if args.canonical_case {
+        println!("  Canonical Case: true");
+    }
+    println!(
+        "  Flexible Char Set: {}",
+        flexible_label(args.flexible_chars, args.flex_level)
+    );
+    if args.max_distance > 0 {
+        println!("  Max Distance: {} wrong character(s)", args.max_distance);
+    }
+    println!("Expected attempts: {:.0}", expected);
+    println!("Difficulty: {}", difficulty_label(expected));
+
+    let threads = start_thread_pool(args.threads);
+    println!(
+        "Benchmarking {} thread(s) for {} seconds...",
+        threads, args.benchmark_secs
+    );
+    let rate = benchmark_rate(
+        &matcher,
+        threads,
+        |_| keypair_source(args.hd, args.entropy),
+        Duration::from_secs(args.benchmark_secs),
+    );
+    println!("Rate: {:.0} addresses/second", rate);
+    println!("Expected time: {}", format_duration(expected / rate));
+    if let Some(watts) = args.watts
+        && let Some(kwh) = expected_energy_kwh(expected, rate, watts)
+    {
+        println!("Expected energy: {} at {} W", format_energy(kwh), watts);
+        if let Some(price) = args.price_per_kwh {
+            println!("Expected cost: {:.2} at {} per kWh", kwh * price, price);
+        }
+    }
+    for (label, secs) in ODDS_WINDOWS {
+        let attempts = (rate * secs) as u64;
+        println!(
+            "Chance within {}: {:.1}%",
+            label,
+            match_probability(&matcher, attempts) * 100.0
+        );
+    }
+
+    if args.compare_flexible {
+        // with lookalikes off on the command line, compare against the level they would have had
+        let level = if args.flex_level == 0 {
+            DEFAULT_FLEX_LEVEL
+        } else {
+            args.flex_level
+        };
+        println!(
+            "Comparing exact and flexible matching for {} seconds each...",
+            args.benchmark_secs
+        );
+        let [exact_rate, flexible_rate] = [0, level].map(|level| {
+            benchmark_rate(
+                &build_matcher(level),
+                threads,
+                |_| keypair_source(args.hd, args.entropy),
+                Duration::from_secs(args.benchmark_secs),
+            )
+        });
+        println!("Exact rate (-l false): {:.0} addresses/second", exact_rate);
+        println!(
+            "Flexible rate (--flex-level {}): {:.0} addresses/second",
+            level, flexible_rate
+        );
+        let overhead = flexible_overhead(exact_rate, flexible_rate);
+        println!(
+            "Flexible matching overhead: {:.1}% {}{}",
+            overhead.abs(),
+            if overhead >= 0.0 { "slower" } else { "faster" },
+            if overhead.abs() < AUTOTUNE_TOLERANCE * 100.0 {
+                " (within run to run noise)"
+            } else {
+                ""
+            }
+        );
+    }
+}
+
+// Search durations estimate reports the odds of a match for
+const ODDS_WINDOWS: [(&str, f64); 4] = [
+    ("1 minute", 60.0),
+    ("10 minutes", 600.0),
+    ("1 hour", 3600.0),
+    ("1 day", 86400.0),
+];
+
+// Number forms of the raw public key bytes that --hex-prefix and --numeric-prefix search instead of the address
+#[derive(Clone, Copy)]
+enum KeyView {
+    Hex,
+    Decimal,
+}
+
+impl KeyView {
+    fn label(self) -> &'static str {
+        match self {
+            KeyView::Hex => "Hex",
+            KeyView::Decimal => "Decimal",
+        }
+    }
+
+    fn render(self, pubkey_bytes: &[u8]) -> String {
+        match self {
+            KeyView::Hex => to_hex(pubkey_bytes),
+            KeyView::Decimal => to_decimal(pubkey_bytes),
+        }
+    }
+}
+
+// Searches for a public key whose number form in view starts with the prefix, printing that form next to the base58
+// address
+fn view_search<M: Matcher + Sync>(
+    args: &Args,
+    matcher: &M,
+    view: KeyView,
+    prefix: &str,
+    expected_attempts: f64,
+) {
+    let format = args.format;
+
+    status!(format, "Now searching with the following config:");
+    status!(format, "  {} Prefix: {}", view.label(), prefix);
+    status!(format, "  Threads: {}", args.threads);
+    if let Some(percent) = args.cpu_limit {
+        status!(format, "  CPU Limit: {}% per thread", percent);
+    }
+    status!(format, "  Expected Attempts: {:.0}", expected_attempts);
+
+    if let Some(path) = &args.output {
+        if let Err(e) = check_output_dir(path, args.mkdir) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        if !args.append && path.exists() {
+            eprintln!(
+                "Output file {} already exists; use --append to add to it",
+                path.display()
+            );
+            std::process::exit(1);
+        }
+    }
+    check_db(args);
+
+    let start = Instant::now();
+    let threads = start_thread_pool(args.threads);
+    let control = configure(SearchControl::new(), args);
+    let outcome = search(
+        matcher,
+        threads,
+        |worker| worker_source(args, worker, threads),
+        &control,
+    );
+    exit_if_failed(&control);
+    print_worker_totals(&control);
+    let results: Vec<FoundKeypair> = outcome
+        .found
+        .map(|(keypair, mnemonic)| FoundKeypair {
+            pattern: prefix.to_string(),
+            keypair,
+            mnemonic,
+            attempts: outcome.attempts,
+            elapsed_secs: start.elapsed().as_secs_f64(),
+            commit_nonce: None,
+        })
+        .into_iter()
+        .collect();
+
+    match format {
+        OutputFormat::Human | OutputFormat::QrPng => {
+            for found in &results {
+                println!("Found address: {}", found.keypair.pubkey());
+                println!(
+                    "{}: {}",
+                    view.label(),
+                    view.render(found.keypair.pubkey().as_ref())
+                );
+                if !args.no_secret {
+                    println!("KP: {}", found.keypair.to_base58_string());
+                    if let Some(mnemonic) = &found.mnemonic {
+                        println!("Seed phrase: {}", mnemonic);
+                        println!("Derivation path: {}", HD_DERIVATION_PATH);
+                    }
+                }
+                if args.show_derivation {
+                    println!("{}", Derivation::new(&found.keypair).lines());
+                }
+            }
+        }
+        OutputFormat::Csv => {
+            if let Err(e) = write_csv(io::stdout(), &results, !args.no_secret) {
+                eprintln!("Failed to write CSV output: {}", e);
+                std::process::exit(1);
+            }
+        }
+        OutputFormat::Json => {
+            for found in &results {
+                let mut record = json_record(found, !args.no_secret);
+                record[view.label().to_ascii_lowercase()] =
+                    view.render(found.keypair.pubkey().as_ref()).into();
+                println!("{}", record);
+            }
+        }
+        OutputFormat::Paper => print_paper_wallets(&results),
+        OutputFormat::Env => {
+            for found in &results {
+                println!("{}", env_assignments(found, !args.no_secret));
+            }
+        }
+        OutputFormat::Template => print_templates(&results, args),
+        OutputFormat::Raw => {
+            if let (Some(found), None) = (results.first(), &args.output) {
+                print_raw(found);
+            }
+        }
+    }
+    if let Some(path) = &args.output
+        && !results.is_empty()
+    {
+        match write_output(path, args, &results) {
+            Ok(message) => status!(format, "{}", message),
+            Err(e) => {
+                eprintln!("Failed to write {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(path) = &args.db
+        && !results.is_empty()
+    {
+        match save_to_db(path, &results) {
+            Ok(message) => status!(format, "{}", message),
+            Err(e) => {
+                eprintln!("Failed to insert into {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+    status!(
+        format,
+        "Took {:.2} minutes",
+        start.elapsed().as_secs_f64() / 60.0
+    );
+    status!(format, "Attempts: {}", outcome.attempts);
+    if let Some(luck) = luck_summary(outcome.attempts, expected_attempts, !results.is_empty()) {
+        status!(format, "Luck: {}", luck);
+    }
+    if !results.is_empty()
+        && let Some(variance) = variance_summary(outcome.attempts, expected_attempts)
+    {
+        status!(format, "Variance: {}", variance);
+    }
+}
+
+// Tries a fixed number of addresses and prints the one with the longest run of repeated leading characters
+fn mine_best(args: &Args, budget: u64) {
+    if budget == 0 {
+        eprintln!("--mine-best needs at least one attempt");
+        std::process::exit(1);
+    }
+
+    println!("Mining the best of {} addresses:", budget);
+    println!("  Threads: {}", args.threads);
+    if let Some(percent) = args.cpu_limit {
+        println!("  CPU Limit: {}% per thread", percent);
+    }
+    println!("  Score: longest run of repeated leading characters");
+
+    let start = Instant::now();
+    let threads = start_thread_pool(args.threads);
+
+    let tracker = BestTracker::new(leading_run);
+    let control = configure(SearchControl::with_attempt_limit(budget), args);
+    let attempts = search_each(
+        &tracker,
+        threads,
+        |worker| worker_source(args, worker, threads),
+        &control,
+        |keypair, source, attempts| {
+            tracker.offer(keypair, source.last_mnemonic().cloned(), attempts);
+            false
+        },
+    );
+    exit_if_failed(&control);
+    print_worker_totals(&control);
+    let elapsed = start.elapsed();
+
+    if let Some(best) = tracker.into_best() {
+        let address = best.keypair.pubkey().to_string();
+        println!("Best address: {}", address);
+        println!("Preview: {}", preview(&address, 0, best.score, args));
+        println!(
+            "Score: {} (leading \"{}\", found after {} attempts)",
+            best.score,
+            &address[..best.score],
+            best.attempts
+        );
+        println!("KP: {}", best.keypair.to_base58_string());
+        if let Some(mnemonic) = &best.mnemonic {
+            println!("Seed phrase: {}", mnemonic);
+            println!("Derivation path: {}", HD_DERIVATION_PATH);
+        }
+    }
+    println!("Took {:.2} minutes", elapsed.as_secs_f64() / 60.0);
+    println!("Attempts: {}", attempts);
+}
+
+// The token program the --mint keypair is for
+fn token_program(args: &Args) -> TokenProgram {
+    if args.token_2022 {
+        TokenProgram::Token2022
+    } else {
+        TokenProgram::Token
+    }
+}
+
+// Human output for one found keypair
+fn print_found(found: &FoundKeypair, matcher: &PatternMatcher, args: &Args) {
+    if matcher.patterns.len() > 1 {
+        println!("Matched pattern: {}", in_alphabet(&found.pattern));
+    }
+    let pubkey = found.keypair.pubkey().to_string();
+    let label = if args.mint {
+        token_program(args).address_label()
+    } else {
+        "Found address"
+    };
+    println!("{}: {}", label, in_alphabet(&pubkey));
+    if args.alphabet.is_some() {
+        println!("Base58 address: {}", pubkey);
+    }
+    if let Some(encoding) = args.secondary_encoding {
+        println!(
+            "{:?} form: {}",
+            encoding,
+            encoding.encoding().encode(found.keypair.pubkey().as_ref())
+        );
+    }
+    // a batch result may satisfy several patterns, joined with '|'
+    for pattern in found.pattern.split('|') {
+        if let Some(start) = matcher.match_start(pubkey.as_bytes(), pattern) {
+            println!(
+                "Preview: {}",
+                preview(&in_alphabet(&pubkey), start, pattern.len(), args)
+            );
+        }
+    }
+    // --compare-match asks for the detail even when the match can only be exact
+    if !args.case_sensitivity || args.max_distance > 0 || args.compare_match {
+        print_match_detail(&pubkey, &found.pattern, matcher, args.compare_match);
+    }
+    if args.byte_sum.is_some() {
+        println!("Byte sum: {}", byte_sum(found.keypair.pubkey().as_ref()));
+    }
+    if let Some(nonce) = &found.commit_nonce {
+        println!("Commitment: {}", commitment(&found.keypair, nonce));
+    }
+    if !args.no_secret {
+        println!("KP: {}", found.keypair.to_base58_string());
+        if let Some(mnemonic) = &found.mnemonic {
+            println!("Seed phrase: {}", mnemonic);
+            println!("Derivation path: {}", HD_DERIVATION_PATH);
+        }
+    }
+    if args.show_derivation {
+        println!("{}", Derivation::new(&found.keypair).lines());
+    }
+}
+
+// One line per keypair with --template, filled in by render_template
+fn print_templates(results: &[FoundKeypair], args: &Args) {
+    if let Some(template) = &args.template {
+        for found in results {
+            println!("{}", render_template(template, found));
+        }
+    }
+}
+
+// One paper wallet block per keypair, each followed by a blank line so consecutive blocks stay apart
+fn print_paper_wallets(results: &[FoundKeypair]) {
+    for found in results {
+        println!("{}\n", paper_wallet(found));
+    }
+}
+
+// Applies --cpu-limit to a search's control
+fn limit_cpu(control: SearchControl, cpu_limit: Option<u8>) -> SearchControl {
+    match cpu_limit {
+        Some(percent) => control.with_cpu_limit(percent),
+        None => control,
+    }
+}
+
+// Sets up a search's control with the options every kind of search shares: --cpu-limit, pausing by signal, and the
+// per-thread counts -v reports
+fn configure(control: SearchControl, args: &Args) -> SearchControl {
+    let control = pausable(limit_cpu(control, args.cpu_limit));
+    if args.verbose > 0 {
+        control.with_worker_stats()
+    } else {
+        control
+    }
+}
+
+// Writes how many candidates each worker thread tried, tagged with its rayon thread index, to stderr. previous holds
+// the counts of the last call, so every line also shows what the thread added since; a thread far below the rest is
+// a straggler.
+fn print_worker_attempts(control: &SearchControl, previous: &mut HashMap<usize, u64>) {
+    for (thread, attempts) in control.worker_attempts() {
+        let before = previous.insert(thread, attempts).unwrap_or(0);
+        eprintln!(
+            "[thread {}] {} attempts (+{})",
+            thread,
+            attempts,
+            attempts - before
+        );
+    }
+}
+
+// Once a search is over, writes each worker thread's final count and its share of all attempts to stderr; nothing
+// unless -v turned the counts on
+fn print_worker_totals(control: &SearchControl) {
+    let total = control.attempts().max(1);
+    for (thread, attempts) in control.worker_attempts() {
+        eprintln!(
+            "[thread {}] {} attempts ({:.1}% of all)",
+            thread,
+            attempts,
+            attempts as f64 * 100.0 / total as f64
+        );
+    }
+}
+
+// Set by SIGUSR1 and cleared by SIGUSR2; every search's workers hold still while it is set
+static PAUSE_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+// Lets every search be paused through PAUSE_REQUESTED
+fn pausable(control: SearchControl) -> SearchControl {
+    control.with_pause_flag(&PAUSE_REQUESTED)
+}
+
+// SIGUSR1 pauses the search and SIGUSR2 resumes it. The handler only flips the flag and writes a fixed message with
+// write(2), both of which are safe inside a signal handler.
+#[cfg(unix)]
+fn install_pause_signals() {
+    extern "C" fn on_signal(signal: libc::c_int) {
+        let (paused, message): (bool, &[u8]) = if signal == libc::SIGUSR1 {
+            (true, b"Search paused; send SIGUSR2 to resume\n")
+        } else {
+            (false, b"Search resumed\n")
+        };
+        PAUSE_REQUESTED.store(paused, Ordering::Relaxed);
+        // SAFETY: write(2) is async-signal-safe and the message is a static buffer of the given length
+        unsafe {
+            libc::write(libc::STDERR_FILENO, message.as_ptr().cast(), message.len());
+        }
+    }
+    let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    // SAFETY: the handler only touches an atomic and calls write(2)
+    unsafe {
+        libc::signal(libc::SIGUSR1, handler);
+        libc::signal(libc::SIGUSR2, handler);
+    }
+}
+
+// There are no user signals to pause with outside Unix
+#[cfg(not(unix))]
+fn install_pause_signals() {}
+
+// Whether --summary or --summary-file asks for a run summary
+fn wants_summary(args: &Args) -> bool {
+    args.summary || args.summary_file.is_some()
+}
+
+// Writes the run summary (see run_summary) to --summary-file, or to stderr with --summary. A summary file that can't
+// be written only warns, since the search itself is over.
+fn write_run_summary(
+    args: &Args,
+    attempts: u64,
+    elapsed: Duration,
+    found: Vec<String>,
+    unfound: Vec<String>,
+    reason: ExitReason,
+) {
+    if !wants_summary(args) {
+        return;
+    }
+    let summary = run_summary(attempts, elapsed.as_secs_f64(), &found, &unfound, reason);
+    match &args.summary_file {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, format!("{}\n", summary)) {
+                eprintln!(
+                    "Warning: could not write the run summary to {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+        None => eprintln!("{}", summary),
+    }
+}
+
+// Set by SIGINT once install_interrupt_signal ran, so a search with a run summary can stop and still write it
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// The first SIGINT only sets INTERRUPTED and puts the default action back, so a second one ends the process at once.
+// Like the pause signals, the handler only touches an atomic and calls async-signal-safe functions.
+#[cfg(unix)]
+fn install_interrupt_signal() {
+    extern "C" fn on_interrupt(_: libc::c_int) {
+        INTERRUPTED.store(true, Ordering::Relaxed);
+        let message = b"Interrupted; stopping the search (press Ctrl-C again to quit at once)\n";
+        // SAFETY: signal(2) and write(2) are async-signal-safe and the message is a static buffer of the given length
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+            libc::write(libc::STDERR_FILENO, message.as_ptr().cast(), message.len());
+        }
+    }
+    let handler = on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    // SAFETY: the handler only touches an atomic and calls signal(2) and write(2)
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+    }
+}
+
+// Without Unix signals Ctrl-C keeps ending the process at once
+#[cfg(not(unix))]
+fn install_interrupt_signal() {}
+
+// A search aborted by a failing worker has no trustworthy result; reports why and exits
+fn exit_if_failed(control: &SearchControl) {
+    if let Some(reason) = control.failure() {
+        eprintln!("Search aborted: {}", reason);
+        std::process::exit(1);
+    }
+}
+
+// Stops the search once the deadline passes; returns whether it was the one that stopped it
+fn stop_at_timeout(control: &SearchControl, deadline: Instant) -> bool {
+    let reached = wait_until(control, deadline);
+    if reached {
+        control.stop();
+    }
+    reached
+}
+
+// Waits for the instant unless the search stops first; returns whether the instant was reached
+fn wait_until(control: &SearchControl, instant: Instant) -> bool {
+    while !control.is_stopped() {
+        let now = Instant::now();
+        if now >= instant {
+            return true;
+        }
+        std::thread::sleep((instant - now).min(Duration::from_millis(100)));
+    }
+    false
+}
+
+// The closest miss is offered when a tenth of the timeout is left, but at least this long before it runs out;
+// a timeout no longer than this offers nothing
+const PROMPT_LEAD_MIN: Duration = Duration::from_secs(10);
+
+// stop_at_timeout that first asks on the terminal whether to take the closest miss so far. The search goes on while
+// the question is open, and the answer has to come before the timeout. Returns whether the search timed out and
+// whether the closest miss was accepted; anything but y or yes keeps searching.
+fn offer_closest_before<F: Fn(&[u8]) -> usize>(
+    control: &SearchControl,
+    closest: &BestTracker<F>,
+    matcher: &PatternMatcher,
+    start: Instant,
+    deadline: Instant,
+) -> (bool, bool) {
+    let lead = ((deadline - start) / 10).max(PROMPT_LEAD_MIN);
+    if !wait_until(control, deadline - lead) {
+        return (false, false);
+    }
+    let Some(address) = closest.best_address() else {
+        return (stop_at_timeout(control, deadline), false);
+    };
+    let (pattern, right) = matcher
+        .closest_pattern(address.as_bytes())
+        .unwrap_or_default();
+    eprint!(
+        "{} seconds left. Accept the closest address so far, {} ({} of {} characters of '{}' right)? [y/N] ",
+        lead.as_secs(),
+        in_alphabet(&address),
+        right,
+        pattern.len(),
+        in_alphabet(pattern)
+    );
+    let _ = io::stderr().flush();
+
+    // reading stdin can't be given up on, so the reader is left behind if no answer comes
+    let (sender, answers) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut answer = String::new();
+        if io::stdin()
+            .read_line(&mut answer)
+            .is_ok_and(|read| read > 0)
+        {
+            let _ = sender.send(answer);
+        }
+    });
+    loop {
+        if control.is_stopped() {
+            eprintln!();
+            return (false, false);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            eprintln!();
+            control.stop();
+            return (true, false);
+        }
+        match answers.recv_timeout((deadline - now).min(Duration::from_millis(100))) {
+            Ok(answer) => {
+                let answer = answer.trim().to_ascii_lowercase();
+                if answer == "y" || answer == "yes" {
+                    control.stop();
+                    return (false, true);
+                }
+                return (stop_at_timeout(control, deadline), false);
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            // stdin was closed without an answer
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return (stop_at_timeout(control, deadline), false);
+            }
+        }
+    }
+}
+
+// Time between progress lines
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+// Whether any of the options that report on a running search is set
+fn reports_progress(args: &Args) -> bool {
+    args.progress || args.progress_json || args.metrics_file.is_some() || args.verbose > 1
+}
+
+// What report_progress writes, and what it needs to know about the search to do so
+struct ProgressReport {
+    // --progress lines
+    print: bool,
+    // --progress-json lines
+    json: bool,
+    metrics_file: Option<PathBuf>,
+    // -vv counts of each worker thread
+    per_thread: bool,
+    // expected attempts of a match, NaN when unknown
+    expected_attempts: f64,
+    // whether the search ends at its first match and counts every attempt toward it, so its attempts so far say how
+    // lucky it has been
+    single_match: bool,
+}
+
+fn progress_report(args: &Args, expected_attempts: f64, single_match: bool) -> ProgressReport {
+    ProgressReport {
+        print: args.progress,
+        json: args.progress_json,
+        metrics_file: args.metrics_file.clone(),
+        per_thread: args.verbose > 1,
+        expected_attempts,
+        single_match,
+    }
+}
+
+// Every PROGRESS_INTERVAL until the search stops: with print, writes the attempt total and the rate since the
+// previous line to stderr, for a single match search with how far into the expected attempts it is, warning once if
+// the rate falls off the way it does when the CPU throttles; with json, writes the same figures and an ETA as a JSON
+// line to stderr; with a metrics file, replaces it with the same figures as Prometheus metrics ('-' prints them to
+// stdout instead). The metrics are written once more when the search stops, so the file ends with the final total.
+// With per_thread, also writes each worker thread's count.
+fn report_progress(control: &SearchControl, start: Instant, report: &ProgressReport) {
+    let metrics_file = report.metrics_file.as_deref();
+    let mut monitor = RateMonitor::new(start);
+    let mut thread_attempts = HashMap::new();
+    let mut next = start + PROGRESS_INTERVAL;
+    let mut last_rate = 0.0;
+    while !control.is_stopped() {
+        let now = Instant::now();
+        if now < next {
+            // short naps so the reporter notices the end of the search quickly
+            std::thread::sleep((next - now).min(Duration::from_millis(100)));
+            continue;
+        }
+        next += PROGRESS_INTERVAL;
+        let attempts = control.attempts();
+        if control.is_paused() {
+            // nothing is searched while paused; the rate picks up again from the first interval after resuming
+            monitor.restart(attempts, now);
+            continue;
+        }
+        let sample = monitor.sample(attempts, now);
+        last_rate = sample.rate;
+        if let Some(path) = metrics_file {
+            export_metrics(path, attempts, sample.rate, now - start);
+        }
+        if report.per_thread {
+            print_worker_attempts(control, &mut thread_attempts);
+        }
+        if report.json {
+            eprintln!(
+                "{}",
+                progress_json(
+                    attempts,
+                    sample.rate,
+                    (now - start).as_secs_f64(),
+                    eta_secs(report.expected_attempts, sample.rate)
+                )
+            );
+        }
+        if !report.print {
+            continue;
+        }
+        let luck = report
+            .single_match
+            .then(|| luck_summary(attempts, report.expected_attempts, false))
+            .flatten()
+            .map(|luck| format!(", {}", luck))
+            .unwrap_or_default();
+        eprintln!(
+            "Progress: {} attempts, {:.0} addresses/second{}",
+            attempts, sample.rate, luck
+        );
+        if sample.throttled {
+            eprintln!(
+                "Warning: the search rate has stayed more than {:.0}% below the {:.0} addresses/second it started at; the machine may be thermal throttling",
+                THROTTLE_DROP * 100.0,
+                monitor.baseline().unwrap_or_default()
+            );
+        }
+    }
+    if let Some(path) = metrics_file {
+        export_metrics(path, control.attempts(), last_rate, start.elapsed());
+    }
+}
+
+// How the attempts spent compare with the expected attempts of a match, e.g. "150% of expected attempts; overdue, 22%
+// of searches run this long". found tells whether the search has ended with its match. None when the expected
+// attempts are unknown.
+fn luck_summary(attempts: u64, expected_attempts: f64, found: bool) -> Option<String> {
+    let percent = luck_ratio(attempts, expected_attempts)? * 100.0;
+    let done = chance_within(expected_attempts, attempts) * 100.0;
+    Some(match (found, percent < 100.0) {
+        (true, true) => format!(
+            "found after {:.0}% of expected attempts; lucky, {:.0}% of searches finish this early",
+            percent, done
+        ),
+        (true, false) => format!(
+            "found after {:.0}% of expected attempts; unlucky, {:.0}% of searches finish sooner",
+            percent, done
+        ),
+        (false, true) => format!("{:.0}% of expected attempts", percent),
+        (false, false) => format!(
+            "{:.0}% of expected attempts; overdue, {:.0}% of searches run this long",
+            percent,
+            100.0 - done
+        ),
+    })
+}
+
+// The attempts a match took against the expected attempts in standard deviations of the geometric distribution, e.g.
+// "2810341 attempts against 1009595 expected, 1.78 standard deviations above; outside typical variance". None when
+// the expected attempts are unknown.
+fn variance_summary(attempts: u64, expected_attempts: f64) -> Option<String> {
+    let deviations = attempt_deviations(attempts, expected_attempts)?;
+    Some(format!(
+        "{} attempts against {:.0} expected, {:.2} standard deviations {}; {} typical variance",
+        attempts,
+        expected_attempts,
+        deviations.abs(),
+        if deviations < 0.0 { "below" } else { "above" },
+        if deviations.abs() <= 1.0 {
+            "within"
+        } else {
+            "outside"
+        }
+    ))
+}
+
+// Writes one set of metrics for report_progress; a failed write is reported but doesn't stop the search
+fn export_metrics(path: &Path, attempts: u64, rate: f64, elapsed: Duration) {
+    let metrics = prometheus_metrics(attempts, rate, elapsed.as_secs_f64());
+    let written = if path == Path::new("-") {
+        let mut stdout = io::stdout().lock();
+        stdout
+            .write_all(metrics.as_bytes())
+            .and_then(|()| stdout.flush())
+    } else {
+        write_metrics_file(path, &metrics)
+    };
+    if let Err(e) = written {
+        eprintln!("Warning: cannot write metrics to {}: {}", path.display(), e);
+    }
+}
+
+// Keeps searching after the first match, printing (and appending, with --output) every match as it arrives until
+// --count matches were found. Workers hand matches over through a channel bounded by --buffer, so when printing or
+// writing falls behind they block instead of piling up matches in memory.
+fn stream_matches(args: &Args, matcher: &PatternMatcher, start: Instant) {
+    let format = args.format;
+    let (sender, receiver) = mpsc::sync_channel::<FoundKeypair>(args.buffer);
+    let control = configure(SearchControl::new(), args);
+    let mut found_count = 0u64;
+    // --count promises distinct addresses, so a keypair that shows up again is skipped rather than counted. Only kept
+    // with --count: it grows by 32 bytes per match, which an endless stream can't afford.
+    let mut distinct = args.count.map(|_| DistinctAddresses::default());
+    // set once stdout can't be written anymore, e.g. the consumer on the other end of a pipe exited
+    let mut output_closed = false;
+    let mut rotation = match &args.output {
+        Some(path) if args.rotate_size.is_some() || args.rotate_count.is_some() => {
+            match RotatingJsonl::open(path, args.rotate_size, args.rotate_count) {
+                Ok(rotation) => Some(rotation),
+                Err(e) => {
+                    eprintln!("Cannot read {}: {}", path.display(), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let attempts = std::thread::scope(|scope| {
+        let control = &control;
+        if reports_progress(args) {
+            let report = progress_report(args, estimate_attempts(matcher), false);
+            scope.spawn(move || report_progress(control, start, &report));
+        }
+        // the sender moves into the search thread so the channel closes once the search has stopped
+        let search = scope.spawn(move || {
+            search_each(
+                matcher,
+                args.threads,
+                |worker| worker_source(args, worker, args.threads),
+                control,
+                |keypair, source, attempts| {
+                    let found = FoundKeypair {
+                        pattern: matcher
+                            .matched_pattern(keypair.pubkey().to_string().as_bytes())
+                            .unwrap_or_default()
+                            .to_string(),
+                        mnemonic: source.last_mnemonic().cloned(),
+                        keypair,
+                        attempts,
+                        elapsed_secs: start.elapsed().as_secs_f64(),
+                        commit_nonce: None,
+                    };
+                    sender.send(found).is_err()
+                },
+            )
+        });
+
+        // matches still in flight after the count is reached are drained so blocked workers can finish
+        for mut found in receiver {
+            if output_closed || args.count.is_some_and(|count| found_count >= count) {
+                continue;
+            }
+            if let Some(distinct) = &mut distinct
+                && !distinct.insert(&found.keypair)
+            {
+                continue;
+            }
+            if args.commit {
+                found.commit_nonce = Some(commit_nonce());
+            }
+            match format {
+                OutputFormat::Human => print_found(&found, matcher, args),
+                OutputFormat::Json | OutputFormat::Template => {
+                    let line = match &args.template {
+                        Some(template) => render_template(template, &found),
+                        None => json_record(&found, !args.no_secret).to_string(),
+                    };
+                    if let Err(e) = write_line(line) {
+                        if e.kind() != io::ErrorKind::BrokenPipe {
+                            eprintln!("Failed to write output: {}", e);
+                        }
+                        output_closed = true;
+                        control.stop();
+                        continue;
+                    }
+                }
+                OutputFormat::Paper => print_paper_wallets(std::slice::from_ref(&found)),
+                OutputFormat::Csv | OutputFormat::QrPng | OutputFormat::Env | OutputFormat::Raw => {
+                    unreachable!("--stream rejects csv, qr-png, env and raw output")
+                }
+            }
+            let output = match (&args.output, &mut rotation) {
+                (Some(_), Some(rotation)) => Some(rotation.next_path(&found)),
+                (path, _) => path.clone(),
+            };
+            if let Some(path) = &output
+                && let Err(e) = save_results(
+                    path,
+                    true,
+                    args.verify_after_write,
+                    std::slice::from_ref(&found),
+                )
+            {
+                eprintln!("Failed to write {}: {}", path.display(), e);
+                control.stop();
+                std::process::exit(1);
+            }
+            if let Some(path) = &args.db
+                && let Err(e) = save_to_db(path, std::slice::from_ref(&found))
+            {
+                eprintln!("Failed to insert into {}: {}", path.display(), e);
+                control.stop();
+                std::process::exit(1);
+            }
+            found_count = found_count.saturating_add(1);
+            if args.count.is_some_and(|count| found_count >= count) {
+                control.stop();
+            }
+        }
+        search.join().unwrap()
+    });
+    exit_if_failed(&control);
+    print_worker_totals(&control);
+    if let Some(distinct) = &distinct {
+        // the match whose write found stdout closed was recorded but not counted
+        debug_assert_eq!(
+            distinct.len() as u64,
+            found_count + u64::from(output_closed),
+            "every reported match must be a different address"
+        );
+    }
+
+    status!(format, "Found: {}", found_count);
+    if let Some(distinct) = distinct.filter(|distinct| distinct.duplicates() > 0) {
+        eprintln!(
+            "Warning: {} repeated address(es) were found and skipped",
+            distinct.duplicates()
+        );
+    }
+    status!(
+        format,
+        "Took {:.2} minutes",
+        start.elapsed().as_secs_f64() / 60.0
+    );
+    status!(format, "Attempts: {}", attempts);
+}
+
+// Writes one line to stdout and flushes it right away, so a consumer reading a pipe sees each JSON line the moment
+// it's found rather than whenever a buffer fills. Errors are returned instead of panicking like println! does, so
+// a consumer that stopped reading ends the stream cleanly.
+fn write_line(line: impl std::fmt::Display) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{}", line)?;
+    stdout.flush()
+}
+
+// The address with the matched region highlighted: in color on a terminal, with brackets otherwise or when color
+// is turned off by --no-color or the NO_COLOR environment variable. With --group the address is shown grouped and
+// the region moved along with its characters.
+fn preview(pubkey: &str, start: usize, len: usize, args: &Args) -> String {
+    let (pubkey, start, len) = match args.group {
+        Some(group) => {
+            let (start, len) = grouped_span(start, len, group);
+            (group_address(pubkey, group), start, len)
+        }
+        None => (pubkey.to_string(), start, len),
+    };
+    let pubkey = pubkey.as_str();
+    if !args.no_color && std::env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal() {
+        format_highlighted_color(pubkey, start, len)
+    } else {
+        format_highlighted(pubkey, start, len)
+    }
+}
+
+// Shows the matched part of the address in its real casing, with a marker under each character telling whether it
+// matched exactly, only when ignoring case, as a flexible lookalike, or (within --max-distance) not at all. With
+// compare, the pattern and the match follow side by side with every substitution spelled out.
+fn print_match_detail(pubkey: &str, patterns: &str, matcher: &PatternMatcher, compare: bool) {
+    // a batch result may satisfy several patterns, joined with '|'
+    for pattern in patterns.split('|') {
+        let Some(start) = matcher.match_start(pubkey.as_bytes(), pattern) else {
+            continue;
+        };
+        let matched = &pubkey[start..start + pattern.len()];
+        let anagram = matcher
+            .patterns
+            .iter()
+            .zip(&matcher.match_types)
+            .any(|(p, &t)| p == pattern && t == MatchType::Anagram);
+        if anagram {
+            println!(
+                "Matched: {} at position {} (pattern: {}, in any order)",
+                matched, start, pattern
+            );
+            continue;
+        }
+        let markers: String = classify_match(
+            matched.as_bytes(),
+            pattern.as_bytes(),
+            matcher.case_sensitive,
+            matcher.flex_level,
+        )
+        .into_iter()
+        .map(CharMatch::marker)
+        .collect();
+        println!(
+            "Matched: {} at position {} (pattern: {})",
+            matched, start, pattern
+        );
+        println!(
+            "         {}  (= exact, ~ case differs, * lookalike{})",
+            markers,
+            if matcher.max_distance > 0 {
+                ", x wrong"
+            } else {
+                ""
+            }
+        );
+        if compare {
+            println!("Comparison:");
+            println!(
+                "{}",
+                match_comparison(
+                    matched.as_bytes(),
+                    pattern.as_bytes(),
+                    matcher.case_sensitive,
+                    matcher.flex_level,
+                )
+            );
+        }
     }
 }
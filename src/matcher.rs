@@ -0,0 +1,1565 @@
+use crate::search::Matcher;
+use clap::ValueEnum;
+use five8::BASE58_ENCODED_32_MAX_LEN;
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum MatchType {
+    Prefix,
+    Suffix,
+    Either,
+    Fingerprint,
+    Range,
+    // anywhere in the address, but only starting at a word boundary (see is_word_boundary)
+    Word,
+    // at the start of what follows the address's leading '1's, e.g. "abc" matches "abc..." and "11abc..."
+    PrefixAfterOnes,
+    // anywhere, as long as the whole match lies within the positions of the window (see with_window)
+    Window,
+    // anywhere, in any order: a stretch of the address as long as the pattern holding exactly its characters, e.g.
+    // "abc" matches "...cab..." (see anagram_start)
+    Anagram,
+}
+
+// Every character a base58 address can contain. There is no '0', 'O', 'I' or 'l', so some letters only exist in
+// one case: 'i' and 'o' have no upper case form, 'L' has no lower case form.
+pub const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// Number of leading characters many wallets and explorers show as a shortened address
+pub const DEFAULT_FINGERPRINT_LEN: usize = 8;
+
+// Window mode searches the whole address unless told otherwise; 44 characters is the longest address
+pub const DEFAULT_WINDOW_END: usize = 44;
+
+// How permissive flexible matching is: 0 accepts no lookalikes, 3 accepts the loosest ones (see lookalikes)
+pub const MAX_FLEX_LEVEL: u8 = 3;
+pub const DEFAULT_FLEX_LEVEL: u8 = 2;
+
+// Built-in matcher: accepts an address if any of the patterns matches its base58 form
+pub struct PatternMatcher {
+    pub patterns: Vec<String>,
+    // match type of patterns that don't have their own
+    pub match_type: MatchType,
+    // match type of each pattern, in the order of patterns
+    pub match_types: Vec<MatchType>,
+    pub case_sensitive: bool,
+    pub flex_level: u8,
+    pub fingerprint_len: usize,
+    // positions the pattern may start at in range mode
+    pub at_range: Range<usize>,
+    // positions a match has to lie within in window mode
+    pub window: Range<usize>,
+    pub excludes: Vec<String>,
+    pub min_distinct: usize,
+    // bounds on the Shannon entropy of the address, in bits per character (see shannon_entropy); both inclusive
+    pub min_entropy: Option<f64>,
+    pub max_entropy: Option<f64>,
+    // reject addresses whose first character is a digit rather than a letter
+    pub no_leading_digit: bool,
+    // with case folding on, still reject matches whose letters aren't in the case the pattern was typed in
+    pub canonical_case: bool,
+    // compare the address and patterns in their collapsed case form (see collapse_char) instead of folding case
+    // character by character
+    pub collapse_case: bool,
+    // the patterns collapsed, in the order of patterns; only filled in collapse_case mode
+    collapsed_patterns: Vec<Vec<u8>>,
+    // pattern characters a match may get wrong (see within_distance); 0 for exact matching
+    pub max_distance: usize,
+    // times a pattern must occur in the whole address, without overlaps (see count_occurrences); 0 or 1 for no minimum
+    pub min_occurrences: usize,
+    // required sum of the raw public key bytes, checked before the address is encoded
+    pub byte_sum: Option<ByteSum>,
+    leading_one_compatible: bool,
+    leading_one_skips: AtomicU64,
+}
+
+impl PatternMatcher {
+    // flexible_chars enables lookalikes at DEFAULT_FLEX_LEVEL; use with_flex_level to pick another level
+    pub fn new(
+        patterns: Vec<String>,
+        match_type: MatchType,
+        case_sensitive: bool,
+        flexible_chars: bool,
+    ) -> Self {
+        let flex_level = if flexible_chars {
+            DEFAULT_FLEX_LEVEL
+        } else {
+            0
+        };
+        let match_types = vec![match_type; patterns.len()];
+        PatternMatcher {
+            leading_one_compatible: leading_one_compatible(
+                &patterns,
+                &match_types,
+                case_sensitive,
+                flex_level,
+            ),
+            patterns,
+            match_type,
+            match_types,
+            case_sensitive,
+            flex_level,
+            fingerprint_len: DEFAULT_FINGERPRINT_LEN,
+            at_range: 0..1,
+            window: 0..DEFAULT_WINDOW_END,
+            excludes: Vec::new(),
+            min_distinct: 0,
+            min_entropy: None,
+            max_entropy: None,
+            no_leading_digit: false,
+            canonical_case: false,
+            collapse_case: false,
+            collapsed_patterns: Vec::new(),
+            max_distance: 0,
+            min_occurrences: 0,
+            byte_sum: None,
+            leading_one_skips: AtomicU64::new(0),
+        }
+    }
+
+    // Sets which lookalikes flexible matching accepts, 0 for none up to MAX_FLEX_LEVEL
+    pub fn with_flex_level(mut self, flex_level: u8) -> Self {
+        self.flex_level = flex_level.min(MAX_FLEX_LEVEL);
+        self.leading_one_compatible = leading_one_compatible(
+            &self.patterns,
+            &self.match_types,
+            self.case_sensitive,
+            self.flex_level,
+        );
+        self
+    }
+
+    // Gives each pattern its own match type, in the order of patterns, e.g. from "xyz:suffix" entries
+    pub fn with_match_types(mut self, match_types: Vec<MatchType>) -> Self {
+        assert_eq!(
+            match_types.len(),
+            self.patterns.len(),
+            "one match type per pattern"
+        );
+        self.match_types = match_types;
+        self.leading_one_compatible = leading_one_compatible(
+            &self.patterns,
+            &self.match_types,
+            self.case_sensitive,
+            self.flex_level,
+        );
+        self
+    }
+
+    // Sets how many leading characters fingerprint mode searches
+    pub fn with_fingerprint_len(mut self, fingerprint_len: usize) -> Self {
+        self.fingerprint_len = fingerprint_len;
+        self
+    }
+
+    // Sets the positions the pattern may start at in range mode
+    pub fn with_at_range(mut self, at_range: Range<usize>) -> Self {
+        self.at_range = at_range;
+        self
+    }
+
+    // Sets the positions a match has to lie within in window mode. An end past a shorter address is cut to its end.
+    pub fn with_window(mut self, window: Range<usize>) -> Self {
+        self.window = window;
+        self
+    }
+
+    // Substrings that disqualify an address wherever they appear, even if it matches a pattern
+    pub fn with_excludes(mut self, excludes: Vec<String>) -> Self {
+        self.excludes = excludes;
+        self
+    }
+
+    // Requires matching addresses to contain at least this many different characters
+    pub fn with_min_distinct(mut self, min_distinct: usize) -> Self {
+        self.min_distinct = min_distinct;
+        self
+    }
+
+    // Requires the Shannon entropy of matching addresses to lie within these bounds, in bits per character: a low
+    // maximum asks for repetitive addresses, a high minimum for busy ones
+    pub fn with_entropy_limits(
+        mut self,
+        min_entropy: Option<f64>,
+        max_entropy: Option<f64>,
+    ) -> Self {
+        self.min_entropy = min_entropy;
+        self.max_entropy = max_entropy;
+        self
+    }
+
+    // Whether the address's Shannon entropy lies within min_entropy and max_entropy
+    pub fn accepts_entropy(&self, pubkey: &[u8]) -> bool {
+        if self.min_entropy.is_none() && self.max_entropy.is_none() {
+            return true;
+        }
+        let entropy = shannon_entropy(pubkey);
+        self.min_entropy.is_none_or(|min| entropy >= min)
+            && self.max_entropy.is_none_or(|max| entropy <= max)
+    }
+
+    // Rejects addresses that start with a digit, whatever pattern they match
+    pub fn with_no_leading_digit(mut self, no_leading_digit: bool) -> Self {
+        self.no_leading_digit = no_leading_digit;
+        self
+    }
+
+    // Accepts a case-insensitive match only when every letter matched in the case it was typed in, e.g. "SoL" then
+    // accepts "SoL" and "5oL" (a lookalike, not a case change) but not "SOL". Has no effect on case sensitive searches.
+    pub fn with_canonical_case(mut self, canonical_case: bool) -> Self {
+        self.canonical_case = canonical_case;
+        self
+    }
+
+    // Matches in collapsed case form: the address is collapsed once per candidate and compared exactly against the
+    // collapsed patterns, e.g. "sol" then matches "SOL", "Sol" and "sOl". Accepts what a case-insensitive search
+    // without lookalikes accepts for any base58 pattern; it only differs for 'I' and 'O', which collapse to themselves
+    // and so match nothing. Has no effect on case sensitive searches.
+    pub fn with_collapse_case(mut self, collapse_case: bool) -> Self {
+        self.collapse_case = collapse_case && !self.case_sensitive;
+        self.collapsed_patterns = if self.collapse_case {
+            self.patterns
+                .iter()
+                .map(|p| p.bytes().map(collapse_char).collect())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self
+    }
+
+    // Accepts near misses: a match may differ from the pattern in up to this many characters, e.g. 1 lets "SoLx"
+    // match an address starting "SoLa". Characters accepted by case folding or a lookalike don't count as different.
+    pub fn with_max_distance(mut self, max_distance: usize) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    // Requires the matched pattern to occur at least this many times anywhere in the address, on top of the match its
+    // match type asks for, e.g. prefix "so" with 2 accepts "so4xso..." but not "so4x..." alone
+    pub fn with_min_occurrences(mut self, min_occurrences: usize) -> Self {
+        self.min_occurrences = min_occurrences;
+        self
+    }
+
+    // Requires the raw public key bytes to add up to a value (see ByteSum), on top of matching a pattern
+    pub fn with_byte_sum(mut self, byte_sum: Option<ByteSum>) -> Self {
+        self.byte_sum = byte_sum;
+        self
+    }
+
+    // Whether the raw public key passes the checks made on its bytes rather than its base58 form
+    pub fn accepts_bytes(&self, pubkey_bytes: &[u8]) -> bool {
+        self.byte_sum
+            .is_none_or(|byte_sum| byte_sum.accepts(pubkey_bytes))
+    }
+
+    // Whether the base58 address contains any excluded substring, ignoring case unless the search is case sensitive
+    pub fn is_excluded(&self, pubkey: &[u8]) -> bool {
+        self.excludes.iter().any(|exclude| {
+            pubkey.windows(exclude.len()).any(|window| {
+                if self.case_sensitive {
+                    window == exclude.as_bytes()
+                } else {
+                    window.eq_ignore_ascii_case(exclude.as_bytes())
+                }
+            })
+        })
+    }
+
+    // The part of the base58 address a pattern of this length is matched against, and where that part starts:
+    // the fingerprint in fingerprint mode, every position a match starting in at_range can cover in range mode, the
+    // window in window mode, otherwise the whole address
+    pub fn searched_part<'a>(
+        &self,
+        pubkey: &'a [u8],
+        pattern_len: usize,
+        match_type: MatchType,
+    ) -> (usize, &'a [u8]) {
+        match match_type {
+            MatchType::Fingerprint => (0, fingerprint(pubkey, self.fingerprint_len)),
+            MatchType::Range => {
+                let start = self.at_range.start.min(pubkey.len());
+                let end = (self.at_range.end - 1 + pattern_len).clamp(start, pubkey.len());
+                (start, &pubkey[start..end])
+            }
+            MatchType::Window => {
+                let start = self.window.start.min(pubkey.len());
+                let end = self.window.end.clamp(start, pubkey.len());
+                (start, &pubkey[start..end])
+            }
+            _ => (0, pubkey),
+        }
+    }
+
+    // A leading '1' encodes a leading zero byte, so in prefix mode such an address can never satisfy a pattern whose
+    // first character doesn't match '1'. When every pattern is matched as a prefix, those candidates are rejected up
+    // front and counted for the final stats. With a max_distance the first character may be one of the wrong ones, so
+    // nothing is rejected.
+    pub fn rejects_leading_one(&self, pubkey: &[u8]) -> bool {
+        if self.leading_one_compatible || self.max_distance > 0 || pubkey.first() != Some(&b'1') {
+            return false;
+        }
+        self.leading_one_skips.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    // Number of candidates rejected by rejects_leading_one
+    pub fn leading_one_skips(&self) -> u64 {
+        self.leading_one_skips.load(Ordering::Relaxed)
+    }
+
+    // Patterns matched as a prefix that no address can start with (see prefix_possible); a search for one of them
+    // alone would never end. Only exact matching is checked; a near miss is left to the search.
+    pub fn impossible_prefixes(&self) -> Vec<&str> {
+        self.patterns
+            .iter()
+            .zip(&self.match_types)
+            .filter(|&(pattern, &match_type)| {
+                match_type == MatchType::Prefix
+                    && self.max_distance == 0
+                    && !prefix_possible(pattern, self.case_sensitive, self.flex_level)
+            })
+            .map(|(pattern, _)| pattern.as_str())
+            .collect()
+    }
+
+    // Returns the first pattern, in the order given, that the base58 address satisfies
+    pub fn matched_pattern(&self, pubkey: &[u8]) -> Option<&str> {
+        let mut buf = [0u8; BASE58_ENCODED_32_MAX_LEN];
+        let compared = self.compared_form(pubkey, &mut buf);
+        (0..self.patterns.len())
+            .find(|&i| self.matches_compared(pubkey, &compared, i))
+            .map(|i| self.patterns[i].as_str())
+    }
+
+    // The address in the form patterns are compared against: collapsed in collapse_case mode, otherwise as it is
+    fn compared_form<'a>(
+        &self,
+        pubkey: &'a [u8],
+        buf: &'a mut [u8; BASE58_ENCODED_32_MAX_LEN],
+    ) -> Cow<'a, [u8]> {
+        if !self.collapse_case {
+            return Cow::Borrowed(pubkey);
+        }
+        match buf.get_mut(..pubkey.len()) {
+            Some(collapsed) => {
+                for (out, &c) in collapsed.iter_mut().zip(pubkey) {
+                    *out = collapse_char(c);
+                }
+                Cow::Borrowed(collapsed)
+            }
+            None => Cow::Owned(pubkey.iter().map(|&c| collapse_char(c)).collect()),
+        }
+    }
+
+    // Where in the base58 address the pattern matched, if it did. A pattern given with several match types is
+    // tried with each of them.
+    pub fn match_start(&self, pubkey: &[u8], pattern: &str) -> Option<usize> {
+        let mut match_types: Vec<MatchType> = self
+            .patterns
+            .iter()
+            .zip(&self.match_types)
+            .filter(|(p, _)| *p == pattern)
+            .map(|(_, &t)| t)
+            .collect();
+        if match_types.is_empty() {
+            match_types.push(self.match_type);
+        }
+        match_types.into_iter().find_map(|match_type| {
+            let (offset, searched) = self.searched_part(pubkey, pattern.len(), match_type);
+            find_match_within(
+                searched,
+                pattern.as_bytes(),
+                match_type,
+                self.case_sensitive,
+                self.flex_level,
+                self.max_distance,
+            )
+            .map(|start| offset + start)
+        })
+    }
+
+    // The pattern the base58 address comes closest to and how many of its characters are right, counting only where
+    // the pattern's match type looks (see right_chars); ties go to the pattern given first. Excludes and the other
+    // extra conditions are not considered.
+    pub fn closest_pattern(&self, pubkey: &[u8]) -> Option<(&str, usize)> {
+        self.best_partial(pubkey, right_chars)
+    }
+
+    // The pattern the base58 address has the most leading characters of, in a row, and how many (see matched_len);
+    // ties go to the pattern given first
+    pub fn longest_partial(&self, pubkey: &[u8]) -> Option<(&str, usize)> {
+        self.best_partial(pubkey, matched_len)
+    }
+
+    // The pattern scoring highest under a partial match score, where its match type looks
+    fn best_partial(
+        &self,
+        pubkey: &[u8],
+        score: fn(&[u8], &[u8], MatchType, bool, u8) -> usize,
+    ) -> Option<(&str, usize)> {
+        (0..self.patterns.len())
+            .map(|i| {
+                let pattern = self.patterns[i].as_bytes();
+                let match_type = self.match_types[i];
+                let right = score(
+                    self.searched_part(pubkey, pattern.len(), match_type).1,
+                    pattern,
+                    match_type,
+                    self.case_sensitive,
+                    self.flex_level,
+                );
+                (self.patterns[i].as_str(), right)
+            })
+            .rev()
+            .max_by_key(|&(_, right)| right)
+    }
+
+    // Checks the base58 address against a single pattern; excludes, the distinct character minimum and the
+    // occurrence minimum are only checked once the pattern matched
+    pub fn matches_index(&self, pubkey: &[u8], index: usize) -> bool {
+        let mut buf = [0u8; BASE58_ENCODED_32_MAX_LEN];
+        let compared = self.compared_form(pubkey, &mut buf);
+        self.matches_compared(pubkey, &compared, index)
+    }
+
+    // matches_index, with the address already in the form compared_form gives. In collapse_case mode both sides
+    // are collapsed, so they are compared exactly.
+    fn matches_compared(&self, pubkey: &[u8], compared: &[u8], index: usize) -> bool {
+        if self.no_leading_digit && starts_with_digit(pubkey) {
+            return false;
+        }
+        let (pattern, case_sensitive, flex_level) = if self.collapse_case {
+            (self.collapsed_patterns[index].as_slice(), true, 0)
+        } else {
+            (
+                self.patterns[index].as_bytes(),
+                self.case_sensitive,
+                self.flex_level,
+            )
+        };
+        let match_type = self.match_types[index];
+        matches_pattern_within(
+            self.searched_part(compared, pattern.len(), match_type).1,
+            pattern,
+            match_type,
+            case_sensitive,
+            flex_level,
+            self.max_distance,
+        ) && !self.is_excluded(pubkey)
+            && distinct_chars(pubkey) >= self.min_distinct
+            && self.accepts_entropy(pubkey)
+            && (self.min_occurrences <= 1
+                || count_occurrences(compared, pattern, case_sensitive, flex_level)
+                    >= self.min_occurrences)
+            && (!self.canonical_case || self.keeps_typed_case(pubkey, &self.patterns[index]))
+    }
+
+    // Whether the pattern's match, at the position match_start reports, has no letter in another case than the
+    // pattern's. In fingerprint, range and word mode only that first match position is checked; an anagram keeps the
+    // typed case when some stretch holds its characters in exactly their case.
+    fn keeps_typed_case(&self, pubkey: &[u8], pattern: &str) -> bool {
+        let anagram = self
+            .patterns
+            .iter()
+            .zip(&self.match_types)
+            .any(|(p, &t)| p == pattern && t == MatchType::Anagram);
+        if anagram {
+            return anagram_start(pubkey, pattern.as_bytes(), true, self.max_distance).is_some();
+        }
+        self.match_start(pubkey, pattern).is_some_and(|start| {
+            !classify_match(
+                &pubkey[start..],
+                pattern.as_bytes(),
+                self.case_sensitive,
+                self.flex_level,
+            )
+            .contains(&CharMatch::CaseFolded)
+        })
+    }
+}
+
+// Copies the settings; the copy counts its own leading '1' skips from zero
+impl Clone for PatternMatcher {
+    fn clone(&self) -> Self {
+        PatternMatcher {
+            patterns: self.patterns.clone(),
+            match_type: self.match_type,
+            match_types: self.match_types.clone(),
+            case_sensitive: self.case_sensitive,
+            flex_level: self.flex_level,
+            fingerprint_len: self.fingerprint_len,
+            at_range: self.at_range.clone(),
+            window: self.window.clone(),
+            excludes: self.excludes.clone(),
+            min_distinct: self.min_distinct,
+            min_entropy: self.min_entropy,
+            max_entropy: self.max_entropy,
+            no_leading_digit: self.no_leading_digit,
+            canonical_case: self.canonical_case,
+            collapse_case: self.collapse_case,
+            collapsed_patterns: self.collapsed_patterns.clone(),
+            max_distance: self.max_distance,
+            min_occurrences: self.min_occurrences,
+            byte_sum: self.byte_sum,
+            leading_one_compatible: self.leading_one_compatible,
+            leading_one_skips: AtomicU64::new(0),
+        }
+    }
+}
+
+// Whether a leading '1' can't be ruled out: some pattern is matched somewhere other than the start, or some
+// pattern's first character accepts a '1'
+fn leading_one_compatible(
+    patterns: &[String],
+    match_types: &[MatchType],
+    case_sensitive: bool,
+    flex_level: u8,
+) -> bool {
+    match_types.iter().any(|&t| t != MatchType::Prefix)
+        || patterns.iter().any(|pattern| {
+            matches_pattern(
+                b"1",
+                &pattern.as_bytes()[..1],
+                MatchType::Prefix,
+                case_sensitive,
+                flex_level,
+            )
+        })
+}
+
+// A base58 character in collapsed case form: letters in upper case, except 'i' and 'o', which have no upper case
+// form in base58 and are left as they are. Digits and anything else stay as they are too.
+pub fn collapse_char(c: u8) -> u8 {
+    match c {
+        b'i' | b'o' => c,
+        _ => c.to_ascii_uppercase(),
+    }
+}
+
+// Splits a pattern's own match type off a pattern entry, e.g. "xyz:suffix" into ("xyz", Some(Suffix)). ':' is not a
+// base58 character; an entry whose text after the last ':' is no match type name is all pattern.
+pub fn split_match_type(entry: &str) -> (&str, Option<MatchType>) {
+    entry
+        .rsplit_once(':')
+        .and_then(|(pattern, name)| {
+            <MatchType as ValueEnum>::from_str(name, true)
+                .ok()
+                .map(|match_type| (pattern, Some(match_type)))
+        })
+        .unwrap_or((entry, None))
+}
+
+// Pattern entries (see split_match_type) for an address that looks like the reference address: its first len
+// characters for a prefix, its last len for a suffix, both for either, and the len after its leading '1's for
+// prefix-after-ones. Fails unless the reference is a 32 byte public key in base58 and the match type is one of those.
+pub fn like_patterns(
+    reference: &str,
+    len: usize,
+    match_type: MatchType,
+) -> Result<Vec<String>, String> {
+    let mut bytes = [0u8; 32];
+    if five8::decode_32(reference, &mut bytes).is_err() {
+        return Err(format!("'{}' is not a base58 public key", reference));
+    }
+    let start = leading_ones(reference.as_bytes());
+    if len == 0 || start + len > reference.len() {
+        return Err(format!(
+            "Can't take {} characters from the {} character address {}",
+            len,
+            reference.len(),
+            reference
+        ));
+    }
+    let entry = |pattern: &str, match_type: MatchType| {
+        format!(
+            "{}:{}",
+            pattern,
+            match_type.to_possible_value().unwrap().get_name()
+        )
+    };
+    let prefix = entry(&reference[..len], MatchType::Prefix);
+    let suffix = entry(&reference[reference.len() - len..], MatchType::Suffix);
+    match match_type {
+        MatchType::Prefix => Ok(vec![prefix]),
+        MatchType::Suffix => Ok(vec![suffix]),
+        MatchType::Either => Ok(vec![prefix, suffix]),
+        MatchType::PrefixAfterOnes => Ok(vec![entry(
+            &reference[start..start + len],
+            MatchType::PrefixAfterOnes,
+        )]),
+        _ => Err(format!(
+            "--like takes the start or end of the address; it can't be combined with {} matching",
+            match_type.to_possible_value().unwrap().get_name()
+        )),
+    }
+}
+
+impl Matcher for PatternMatcher {
+    fn is_match(&self, pubkey_bytes: &[u8]) -> bool {
+        if !self.accepts_bytes(pubkey_bytes) {
+            return false;
+        }
+        let Ok(pubkey_bytes) = pubkey_bytes.try_into() else {
+            return false;
+        };
+        let mut buf = [0u8; BASE58_ENCODED_32_MAX_LEN];
+        let encoded = encode_base58(pubkey_bytes, &mut buf);
+        !self.rejects_leading_one(encoded) && self.matched_pattern(encoded).is_some()
+    }
+}
+
+// Largest sum the 32 bytes of a public key can have
+pub const MAX_BYTE_SUM: u32 = 32 * 255;
+
+// A value the 32 raw public key bytes have to add up to, e.g. for collectors after a "lucky" key. With a modulus
+// only the remainder of the sum counts, so 7 mod 256 accepts sums of 7, 263, 519 and so on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ByteSum {
+    pub target: u32,
+    pub modulus: Option<u32>,
+}
+
+impl ByteSum {
+    pub fn accepts(&self, pubkey_bytes: &[u8]) -> bool {
+        let sum = byte_sum(pubkey_bytes);
+        match self.modulus {
+            Some(modulus) => sum % modulus == self.target,
+            None => sum == self.target,
+        }
+    }
+}
+
+// Sum of the bytes as unsigned numbers
+pub fn byte_sum(bytes: &[u8]) -> u32 {
+    bytes.iter().map(|&b| b as u32).sum()
+}
+
+// Matches the hex form of the raw public key bytes against a prefix, e.g. "deadbeef". The hex is never built:
+// whole bytes are compared directly and an odd final digit is checked against the high nibble of the next byte.
+pub struct HexPrefixMatcher {
+    pub prefix: String,
+    bytes: Vec<u8>,
+    high_nibble: Option<u8>,
+}
+
+impl HexPrefixMatcher {
+    // prefix must be valid hex of at most 64 digits
+    pub fn new(prefix: &str) -> Self {
+        let prefix = prefix.to_ascii_lowercase();
+        let digits: Vec<u8> = prefix
+            .chars()
+            .map(|c| c.to_digit(16).expect("hex prefix must be validated") as u8)
+            .collect();
+        let bytes = digits
+            .chunks_exact(2)
+            .map(|pair| (pair[0] << 4) | pair[1])
+            .collect();
+        let high_nibble = (digits.len() % 2 == 1).then(|| digits[digits.len() - 1]);
+        HexPrefixMatcher {
+            prefix,
+            bytes,
+            high_nibble,
+        }
+    }
+
+    // Expected candidates per match: every hex digit is one of 16 equally likely values
+    pub fn expected_attempts(&self) -> f64 {
+        16f64.powi(self.prefix.len() as i32)
+    }
+}
+
+impl Matcher for HexPrefixMatcher {
+    fn is_match(&self, pubkey_bytes: &[u8]) -> bool {
+        let len = self.bytes.len();
+        pubkey_bytes.len() >= 32
+            && pubkey_bytes[..len] == self.bytes[..]
+            && self
+                .high_nibble
+                .is_none_or(|nibble| pubkey_bytes[len] >> 4 == nibble)
+    }
+}
+
+// Lower case hex form of a public key
+pub fn to_hex(pubkey_bytes: &[u8]) -> String {
+    pubkey_bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// 10^19, the largest power of ten below 2^64: to_decimal peels off 19 digits per pass
+const DECIMAL_CHUNK: u128 = 10_000_000_000_000_000_000;
+
+// Decimal form of the bytes read as one big-endian unsigned integer, e.g. a public key as a 256-bit number
+pub fn to_decimal(bytes: &[u8]) -> String {
+    let mut number = bytes.to_vec();
+    // groups of 19 digits, least significant first
+    let mut chunks = Vec::new();
+    while number.iter().any(|&b| b != 0) {
+        // long division of the whole number by 10^19; the running remainder stays below 10^19 * 256
+        let mut remainder = 0u128;
+        for byte in number.iter_mut() {
+            let value = (remainder << 8) | *byte as u128;
+            *byte = (value / DECIMAL_CHUNK) as u8;
+            remainder = value % DECIMAL_CHUNK;
+        }
+        chunks.push(remainder as u64);
+    }
+    match chunks.split_last() {
+        None => "0".to_string(),
+        Some((leading, rest)) => {
+            let mut decimal = leading.to_string();
+            for chunk in rest.iter().rev() {
+                decimal.push_str(&format!("{:019}", chunk));
+            }
+            decimal
+        }
+    }
+}
+
+// Base58 digits (indices into BASE58_ALPHABET) of the bytes read as one big-endian unsigned integer, most
+// significant first and without the '1' digits leading zero bytes become
+fn base58_digits(bytes: &[u8]) -> Vec<u8> {
+    let mut number = bytes.to_vec();
+    let mut digits = Vec::new();
+    while number.iter().any(|&b| b != 0) {
+        let mut remainder = 0u32;
+        for byte in number.iter_mut() {
+            let value = (remainder << 8) | *byte as u32;
+            *byte = (value / 58) as u8;
+            remainder = value % 58;
+        }
+        digits.push(remainder as u8);
+    }
+    digits.reverse();
+    digits
+}
+
+// Whether any address starts with the pattern, counting every character accepted_chars lets each pattern character
+// stand for.
+//
+// The largest key, 2^256 - 1, encodes as the 44 characters "JEKNV...", and 58^43 < 2^256, so an address has 43
+// characters for keys below 58^43 and 44 above it. A 44 character address can only start with '2' to 'J', but the
+// 43 character ones start with every character, so no first character rules a pattern out on its own. What does
+// is length: an address with z leading '1's (z leading zero bytes) continues with the digits of a number v whose
+// 32 - z bytes have a nonzero top byte, 2^(8(31 - z)) <= v < 2^(8(32 - z)). A pattern is possible if, for some z,
+// its first z characters accept '1' and the rest can lead the digits of such a v, which fails once it is as long as
+// those digits and runs past the largest of them, e.g. 31 '1's followed by "zz" (v = 3363 > 255).
+pub fn prefix_possible(pattern: &str, case_sensitive: bool, flex_level: u8) -> bool {
+    let accepted: Vec<Vec<u8>> = pattern
+        .bytes()
+        .map(|target| {
+            accepted_chars(target, case_sensitive, flex_level)
+                .into_iter()
+                .filter_map(|c| BASE58_ALPHABET.iter().position(|&a| a == c))
+                .map(|digit| digit as u8)
+                .collect()
+        })
+        .collect();
+    (0..=32).any(|zeros| {
+        let ones = zeros.min(accepted.len());
+        if !accepted[..ones].iter().all(|digits| digits.contains(&0)) {
+            return false;
+        }
+        let rest = &accepted[ones..];
+        if zeros == 32 {
+            // the all-zero key is 32 '1's and nothing else
+            return rest.is_empty();
+        }
+        let mut smallest = vec![0u8; 32 - zeros];
+        smallest[0] = 1;
+        let low = base58_digits(&smallest);
+        let high = base58_digits(&vec![0xff; 32 - zeros]);
+        (low.len()..=high.len()).any(|len| {
+            len >= rest.len()
+                && leads_number_in(
+                    rest,
+                    (len == low.len()).then_some(low.as_slice()),
+                    (len == high.len()).then_some(high.as_slice()),
+                    true,
+                )
+        })
+    })
+}
+
+// Whether some number of the bounds' length, with its leading digits drawn from the sets, lies within
+// [low, high]. A missing bound doesn't constrain that side. The first digit of the number can't be 0, which would
+// be one more leading '1'.
+fn leads_number_in(sets: &[Vec<u8>], low: Option<&[u8]>, high: Option<&[u8]>, first: bool) -> bool {
+    let Some((digits, rest)) = sets.split_first() else {
+        // free digits can always go up to a lower bound or down to an upper one; both at once need room
+        return match (low, high) {
+            (Some(low), Some(high)) => low <= high,
+            _ => true,
+        };
+    };
+    digits.iter().any(|&digit| {
+        if first && digit == 0 {
+            return false;
+        }
+        let low_digit = low.map(|low| low[0]);
+        let high_digit = high.map(|high| high[0]);
+        if low_digit.is_some_and(|d| digit < d) || high_digit.is_some_and(|d| digit > d) {
+            return false;
+        }
+        // a bound only keeps constraining while the number has followed it digit for digit
+        let low = low.filter(|low| low[0] == digit).map(|low| &low[1..]);
+        let high = high.filter(|high| high[0] == digit).map(|high| &high[1..]);
+        leads_number_in(rest, low, high, false)
+    })
+}
+
+// Matches public keys whose value as a 256-bit big-endian integer starts with the given decimal digits
+pub struct NumericPrefixMatcher {
+    pub prefix: String,
+}
+
+impl NumericPrefixMatcher {
+    // prefix must be decimal digits without a leading zero
+    pub fn new(prefix: &str) -> Self {
+        NumericPrefixMatcher {
+            prefix: prefix.to_string(),
+        }
+    }
+
+    // Expected candidates per match. Leading digits of a uniform 256-bit number are not uniform: numbers starting
+    // with the prefix d form the ranges [d * 10^k, (d + 1) * 10^k) for every k, and the last range is cut off at 2^256,
+    // so small leading digits are more common (e.g. a leading 1 in about 23% of keys, a leading 7 in about 10%).
+    // Infinite when no 256-bit number starts with the prefix.
+    pub fn expected_attempts(&self) -> f64 {
+        let keys = 2f64.powi(256);
+        let digits: f64 = self.prefix.parse().unwrap_or(f64::INFINITY);
+        let mut hits = 0.0;
+        let mut scale = 1.0;
+        while digits * scale < keys {
+            let (low, high) = (digits * scale, (digits + 1.0) * scale);
+            hits += if high <= keys { scale } else { keys - low };
+            scale *= 10.0;
+        }
+        keys / hits
+    }
+}
+
+impl Matcher for NumericPrefixMatcher {
+    fn is_match(&self, pubkey_bytes: &[u8]) -> bool {
+        pubkey_bytes.len() >= 32 && to_decimal(&pubkey_bytes[..32]).starts_with(&self.prefix)
+    }
+}
+
+// Number of different characters in a base58 address. Every base58 character lies between '1' and 'z', so one bit
+// per character of that range fits in a u128.
+pub fn distinct_chars(address: &[u8]) -> usize {
+    address
+        .iter()
+        .fold(0u128, |seen, &c| seen | 1 << (c.wrapping_sub(b'1') & 127))
+        .count_ones() as usize
+}
+
+// Whether the address's first character is a digit. About half of all addresses start with one, since the first
+// character of a 44 character address can only be one of the 16 smallest base58 digits, '2' to 'H'.
+pub fn starts_with_digit(address: &[u8]) -> bool {
+    address.first().is_some_and(u8::is_ascii_digit)
+}
+
+// Shannon entropy of the address's characters in bits per character: -sum(p * log2(p)) over the share p of the address
+// each different character makes up. 0 for a single repeated character, log2(n) when all n characters differ, so a
+// 44 character address has at most log2(44), about 5.46; a random one has about 4.8. Case counts: 'a' and 'A' are
+// different characters.
+pub fn shannon_entropy(address: &[u8]) -> f64 {
+    if address.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0u32; 128];
+    for &c in address {
+        counts[(c & 127) as usize] += 1;
+    }
+    // log2(n) - sum(c * log2(c)) / n, which keeps exact results exact, e.g. 1 for "abab"
+    let len = address.len() as f64;
+    let weighted: f64 = counts
+        .iter()
+        .filter(|&&count| count > 1)
+        .map(|&count| count as f64 * (count as f64).log2())
+        .sum();
+    len.log2() - weighted / len
+}
+
+// Number of times the pattern occurs in the address with no two occurrences sharing a character, counted left to
+// right, e.g. 2 for "aa" in "aaaaa" and 1 for "sos" in "sosos". Characters compare the way matching does.
+pub fn count_occurrences(
+    address: &[u8],
+    pattern: &[u8],
+    case_sensitive: bool,
+    flex_level: u8,
+) -> usize {
+    if pattern.is_empty() {
+        return 0;
+    }
+    let flex_level = if case_sensitive { 0 } else { flex_level };
+    let mut count = 0;
+    let mut start = 0;
+    while start + pattern.len() <= address.len() {
+        if within_distance(
+            &address[start..start + pattern.len()],
+            pattern,
+            case_sensitive,
+            flex_level,
+            0,
+        ) {
+            count += 1;
+            start += pattern.len();
+        } else {
+            start += 1;
+        }
+    }
+    count
+}
+
+// Encodes a pubkey into the stack buffer, avoiding the String allocation of to_string() in the hot loop
+pub fn encode_base58<'a>(
+    pubkey: &[u8; 32],
+    buf: &'a mut [u8; BASE58_ENCODED_32_MAX_LEN],
+) -> &'a [u8] {
+    let len = five8::encode_32(pubkey, buf) as usize;
+    &buf[..len]
+}
+
+// First len characters of a base58 address, or all of it if it is shorter
+pub fn fingerprint(pubkey: &[u8], len: usize) -> &[u8] {
+    &pubkey[..len.min(pubkey.len())]
+}
+
+// Pattern finder; in fingerprint and range mode the pattern may start anywhere within the pubkey slice it is given,
+// in word mode anywhere a word starts
+pub fn matches_pattern(
+    pubkey: &[u8],
+    pattern: &[u8],
+    match_type: MatchType,
+    case_sensitive: bool,
+    flex_level: u8,
+) -> bool {
+    matches_pattern_within(pubkey, pattern, match_type, case_sensitive, flex_level, 0)
+}
+
+// matches_pattern, accepting a place where up to max_distance pattern characters are wrong
+pub fn matches_pattern_within(
+    pubkey: &[u8],
+    pattern: &[u8],
+    match_type: MatchType,
+    case_sensitive: bool,
+    flex_level: u8,
+    max_distance: usize,
+) -> bool {
+    let pubkey_len = pubkey.len();
+    let pattern_len = pattern.len();
+    // only an unvalidated pattern (--no-validate) can be longer than the address
+    if pattern_len > pubkey_len {
+        return false;
+    }
+    let flex_level = if case_sensitive { 0 } else { flex_level };
+    let matches_at =
+        |window: &[u8]| within_distance(window, pattern, case_sensitive, flex_level, max_distance);
+
+    match match_type {
+        MatchType::Prefix => matches_at(&pubkey[..pattern_len]),
+        MatchType::Suffix => matches_at(&pubkey[pubkey_len - pattern_len..]),
+        MatchType::PrefixAfterOnes => {
+            let rest = &pubkey[leading_ones(pubkey)..];
+            rest.len() >= pattern_len && matches_at(&rest[..pattern_len])
+        }
+        // check prefix first, the suffix only if the prefix doesn't match
+        MatchType::Either => {
+            matches_at(&pubkey[..pattern_len]) || matches_at(&pubkey[pubkey_len - pattern_len..])
+        }
+        MatchType::Fingerprint | MatchType::Range | MatchType::Window => {
+            pubkey.windows(pattern_len).any(matches_at)
+        }
+        MatchType::Word => pubkey
+            .windows(pattern_len)
+            .enumerate()
+            .any(|(start, window)| is_word_boundary(pubkey, start) && matches_at(window)),
+        MatchType::Anagram => {
+            anagram_start(pubkey, pattern, case_sensitive, max_distance).is_some()
+        }
+    }
+}
+
+// The most pattern characters right at any one place matches_pattern would check, e.g. 3 for prefix "Sola" on
+// "Solx...": the pattern length less the fewest wrong characters. 0 when the address is too short for the pattern.
+pub fn right_chars(
+    pubkey: &[u8],
+    pattern: &[u8],
+    match_type: MatchType,
+    case_sensitive: bool,
+    flex_level: u8,
+) -> usize {
+    partial_match(
+        pubkey,
+        pattern,
+        match_type,
+        case_sensitive,
+        flex_level,
+        false,
+    )
+}
+
+// The most leading pattern characters right in a row at any one place matches_pattern would check, e.g. 2 for prefix
+// "Sola" on "Soxa...", where right_chars counts 3. An anagram has no order, so it counts like right_chars.
+pub fn matched_len(
+    pubkey: &[u8],
+    pattern: &[u8],
+    match_type: MatchType,
+    case_sensitive: bool,
+    flex_level: u8,
+) -> usize {
+    partial_match(
+        pubkey,
+        pattern,
+        match_type,
+        case_sensitive,
+        flex_level,
+        true,
+    )
+}
+
+// right_chars, or with leading_only matched_len
+fn partial_match(
+    pubkey: &[u8],
+    pattern: &[u8],
+    match_type: MatchType,
+    case_sensitive: bool,
+    flex_level: u8,
+    leading_only: bool,
+) -> usize {
+    let pattern_len = pattern.len();
+    if pubkey.len() < pattern_len {
+        return 0;
+    }
+    let flex_level = if case_sensitive { 0 } else { flex_level };
+    let right_at = |window: &[u8]| {
+        let right = window
+            .iter()
+            .zip(pattern)
+            .map(|(&c, &target)| matches_char(c, target, case_sensitive, flex_level));
+        if leading_only {
+            right.take_while(|&ok| ok).count()
+        } else {
+            right.filter(|&ok| ok).count()
+        }
+    };
+    let prefix = || right_at(&pubkey[..pattern_len]);
+    let suffix = || right_at(&pubkey[pubkey.len() - pattern_len..]);
+
+    match match_type {
+        MatchType::Prefix => prefix(),
+        MatchType::Suffix => suffix(),
+        MatchType::PrefixAfterOnes => right_at(&pubkey[leading_ones(pubkey)..]),
+        MatchType::Either => prefix().max(suffix()),
+        MatchType::Fingerprint | MatchType::Range | MatchType::Window => {
+            pubkey.windows(pattern_len).map(right_at).max().unwrap_or(0)
+        }
+        MatchType::Word => pubkey
+            .windows(pattern_len)
+            .enumerate()
+            .filter(|&(start, _)| is_word_boundary(pubkey, start))
+            .map(|(_, window)| right_at(window))
+            .max()
+            .unwrap_or(0),
+        MatchType::Anagram => {
+            let mut fewest_missing = pattern_len;
+            scan_anagram(pubkey, pattern, case_sensitive, |missing| {
+                fewest_missing = fewest_missing.min(missing);
+                fewest_missing == 0
+            });
+            pattern_len - fewest_missing
+        }
+    }
+}
+
+// Whether the window differs from the pattern in at most max_distance characters: the Hamming distance, with a
+// character matches_char accepts counting as equal. Gives up at the first mismatch past the allowance, so exact
+// matching (0) stops at the first wrong character like a plain comparison.
+#[inline]
+pub fn within_distance(
+    window: &[u8],
+    pattern: &[u8],
+    case_sensitive: bool,
+    flex_level: u8,
+    max_distance: usize,
+) -> bool {
+    let mut mismatches = 0;
+    for (&c, &target) in window.iter().zip(pattern) {
+        if !matches_char(c, target, case_sensitive, flex_level) {
+            mismatches += 1;
+            if mismatches > max_distance {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+// Finds where the pattern matched, checking the prefix before the suffix like matches_pattern does.
+// In fingerprint, range and word mode this is the first position within the given slice.
+pub fn find_match(
+    pubkey: &[u8],
+    pattern: &[u8],
+    match_type: MatchType,
+    case_sensitive: bool,
+    flex_level: u8,
+) -> Option<usize> {
+    find_match_within(pubkey, pattern, match_type, case_sensitive, flex_level, 0)
+}
+
+// find_match, accepting a place where up to max_distance pattern characters are wrong
+pub fn find_match_within(
+    pubkey: &[u8],
+    pattern: &[u8],
+    match_type: MatchType,
+    case_sensitive: bool,
+    flex_level: u8,
+    max_distance: usize,
+) -> Option<usize> {
+    let matches_at = |window: &[u8]| {
+        matches_pattern_within(
+            window,
+            pattern,
+            MatchType::Prefix,
+            case_sensitive,
+            flex_level,
+            max_distance,
+        )
+    };
+    let at_prefix = || matches_at(pubkey).then_some(0);
+    let at_suffix = || {
+        let start = pubkey.len() - pattern.len();
+        matches_at(&pubkey[start..]).then_some(start)
+    };
+
+    match match_type {
+        MatchType::Prefix => at_prefix(),
+        MatchType::Suffix => at_suffix(),
+        MatchType::PrefixAfterOnes => {
+            let start = leading_ones(pubkey);
+            (pubkey.len() - start >= pattern.len() && matches_at(&pubkey[start..])).then_some(start)
+        }
+        MatchType::Either => at_prefix().or_else(at_suffix),
+        MatchType::Fingerprint | MatchType::Range | MatchType::Window => {
+            pubkey.windows(pattern.len()).position(matches_at)
+        }
+        MatchType::Word => pubkey
+            .windows(pattern.len())
+            .enumerate()
+            .position(|(start, window)| is_word_boundary(pubkey, start) && matches_at(window)),
+        MatchType::Anagram => anagram_start(pubkey, pattern, case_sensitive, max_distance),
+    }
+}
+
+// Where the first stretch of the address as long as the pattern starts that holds the pattern's characters in any
+// order, with at most max_distance of them missing (and as many others in their place). Case is ignored unless
+// case_sensitive; lookalikes never count, since an anagram is about the characters themselves.
+pub fn anagram_start(
+    pubkey: &[u8],
+    pattern: &[u8],
+    case_sensitive: bool,
+    max_distance: usize,
+) -> Option<usize> {
+    scan_anagram(pubkey, pattern, case_sensitive, |missing| {
+        missing <= max_distance
+    })
+}
+
+// Slides a window of the pattern's length along the address, keeping a count of each character the window still
+// lacks, and hands visit how many pattern characters each window misses until it returns true. Returns the start of
+// that window.
+fn scan_anagram<V: FnMut(usize) -> bool>(
+    pubkey: &[u8],
+    pattern: &[u8],
+    case_sensitive: bool,
+    mut visit: V,
+) -> Option<usize> {
+    let len = pattern.len();
+    if len == 0 || pubkey.len() < len {
+        return None;
+    }
+    let fold = |c: u8| {
+        if case_sensitive {
+            c as usize
+        } else {
+            c.to_ascii_lowercase() as usize
+        }
+    };
+    // how many more of each character the window needs; below 0 where it holds more than the pattern
+    let mut needed = [0i32; 256];
+    for &c in pattern {
+        needed[fold(c)] += 1;
+    }
+    let mut missing = len;
+    for (end, &c) in pubkey.iter().enumerate() {
+        let added = fold(c);
+        if needed[added] > 0 {
+            missing -= 1;
+        }
+        needed[added] -= 1;
+        if end >= len {
+            let dropped = fold(pubkey[end - len]);
+            needed[dropped] += 1;
+            if needed[dropped] > 0 {
+                missing += 1;
+            }
+        }
+        if end + 1 >= len && visit(missing) {
+            return Some(end + 1 - len);
+        }
+    }
+    None
+}
+
+// Number of '1's an address starts with, one per leading zero byte of the public key
+pub fn leading_ones(address: &[u8]) -> usize {
+    address.iter().take_while(|&&c| c == b'1').count()
+}
+
+// Whether a word starts at this position of the address, the way a reader splits "MySolWallet42" into words:
+// - the start of the address
+// - an upper case letter after a lower case letter or a digit ("my|Sol", "42|Sol"), but not after another upper
+//   case letter, since a run of capitals reads as one word
+// - a letter after a digit ("42|sol") or a digit after a letter ("sol|42")
+// A lower case letter after a lower case letter or a capital never starts a word.
+pub fn is_word_boundary(address: &[u8], position: usize) -> bool {
+    let Some(previous) = position.checked_sub(1).map(|i| address[i]) else {
+        return true;
+    };
+    let current = address[position];
+    (current.is_ascii_uppercase() && !previous.is_ascii_uppercase())
+        || (current.is_ascii_alphabetic() && previous.is_ascii_digit())
+        || (current.is_ascii_digit() && previous.is_ascii_alphabetic())
+}
+
+// How a single address character satisfied the pattern character at the same position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CharMatch {
+    Exact,
+    CaseFolded,
+    Lookalike,
+    // not accepted at all; only a match within a max distance has these
+    Mismatch,
+}
+
+impl CharMatch {
+    // Marker shown under the character: '=' exact, '~' case differs, '*' lookalike, 'x' wrong
+    pub fn marker(self) -> char {
+        match self {
+            CharMatch::Exact => '=',
+            CharMatch::CaseFolded => '~',
+            CharMatch::Lookalike => '*',
+            CharMatch::Mismatch => 'x',
+        }
+    }
+}
+
+// Classifies each character of a matched region against the pattern it matched, under the search's character rules
+pub fn classify_match(
+    matched: &[u8],
+    pattern: &[u8],
+    case_sensitive: bool,
+    flex_level: u8,
+) -> Vec<CharMatch> {
+    let flex_level = if case_sensitive { 0 } else { flex_level };
+    matched
+        .iter()
+        .zip(pattern)
+        .map(|(&c, &target)| {
+            if c == target {
+                CharMatch::Exact
+            } else if !matches_char(c, target, case_sensitive, flex_level) {
+                CharMatch::Mismatch
+            } else if c.eq_ignore_ascii_case(&target) {
+                CharMatch::CaseFolded
+            } else {
+                CharMatch::Lookalike
+            }
+        })
+        .collect()
+}
+
+// The pattern and the address characters it matched side by side, a column per character with its marker below, then
+// every substitution in words, so the user can judge whether the lookalikes really look alike. For "SoL" matched by
+// "5ol" with lookalikes on:
+//   pattern  S o L
+//   address  5 o l
+//            * = ~
+//   S as 5 (lookalike), L as l (case)
+// An exact match ends with "no substitutions".
+pub fn match_comparison(
+    matched: &[u8],
+    pattern: &[u8],
+    case_sensitive: bool,
+    flex_level: u8,
+) -> String {
+    let kinds = classify_match(matched, pattern, case_sensitive, flex_level);
+    fn spaced(chars: impl Iterator<Item = char>) -> String {
+        chars.map(String::from).collect::<Vec<_>>().join(" ")
+    }
+    let substitutions: Vec<String> = kinds
+        .iter()
+        .zip(pattern.iter().zip(matched))
+        .filter_map(|(&kind, (&target, &c))| {
+            let label = match kind {
+                CharMatch::Exact => return None,
+                CharMatch::CaseFolded => "case",
+                CharMatch::Lookalike => "lookalike",
+                CharMatch::Mismatch => "wrong",
+            };
+            Some(format!("{} as {} ({})", target as char, c as char, label))
+        })
+        .collect();
+    format!(
+        "  pattern  {}\n  address  {}\n           {}\n  {}",
+        spaced(pattern.iter().map(|&c| c as char)),
+        spaced(matched.iter().map(|&c| c as char)),
+        spaced(kinds.iter().map(|kind| kind.marker())),
+        if substitutions.is_empty() {
+            "no substitutions".to_string()
+        } else {
+            substitutions.join(", ")
+        }
+    )
+}
+
+// The base58 characters an address character may be for the pattern character to match, in alphabet order.
+// Pattern characters outside base58 accept nothing.
+pub fn accepted_chars(target: u8, case_sensitive: bool, flex_level: u8) -> Vec<u8> {
+    let flex_level = if case_sensitive { 0 } else { flex_level };
+    BASE58_ALPHABET
+        .iter()
+        .copied()
+        .filter(|&c| matches_char(c, target, case_sensitive, flex_level))
+        .collect()
+}
+
+// Checks which pattern finder method to use.
+// Ignoring case only ever widens a letter to the other case that base58 actually has: c always comes from an
+// address, so 'i' and 'o' can only match themselves and 'L' only 'L', never the missing 'I', 'O' or 'l'. Treating
+// those as two choices would double their odds in estimate, so accepted_chars counts against the alphabet.
+#[inline]
+fn matches_char(c: u8, target: u8, case_sensitive: bool, flex_level: u8) -> bool {
+    // case_sensitivity is true, flexible_chars is false
+    if case_sensitive {
+        c == target
+    // flexible_chars is true, case_sensitivity is false
+    } else if flex_level > 0 {
+        matches_flexible(c, target, flex_level)
+    // flexible_chars is false, case_sensitivity is false
+    } else {
+        c.eq_ignore_ascii_case(&target)
+    }
+}
+
+// Flexible char pattern finder that looks for similar chars, ignoring case, up to the given level
+#[inline]
+fn matches_flexible(c: u8, target: u8, flex_level: u8) -> bool {
+    c.eq_ignore_ascii_case(&target)
+        || (1..=flex_level).any(|level| lookalikes(target, level).contains(&c))
+}
+
+// Characters of the pattern that are easily mistaken for another base58 character (the level 1 lookalikes), each
+// once, in the order they appear, with the characters they are mistaken for. Without flexible matching such a
+// character only matches itself, which may not be the one the user meant.
+pub fn ambiguous_chars(pattern: &str) -> Vec<(u8, &'static [u8])> {
+    let mut ambiguous: Vec<(u8, &'static [u8])> = Vec::new();
+    for c in pattern.bytes() {
+        let confused_with = lookalikes(c, 1);
+        if !confused_with.is_empty() && !ambiguous.iter().any(|&(seen, _)| seen == c) {
+            ambiguous.push((c, confused_with));
+        }
+    }
+    ambiguous
+}
+
+// Base58 characters added as lookalikes of a pattern character at each flex level. The levels stack, so level 2
+// also accepts everything from level 1. Level 1 holds the digit and letter pairs that are hard to tell apart in most
+// fonts, level 2 the looser digit and letter pairs (the table --flexible-chars always used), level 3 letters that
+// only resemble each other at a glance.
+pub fn lookalikes(target: u8, level: u8) -> &'static [u8] {
+    match (level, target) {
+        (1, b'1') => b"iL",
+        (1, b'2') => b"zZ",
+        (1, b'5') => b"sS",
+        (1, b'8') => b"B",
+        (1, b'i' | b'L') => b"1",
+        (1, b'z' | b'Z') => b"2",
+        (1, b's' | b'S') => b"5",
+        (1, b'B') => b"8",
+
+        (2, b'3') => b"E",
+        (2, b'4') => b"A",
+        (2, b'6') => b"bG",
+        (2, b'7') => b"T",
+        (2, b'9') => b"g",
+        (2, b'a' | b'A') => b"4",
+        (2, b'b' | b'B') => b"6",
+        (2, b'e' | b'E') => b"3",
+        (2, b'g' | b'G') => b"69",
+        (2, b't' | b'T') => b"7",
+
+        (3, b'9') => b"qQ",
+        (3, b'a' | b'A') => b"o",
+        (3, b'o') => b"aA",
+        (3, b'c' | b'C') => b"eE",
+        (3, b'e' | b'E') => b"cC",
+        (3, b'f' | b'F') => b"tT",
+        (3, b't' | b'T') => b"fF",
+        (3, b'g' | b'G') => b"qQ",
+        (3, b'q' | b'Q') => b"gG9",
+        (3, b'h' | b'H') => b"nN",
+        (3, b'n' | b'N') => b"hH",
+        (3, b'i') => b"jJ",
+        (3, b'j' | b'J') => b"i",
+        (3, b'u' | b'U') => b"vV",
+        (3, b'v' | b'V') => b"uU",
+
+        _ => b"",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vectors::{USDC_MINT, WSOL_MINT};
+
+    fn matches(matcher: PatternMatcher, address: &str) -> bool {
+        matcher.matched_pattern(address.as_bytes()).is_some()
+    }
+
+    #[test]
+    fn fingerprint_matches_inside_the_window_only() {
+        let fingerprint = |pattern: &str, len| {
+            PatternMatcher::new(
+                vec![pattern.to_string()],
+                MatchType::Fingerprint,
+                true,
+                false,
+            )
+            .with_fingerprint_len(len)
+        };
+        // Wdd5 takes up characters 4 to 7, so it is no prefix but fits an 8 character fingerprint
+        assert!(!matches(
+            PatternMatcher::new(vec!["Wdd5".to_string()], MatchType::Prefix, true, false),
+            USDC_MINT
+        ));
+        assert!(matches(fingerprint("Wdd5", 8), USDC_MINT));
+        assert!(!matches(fingerprint("Wdd5", 7), USDC_MINT));
+        // further along the address doesn't count
+        assert!(!matches(fingerprint("AufqS", 8), USDC_MINT));
+        assert!(matches(fingerprint("AufqS", 13), USDC_MINT));
+    }
+
+    #[test]
+    fn exclude_rejects_an_address_the_pattern_matches() {
+        let prefix = |excludes: &[&str], case_sensitive| {
+            PatternMatcher::new(
+                vec!["EPj".to_string()],
+                MatchType::Prefix,
+                case_sensitive,
+                false,
+            )
+            .with_excludes(excludes.iter().map(|e| e.to_string()).collect())
+        };
+        assert!(matches(prefix(&[], true), USDC_MINT));
+        assert!(!matches(prefix(&["qSS"], true), USDC_MINT));
+        assert!(!matches(prefix(&["zzz", "Dt1v"], true), USDC_MINT));
+        // excludes ignore case only when the search does
+        assert!(matches(prefix(&["qss"], true), USDC_MINT));
+        assert!(!matches(prefix(&["qss"], false), USDC_MINT));
+    }
+
+    #[test]
+    fn range_matches_at_every_start_inside_the_range_only() {
+        let matcher = PatternMatcher::new(vec!["Sol".to_string()], MatchType::Range, true, false)
+            .with_at_range(2..5);
+        for offset in 0..=8 {
+            let address = format!("{}Sol{}", "x".repeat(offset), "x".repeat(41 - offset));
+            assert_eq!(
+                matcher.match_start(address.as_bytes(), "Sol"),
+                (2..5).contains(&offset).then_some(offset),
+                "Sol at {}",
+                offset
+            );
+        }
+    }
+
+    // Pattern entries and the pattern and per-pattern match type they split into
+    const MATCH_TYPE_VECTORS: &[(&str, &str, Option<MatchType>)] = &[
+        ("SoL", "SoL", None),
+        ("SoL:suffix", "SoL", Some(MatchType::Suffix)),
+        ("SoL:Prefix", "SoL", Some(MatchType::Prefix)),
+        ("SoL:middle", "SoL:middle", None),
+        ("a:b:either", "a:b", Some(MatchType::Either)),
+    ];
+
+    // Patterns, each with the match type it is searched with
+    type TypedPatterns = &'static [(&'static str, MatchType)];
+
+    // Addresses searched for several patterns that each have their own match type, and the pattern that must be
+    // reported as matched
+    const MIXED_TYPE_VECTORS: &[(&str, TypedPatterns, Option<&str>)] = &[
+        (
+            USDC_MINT,
+            &[("EPjF", MatchType::Prefix), ("Dt1v", MatchType::Suffix)],
+            Some("EPjF"),
+        ),
+        (
+            USDC_MINT,
+            &[("Dt1v", MatchType::Prefix), ("Dt1v", MatchType::Suffix)],
+            Some("Dt1v"),
+        ),
+        (
+            USDC_MINT,
+            &[("Dt1v", MatchType::Prefix), ("EPjF", MatchType::Suffix)],
+            None,
+        ),
+        (
+            WSOL_MINT,
+            &[("112", MatchType::Prefix), ("112", MatchType::Suffix)],
+            Some("112"),
+        ),
+        (
+            WSOL_MINT,
+            &[("So1", MatchType::Suffix), ("111", MatchType::Prefix)],
+            None,
+        ),
+    ];
+
+    #[test]
+    fn pattern_entries_carry_their_match_type() {
+        for &(entry, pattern, match_type) in MATCH_TYPE_VECTORS {
+            assert_eq!(
+                split_match_type(entry),
+                (pattern, match_type),
+                "pattern entry {:?}",
+                entry
+            );
+        }
+    }
+
+    #[test]
+    fn each_pattern_matches_with_its_own_type() {
+        for &(address, patterns, expected) in MIXED_TYPE_VECTORS {
+            let matcher = PatternMatcher::new(
+                patterns.iter().map(|(p, _)| p.to_string()).collect(),
+                MatchType::Prefix,
+                true,
+                false,
+            )
+            .with_match_types(patterns.iter().map(|&(_, t)| t).collect());
+            assert_eq!(
+                matcher.matched_pattern(address.as_bytes()),
+                expected,
+                "patterns {:?} on {}",
+                patterns,
+                address
+            );
+        }
+    }
+}
@@ -0,0 +1,1279 @@
+use crate::commit::{NONCE_LEN, commitment};
+use crate::hd::HD_DERIVATION_PATH;
+use crate::matcher::{encode_base58, to_hex};
+use bip39::Mnemonic;
+use clap::ValueEnum;
+use five8::BASE58_ENCODED_32_MAX_LEN;
+use image::{ImageFormat, Luma};
+use qrcode::{EcLevel, QrCode};
+use serde_json::json;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    Human,
+    Csv,
+    Json,
+    // human output on the console plus a PNG QR code of the address written to --output
+    QrPng,
+    // the solana-keygen paper wallet layout, ready to print for cold storage
+    Paper,
+    // SOLANA_KEYPAIR=... and SOLANA_PUBKEY=... lines for eval or source in scripts
+    Env,
+    // the keypair as 64 raw bytes (seed, then public key) in --output, or on stdout when that isn't a terminal
+    Raw,
+    // one line per found keypair from the --template string; not a --format value, --template selects it
+    #[value(skip)]
+    Template,
+}
+
+impl OutputFormat {
+    // Whether the console gets the human-oriented output
+    pub fn is_human(self) -> bool {
+        matches!(self, OutputFormat::Human | OutputFormat::QrPng)
+    }
+
+    // Ending of the file a format after the first in --format is written to
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            OutputFormat::Human => "txt",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Json => "record.json",
+            OutputFormat::QrPng => "png",
+            OutputFormat::Paper => "paper.txt",
+            OutputFormat::Env => "env",
+            OutputFormat::Raw => "bin",
+            OutputFormat::Template => "txt",
+        }
+    }
+}
+
+// A search result along with the stats reported for it
+pub struct FoundKeypair {
+    pub pattern: String,
+    pub keypair: Keypair,
+    pub mnemonic: Option<Mnemonic>,
+    pub attempts: u64,
+    pub elapsed_secs: f64,
+    // nonce of the --commit commitment to the keypair, kept with the record so the commitment can be opened later
+    pub commit_nonce: Option<[u8; NONCE_LEN]>,
+}
+
+// Remembers the addresses a collector has already reported, so a keypair that turns up twice (from a faulty
+// source, or a bug handing the same match over twice) isn't counted twice. Holds 32 bytes per address.
+#[derive(Default)]
+pub struct DistinctAddresses {
+    seen: HashSet<[u8; 32]>,
+    duplicates: u64,
+}
+
+impl DistinctAddresses {
+    // Records the keypair's address; false if it was already seen
+    pub fn insert(&mut self, keypair: &Keypair) -> bool {
+        let new = self.seen.insert(keypair.pubkey().to_bytes());
+        if !new {
+            self.duplicates += 1;
+        }
+        new
+    }
+
+    // Number of different addresses recorded
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    // Number of inserts turned away as repeats
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+}
+
+// Writes a header row plus one row per found keypair; non-numeric fields (including the secret) are always quoted.
+// HD results get an extra seed_phrase column since the phrase, not the derived secret, is what wallets import.
+// Without include_secret both the secret_base58 and seed_phrase columns are left out.
+pub fn write_csv<W: Write>(
+    writer: W,
+    rows: &[FoundKeypair],
+    include_secret: bool,
+) -> csv::Result<()> {
+    let with_seed_phrase = include_secret && rows.iter().any(|row| row.mnemonic.is_some());
+    let mut writer = csv::WriterBuilder::new()
+        .quote_style(csv::QuoteStyle::NonNumeric)
+        .from_writer(writer);
+    let mut header = vec![
+        "pattern",
+        "pubkey",
+        "secret_base58",
+        "attempts",
+        "elapsed_secs",
+    ];
+    if !include_secret {
+        header.retain(|&column| column != "secret_base58");
+    }
+    if with_seed_phrase {
+        header.push("seed_phrase");
+    }
+    writer.write_record(&header)?;
+    for row in rows {
+        let mut record = vec![
+            row.pattern.clone(),
+            row.keypair.pubkey().to_string(),
+            row.keypair.to_base58_string(),
+            row.attempts.to_string(),
+            format!("{:.3}", row.elapsed_secs),
+        ];
+        if !include_secret {
+            record.remove(2);
+        }
+        if with_seed_phrase {
+            record.push(
+                row.mnemonic
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            );
+        }
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// One JSON object per found keypair; "keypair" is the 64 byte array solana-keygen files use. With --commit it also
+// has the "commitment" and the "commit_nonce" that opens it, both hex.
+// Without include_secret the keypair, seed_phrase and commit_nonce fields are left out.
+pub fn json_record(found: &FoundKeypair, include_secret: bool) -> serde_json::Value {
+    let mut record = json!({
+        "pattern": found.pattern,
+        "pubkey": found.keypair.pubkey().to_string(),
+        "attempts": found.attempts,
+        "elapsed_secs": found.elapsed_secs,
+    });
+    if let Some(nonce) = &found.commit_nonce {
+        record["commitment"] = json!(commitment(&found.keypair, nonce));
+    }
+    if include_secret {
+        record["keypair"] = json!(found.keypair.to_bytes().to_vec());
+        if let Some(mnemonic) = &found.mnemonic {
+            record["seed_phrase"] = json!(mnemonic.to_string());
+        }
+        if let Some(nonce) = &found.commit_nonce {
+            record["commit_nonce"] = json!(to_hex(nonce));
+        }
+    }
+    record
+}
+
+// Shell variable assignments for a found keypair, one per line, for `eval` or `source` in scripts and CI jobs.
+// Base58 has no characters a shell treats specially, so the values need no quoting. Without include_secret only
+// SOLANA_PUBKEY is set.
+pub fn env_assignments(found: &FoundKeypair, include_secret: bool) -> String {
+    let pubkey = format!("SOLANA_PUBKEY={}", found.keypair.pubkey());
+    if include_secret {
+        format!(
+            "SOLANA_KEYPAIR={}\n{}",
+            found.keypair.to_base58_string(),
+            pubkey
+        )
+    } else {
+        pubkey
+    }
+}
+
+// Placeholders a --template line can use
+pub const TEMPLATE_PLACEHOLDERS: &[&str] = &["pubkey", "secret", "attempts", "elapsed", "pattern"];
+
+// Copies the template, replacing each {name} with what field gives for it. "{{" and "}}" stand for literal braces,
+// and any other lone brace is an error.
+fn expand_template(
+    template: &str,
+    mut field: impl FnMut(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if let Some(after) = tail.strip_prefix("{{") {
+            out.push('{');
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("}}") {
+            out.push('}');
+            rest = after;
+        } else if tail.starts_with('}') {
+            return Err(
+                "the template has a '}' that closes nothing; write '}}' for a literal brace"
+                    .to_string(),
+            );
+        } else {
+            let end = tail.find('}').ok_or(
+                "the template has a '{' that is never closed; write '{{' for a literal brace",
+            )?;
+            out.push_str(&field(&tail[1..end])?);
+            rest = &tail[end + 1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+// Checks a --template before the search starts, returning the placeholders it uses
+pub fn check_template(template: &str) -> Result<Vec<String>, String> {
+    let mut used = Vec::new();
+    expand_template(template, |name| {
+        if !TEMPLATE_PLACEHOLDERS.contains(&name) {
+            let known: Vec<String> = TEMPLATE_PLACEHOLDERS
+                .iter()
+                .map(|p| format!("{{{}}}", p))
+                .collect();
+            return Err(format!(
+                "unknown placeholder {{{}}} in the template; the placeholders are {}",
+                name,
+                known.join(", ")
+            ));
+        }
+        used.push(name.to_string());
+        Ok(String::new())
+    })?;
+    Ok(used)
+}
+
+// The --template line for a found keypair: {secret} is the base58 secret key as in the KP line and {elapsed} the
+// seconds the search took. The template is expected to have passed check_template; a placeholder it doesn't know
+// is left as written.
+pub fn render_template(template: &str, found: &FoundKeypair) -> String {
+    expand_template(template, |name| {
+        Ok(match name {
+            "pubkey" => found.keypair.pubkey().to_string(),
+            "secret" => found.keypair.to_base58_string(),
+            "attempts" => found.attempts.to_string(),
+            "elapsed" => format!("{:.2}", found.elapsed_secs),
+            "pattern" => found.pattern.clone(),
+            other => format!("{{{}}}", other),
+        })
+    })
+    .unwrap_or_else(|_| template.to_string())
+}
+
+// The paper wallet layout of solana-keygen: the address and what recovers it, between divider lines as wide as the
+// longest line. A key from a seed phrase shows the phrase and the account path it was derived with; any other key
+// shows its base58 secret key.
+pub fn paper_wallet(found: &FoundKeypair) -> String {
+    let mut lines = vec![format!("pubkey: {}", found.keypair.pubkey())];
+    let mut body = Vec::new();
+    match &found.mnemonic {
+        Some(mnemonic) => {
+            body.push("Save this seed phrase to recover your new keypair:".to_string());
+            body.push(mnemonic.to_string());
+            body.push(format!("Derivation path: {}", HD_DERIVATION_PATH));
+            body.push("Recover with: solana-keygen recover 'prompt://?key=0/0'".to_string());
+        }
+        None => {
+            body.push("Save this secret key to recover your new keypair:".to_string());
+            body.push(found.keypair.to_base58_string());
+            body.push("Recover by importing it as a private key into a wallet".to_string());
+        }
+    }
+    let width = lines
+        .iter()
+        .chain(&body)
+        .map(|line| line.len())
+        .max()
+        .unwrap_or(0);
+    let divider = "=".repeat(width);
+
+    lines.insert(0, divider.clone());
+    lines.push(divider.clone());
+    lines.extend(body);
+    lines.push(divider);
+    lines.join("\n")
+}
+
+// The address with the len characters from start wrapped in brackets, e.g. "[Sol]ana..." for a prefix match
+pub fn format_highlighted(pubkey: &str, start: usize, len: usize) -> String {
+    highlight(pubkey, start, len, "[", "]")
+}
+
+// Like format_highlighted, but marks the region bold green with ANSI codes for terminals
+pub fn format_highlighted_color(pubkey: &str, start: usize, len: usize) -> String {
+    highlight(pubkey, start, len, "\x1b[1;32m", "\x1b[0m")
+}
+
+// Separator --group puts between groups of characters
+pub const GROUP_SEPARATOR: char = ' ';
+
+// The address split into groups of `group` characters, e.g. "SoLa na5z H2Cy..." for 4, the way some wallets and
+// explorers display it
+pub fn group_address(pubkey: &str, group: usize) -> String {
+    let mut grouped = String::with_capacity(pubkey.len() + pubkey.len() / group);
+    for (i, ch) in pubkey.chars().enumerate() {
+        if i > 0 && i % group == 0 {
+            grouped.push(GROUP_SEPARATOR);
+        }
+        grouped.push(ch);
+    }
+    grouped
+}
+
+// Maps a match at start with len characters in the address to the same characters in group_address's display:
+// every group boundary before a character shifts it one further. A match spanning a boundary takes the separator
+// with it, so it stays one highlighted region.
+pub fn grouped_span(start: usize, len: usize, group: usize) -> (usize, usize) {
+    let grouped_start = start + start / group;
+    if len == 0 {
+        return (grouped_start, 0);
+    }
+    let last = start + len - 1;
+    (grouped_start, last + last / group + 1 - grouped_start)
+}
+
+fn highlight(pubkey: &str, start: usize, len: usize, open: &str, close: &str) -> String {
+    let start = start.min(pubkey.len());
+    let end = (start + len).min(pubkey.len());
+    format!(
+        "{}{}{}{}{}",
+        &pubkey[..start],
+        open,
+        &pubkey[start..end],
+        close,
+        &pubkey[end..]
+    )
+}
+
+// Each QR module is drawn as a square of this many pixels
+pub const QR_MODULE_PX: u32 = 8;
+
+// Writes a PNG QR code of the address using error correction level M (about 15% of the code can be damaged and
+// still scan), surrounded by the standard 4 module quiet zone. Returns the image width and height in pixels.
+pub fn write_qr_png(path: &Path, address: &str) -> Result<(u32, u32), String> {
+    let code =
+        QrCode::with_error_correction_level(address, EcLevel::M).map_err(|e| e.to_string())?;
+    let image = code
+        .render::<Luma<u8>>()
+        .module_dimensions(QR_MODULE_PX, QR_MODULE_PX)
+        .build();
+
+    let file = File::create_new(path).map_err(|e| e.to_string())?;
+    image
+        .write_to(&mut BufWriter::new(file), ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(image.dimensions())
+}
+
+// Opens a file readable only by the owner, since it will hold secret keys
+pub(crate) fn open_private(options: &mut OpenOptions, path: &Path) -> io::Result<File> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+// Makes sure the output file's directory exists (creating it if asked) and accepts new files, so a bad path is
+// reported before the search rather than after it
+pub fn check_output_dir(path: &Path, create: bool) -> Result<(), String> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if !dir.is_dir() {
+        if !create {
+            return Err(format!(
+                "Output directory {} does not exist; pass --mkdir to create it",
+                dir.display()
+            ));
+        }
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Cannot create output directory {}: {}", dir.display(), e))?;
+    }
+
+    let probe = dir.join(format!(".vanity-write-check-{}", std::process::id()));
+    File::create_new(&probe)
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| format!("Output directory {} is not writable: {}", dir.display(), e))
+}
+
+// Writes a keypair file in the solana-keygen format; refuses to overwrite an existing file
+pub fn write_keypair_file(path: &Path, keypair: &Keypair) -> io::Result<()> {
+    let mut file = open_private(OpenOptions::new().write(true).create_new(true), path)?;
+    solana_keypair::write_keypair(keypair, &mut file)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    file.sync_all()
+}
+
+// Parses an age X25519 recipient, the "age1..." public key age-keygen prints
+pub fn parse_age_recipient(s: &str) -> Result<age::x25519::Recipient, String> {
+    s.parse()
+        .map_err(|e| format!("'{}' is not an age recipient: {}", s, e))
+}
+
+// The solana-keygen keypair file encrypted to the age recipient; `age -d -i <identity file>` gives back the file
+pub fn encrypt_keypair(
+    keypair: &Keypair,
+    recipient: &age::x25519::Recipient,
+) -> Result<Vec<u8>, String> {
+    let mut plaintext = Vec::new();
+    solana_keypair::write_keypair(keypair, &mut plaintext).map_err(|e| e.to_string())?;
+    age::encrypt(recipient, &plaintext).map_err(|e| e.to_string())
+}
+
+// Writes the keypair file encrypted to the age recipient; refuses to overwrite an existing file
+pub fn write_age_keypair_file(
+    path: &Path,
+    keypair: &Keypair,
+    recipient: &age::x25519::Recipient,
+) -> io::Result<()> {
+    let ciphertext = encrypt_keypair(keypair, recipient).map_err(io::Error::other)?;
+    let mut file = open_private(OpenOptions::new().write(true).create_new(true), path)?;
+    file.write_all(&ciphertext)?;
+    file.sync_all()
+}
+
+// File a format after the first in --format writes a found keypair to, e.g. "<ADDRESS>.paper.txt"
+pub fn format_file_name(address: &str, format: OutputFormat) -> String {
+    format!("{}.{}", address, format.file_extension())
+}
+
+// The file contents of one found keypair in a format, secret included since the file is owner-only like a keypair
+// file. The human output is for the console and qr-png renders an image, so neither has text to give.
+pub fn render_format(found: &FoundKeypair, format: OutputFormat) -> Option<Vec<u8>> {
+    let text = match format {
+        OutputFormat::Human | OutputFormat::QrPng | OutputFormat::Template => return None,
+        OutputFormat::Raw => return Some(found.keypair.to_bytes().to_vec()),
+        OutputFormat::Csv => {
+            let mut csv = Vec::new();
+            write_csv(&mut csv, std::slice::from_ref(found), true).ok()?;
+            return Some(csv);
+        }
+        OutputFormat::Json => json_record(found, true).to_string(),
+        OutputFormat::Paper => paper_wallet(found),
+        OutputFormat::Env => env_assignments(found, true),
+    };
+    Some(format!("{}\n", text).into_bytes())
+}
+
+// Writes one found keypair in a format to a new owner-only file; qr-png writes the address as a PNG QR code
+pub fn write_format_file(
+    path: &Path,
+    format: OutputFormat,
+    found: &FoundKeypair,
+) -> Result<(), String> {
+    if format == OutputFormat::QrPng {
+        return write_qr_png(path, &found.keypair.pubkey().to_string()).map(|_| ());
+    }
+    let contents = render_format(found, format)
+        .ok_or_else(|| format!("{:?} output can't be written to a file", format))?;
+    let mut file = open_private(OpenOptions::new().write(true).create_new(true), path)
+        .map_err(|e| e.to_string())?;
+    file.write_all(&contents)
+        .and_then(|()| file.sync_all())
+        .map_err(|e| e.to_string())
+}
+
+// The token program a --mint keypair is meant for. The mint address is a plain keypair address either way; the
+// program only decides how the result is labeled and which spl-token command creates the token.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenProgram {
+    Token,
+    // Token-2022, the token extensions program, which spl-token uses with --program-2022
+    Token2022,
+}
+
+impl TokenProgram {
+    // Label of the found address in the human output
+    pub fn address_label(self) -> &'static str {
+        match self {
+            TokenProgram::Token => "Found mint address",
+            TokenProgram::Token2022 => "Found Token-2022 mint address",
+        }
+    }
+
+    // The spl-token command that creates the token with the mint keypair file at path
+    pub fn create_command(self, path: &Path) -> String {
+        match self {
+            TokenProgram::Token => format!("spl-token create-token {}", path.display()),
+            TokenProgram::Token2022 => {
+                format!("spl-token --program-2022 create-token {}", path.display())
+            }
+        }
+    }
+}
+
+// Reads a saved mint keypair file back and checks that it can create the mint: spl-token signs the creation of the
+// mint account with it, so the file has to hold the found keypair and its secret has to sign for the address
+pub fn check_mint_keypair_file(path: &Path, keypair: &Keypair) -> Result<(), String> {
+    verify_keypair_file(path, keypair)?;
+    let message = b"solana_vanity_address mint check";
+    if !keypair
+        .sign_message(message)
+        .verify(keypair.pubkey().as_ref(), message)
+    {
+        return Err("the keypair's secret doesn't sign for its address".to_string());
+    }
+    Ok(())
+}
+
+// Ending of the file a mint keypair is saved to when no --output is given
+pub const MINT_FILE_SUFFIX: &str = "-mint.json";
+
+// File name for a token mint keypair, e.g. "MintXyz...-mint.json", so a directory of them shows which address each
+// one creates and that it is meant for spl-token create-token rather than as a wallet
+pub fn mint_keypair_file_name(address: &str) -> String {
+    format!("{}{}", address, MINT_FILE_SUFFIX)
+}
+
+// The steps from a keypair's secret to its address. Each is recomputed from the one before instead of read back
+// from the keypair, so printing them shows the address really follows from the secret.
+pub struct Derivation {
+    // the 32 byte ed25519 seed, the first half of a keypair file
+    pub seed: [u8; 32],
+    // the public key ed25519 derives from the seed
+    pub pubkey: [u8; 32],
+    // the public key in base58, which is the address
+    pub address: String,
+}
+
+impl Derivation {
+    pub fn new(keypair: &Keypair) -> Self {
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&keypair.to_bytes()[..32]);
+        let pubkey = Keypair::new_from_array(seed).pubkey().to_bytes();
+        let mut buf = [0u8; BASE58_ENCODED_32_MAX_LEN];
+        let address = String::from_utf8_lossy(encode_base58(&pubkey, &mut buf)).into_owned();
+        Derivation {
+            seed,
+            pubkey,
+            address,
+        }
+    }
+
+    // One line per step, bytes in lower case hex
+    pub fn lines(&self) -> String {
+        format!(
+            "Seed (32 bytes): {}\nPublic key (32 bytes): {}\nBase58 public key: {}",
+            to_hex(&self.seed),
+            to_hex(&self.pubkey),
+            self.address
+        )
+    }
+}
+
+// Length of a raw keypair: the 32 byte ed25519 seed followed by the 32 byte public key
+pub const RAW_KEYPAIR_LEN: usize = 64;
+
+// Writes the keypair as exactly RAW_KEYPAIR_LEN bytes, the same bytes a keypair file holds as a JSON array
+pub fn write_raw(mut out: impl Write, keypair: &Keypair) -> io::Result<()> {
+    out.write_all(&keypair.to_bytes())?;
+    out.flush()
+}
+
+// Writes the raw keypair to a new file readable only by the owner
+pub fn write_raw_keypair_file(path: &Path, keypair: &Keypair) -> io::Result<()> {
+    let mut file = open_private(OpenOptions::new().write(true).create_new(true), path)?;
+    write_raw(&mut file, keypair)?;
+    file.sync_all()
+}
+
+// Parses raw keypair bytes, checking the length and that the public key belongs to the seed
+pub fn read_raw_keypair(bytes: &[u8]) -> Result<Keypair, String> {
+    if bytes.len() != RAW_KEYPAIR_LEN {
+        return Err(format!(
+            "expected {} bytes, found {}",
+            RAW_KEYPAIR_LEN,
+            bytes.len()
+        ));
+    }
+    Keypair::try_from(bytes).map_err(|e| e.to_string())
+}
+
+// Reads a keypair file back and checks it holds exactly the given keypair
+pub fn verify_keypair_file(path: &Path, keypair: &Keypair) -> Result<(), String> {
+    let read = solana_keypair::read_keypair_file(path).map_err(|e| e.to_string())?;
+    if read.to_bytes() != keypair.to_bytes() {
+        return Err("file contents don't match the found keypair".to_string());
+    }
+    Ok(())
+}
+
+// Addresses of every record in a JSON lines file, skipping lines that aren't records
+pub fn stored_pubkeys(path: &Path) -> io::Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter_map(|record| record["pubkey"].as_str().map(str::to_string))
+        .collect())
+}
+
+// Reads a JSON lines file back and returns the indices of the rows whose keypair isn't stored intact in it
+pub fn missing_from_jsonl(path: &Path, rows: &[FoundKeypair]) -> io::Result<Vec<usize>> {
+    let contents = std::fs::read_to_string(path)?;
+    let stored: Vec<Vec<u8>> = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter_map(|record| serde_json::from_value(record["keypair"].clone()).ok())
+        .collect();
+    Ok(rows
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| {
+            let bytes = row.keypair.to_bytes();
+            !stored.iter().any(|s| s[..] == bytes[..])
+        })
+        .map(|(i, _)| i)
+        .collect())
+}
+
+// Appends one JSON line per keypair. The file is locked for the duration and each run's lines are written in a
+// single call, so concurrent invocations appending to the same file don't interleave.
+pub fn append_jsonl<'a>(
+    path: &Path,
+    rows: impl IntoIterator<Item = &'a FoundKeypair>,
+) -> io::Result<()> {
+    let mut lines = String::new();
+    for row in rows {
+        lines.push_str(&json_record(row, true).to_string());
+        lines.push('\n');
+    }
+
+    let mut file = open_private(OpenOptions::new().append(true).create(true), path)?;
+    file.lock()?;
+    file.write_all(lines.as_bytes())?;
+    file.sync_all()?;
+    file.unlock()
+}
+
+// The n-th file of a rotated --output: the file itself first, then found.1.jsonl, found.2.jsonl and so on
+pub fn rotated_path(base: &Path, n: u32) -> PathBuf {
+    if n == 0 {
+        return base.to_path_buf();
+    }
+    let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match base.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, n),
+    };
+    base.with_file_name(name)
+}
+
+// Spreads the JSON lines of a long --stream run over numbered files, moving on to the next file once the current
+// one would grow past max_size bytes or already holds max_count lines. A line longer than max_size gets a file of
+// its own rather than being split.
+pub struct RotatingJsonl {
+    base: PathBuf,
+    max_size: Option<u64>,
+    max_count: Option<u64>,
+    index: u32,
+    size: u64,
+    count: u64,
+}
+
+impl RotatingJsonl {
+    // Continues in the last numbered file an earlier run left behind, so appending again never overfills a file
+    pub fn open(base: &Path, max_size: Option<u64>, max_count: Option<u64>) -> io::Result<Self> {
+        let mut index = 0;
+        while rotated_path(base, index + 1).exists() {
+            index += 1;
+        }
+        let (size, count) = match std::fs::read(rotated_path(base, index)) {
+            Ok(bytes) => (
+                bytes.len() as u64,
+                bytes.iter().filter(|&&b| b == b'\n').count() as u64,
+            ),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (0, 0),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            base: base.to_path_buf(),
+            max_size,
+            max_count,
+            index,
+            size,
+            count,
+        })
+    }
+
+    // The file the JSON line of this keypair goes to, counting the line as written there
+    pub fn next_path(&mut self, found: &FoundKeypair) -> PathBuf {
+        let len = json_record(found, true).to_string().len() as u64 + 1;
+        let full = self.size > 0
+            && (self.max_size.is_some_and(|max| self.size + len > max)
+                || self.max_count.is_some_and(|max| self.count >= max));
+        if full {
+            self.index += 1;
+            self.size = 0;
+            self.count = 0;
+        }
+        self.size += len;
+        self.count += 1;
+        rotated_path(&self.base, self.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vectors::{USDC_MINT, WSOL_MINT};
+
+    // A path in the temporary directory that no other test or concurrent run uses
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("vanity-output-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn missing_output_directory_is_refused_unless_created() {
+        let dir = temp_path("missing");
+        let path = dir.join("nested").join("key.json");
+        let refused = check_output_dir(&path, false);
+        assert!(refused.unwrap_err().contains("--mkdir"));
+        assert!(!dir.exists());
+
+        let created = check_output_dir(&path, true);
+        let nested = path.parent().unwrap().is_dir();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(created, Ok(()));
+        assert!(nested);
+    }
+
+    #[test]
+    fn written_keypair_file_reads_back() {
+        let path = temp_path("verify.json");
+        let keypair = Keypair::new_from_array([1; 32]);
+        let written = write_keypair_file(&path, &keypair);
+        let own = verify_keypair_file(&path, &keypair);
+        let other = verify_keypair_file(&path, &Keypair::new_from_array([2; 32]));
+        // a write cut short leaves a file that no longer parses
+        let contents = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &contents[..contents.len() / 2]).unwrap();
+        let truncated = verify_keypair_file(&path, &keypair);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(written.is_ok());
+        assert_eq!(own, Ok(()));
+        assert!(other.is_err());
+        assert!(truncated.is_err());
+    }
+
+    #[test]
+    fn repeated_addresses_are_counted_once() {
+        let mut distinct = DistinctAddresses::default();
+        let inserted: Vec<bool> = [1, 2, 1, 1, 3]
+            .iter()
+            .map(|&seed| distinct.insert(&Keypair::new_from_array([seed; 32])))
+            .collect();
+        assert_eq!(inserted, [true, true, false, false, true]);
+        assert_eq!((distinct.len(), distinct.duplicates()), (3, 2));
+    }
+
+    // Seeds ([b; 32] for each b) of keypair files encrypted to a freshly generated age key. Each must decrypt with that
+    // key back to the same keypair, and not with another key.
+    const AGE_VECTORS: &[u8] = &[1, 7, 255];
+
+    // --age-recipient values and whether they are accepted
+    const AGE_RECIPIENT_VECTORS: &[(&str, bool)] = &[
+        (
+            "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p",
+            true,
+        ),
+        // checksum broken by the last character
+        (
+            "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8q",
+            false,
+        ),
+        ("age1bad", false),
+        (USDC_MINT, false),
+        ("", false),
+    ];
+
+    #[test]
+    fn age_encrypted_keypair_opens_with_its_own_key_only() {
+        for &seed in AGE_VECTORS {
+            let keypair = Keypair::new_from_array([seed; 32]);
+            let identity = age::x25519::Identity::generate();
+            let other = age::x25519::Identity::generate();
+            let ciphertext = encrypt_keypair(&keypair, &identity.to_public()).unwrap();
+            let plaintext = age::decrypt(&identity, &ciphertext).unwrap();
+            let decrypted = solana_keypair::read_keypair(&mut plaintext.as_slice()).unwrap();
+            assert_eq!(
+                decrypted.to_bytes(),
+                keypair.to_bytes(),
+                "age encrypted keypair of seed {}",
+                seed
+            );
+            assert!(
+                age::decrypt(&other, &ciphertext).is_err(),
+                "age encrypted keypair of seed {} opens with another key",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn age_recipients_are_checked() {
+        for &(recipient, expected) in AGE_RECIPIENT_VECTORS {
+            assert_eq!(
+                parse_age_recipient(recipient).is_ok(),
+                expected,
+                "age recipient '{}'",
+                recipient
+            );
+        }
+    }
+
+    // Secret seeds written in the raw format and read back. With the last byte of the public key flipped, reading has to
+    // fail since the public key no longer belongs to the seed.
+    const RAW_VECTORS: &[[u8; 32]] = &[[1; 32], [7; 32], [0; 32]];
+
+    // Secret seeds, whether the secret is included, and the exact lines --format env prints for that keypair
+    const ENV_VECTORS: &[([u8; 32], bool, &str)] = &[
+        (
+            [1; 32],
+            true,
+            "SOLANA_KEYPAIR=2AXDGYSE4f2sz7tvMMzyHvUfcoJmxudvdhBcmiUSo6iuCXagjUCKEQF21awZnUGxmwD4m9vGXuC3qieHXJQHAcT\n\
+             SOLANA_PUBKEY=AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9",
+        ),
+        (
+            [1; 32],
+            false,
+            "SOLANA_PUBKEY=AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9",
+        ),
+    ];
+
+    // --template strings and the line each must print for the keypair of seed [1; 32] found for "SoL" after 1234
+    // attempts in 2.5 seconds; None where the template has to be rejected at startup
+    const TEMPLATE_VECTORS: &[(&str, Option<&str>)] = &[
+        (
+            "{pubkey}",
+            Some("AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9"),
+        ),
+        (
+            "{pattern}: {pubkey} after {attempts} tries in {elapsed}s",
+            Some("SoL: AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9 after 1234 tries in 2.50s"),
+        ),
+        (
+            "{pubkey},{secret}",
+            Some(
+                "AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9,\
+                 2AXDGYSE4f2sz7tvMMzyHvUfcoJmxudvdhBcmiUSo6iuCXagjUCKEQF21awZnUGxmwD4m9vGXuC3qieHXJQHAcT",
+            ),
+        ),
+        // doubled braces are literal
+        (
+            "{{\"address\": \"{pubkey}\"}}",
+            Some("{\"address\": \"AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9\"}"),
+        ),
+        ("found one", Some("found one")),
+        ("{address}", None),
+        ("{}", None),
+        ("{pubkey", None),
+        ("pubkey}", None),
+    ];
+
+    // (start, len) of a span of characters
+    type Span = (usize, usize);
+
+    // A match at (start, len) in USDC_MINT, the --group size, where the match lands in the grouped display, and the
+    // bracketed preview. Matches that cross a group boundary take the separator into the highlight.
+    const GROUP_VECTORS: &[(usize, usize, usize, Span, &str)] = &[
+        (
+            0,
+            3,
+            4,
+            (0, 3),
+            "[EPj]F Wdd5 Aufq SSqe M2qN 1xzy bapC 8G4w EGGk ZwyT Dt1v",
+        ),
+        (
+            2,
+            4,
+            4,
+            (2, 5),
+            "EP[jF Wd]d5 Aufq SSqe M2qN 1xzy bapC 8G4w EGGk ZwyT Dt1v",
+        ),
+        (
+            4,
+            4,
+            4,
+            (5, 4),
+            "EPjF [Wdd5] Aufq SSqe M2qN 1xzy bapC 8G4w EGGk ZwyT Dt1v",
+        ),
+        (
+            5,
+            9,
+            4,
+            (6, 11),
+            "EPjF W[dd5 Aufq SS]qe M2qN 1xzy bapC 8G4w EGGk ZwyT Dt1v",
+        ),
+        (
+            40,
+            4,
+            4,
+            (50, 4),
+            "EPjF Wdd5 Aufq SSqe M2qN 1xzy bapC 8G4w EGGk ZwyT [Dt1v]",
+        ),
+    ];
+
+    // Formats listed after the first in --format, the file each writes the keypair of seed [1; 32] to, and whether the
+    // file is a line of text naming the address (raw holds the keypair bytes, qr-png is drawn by write_qr_png)
+    const FORMAT_FILE_VECTORS: &[(OutputFormat, &str, bool)] = &[
+        (
+            OutputFormat::Json,
+            "AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9.record.json",
+            true,
+        ),
+        (
+            OutputFormat::Paper,
+            "AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9.paper.txt",
+            true,
+        ),
+        (
+            OutputFormat::Csv,
+            "AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9.csv",
+            true,
+        ),
+        (
+            OutputFormat::Env,
+            "AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9.env",
+            true,
+        ),
+        (
+            OutputFormat::Raw,
+            "AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9.bin",
+            false,
+        ),
+        (
+            OutputFormat::QrPng,
+            "AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9.png",
+            false,
+        ),
+    ];
+
+    // Seeds ([b; 32] for each b) whose --show-derivation output must carry exactly the keypair's public key bytes and
+    // address
+    const DERIVATION_VECTORS: &[u8] = &[1, 7, 255];
+
+    // Base names of --output and the file rotation number 2 goes to
+    const ROTATED_PATH_VECTORS: &[(&str, &str)] = &[
+        ("found.jsonl", "found.2.jsonl"),
+        ("out/found.jsonl", "out/found.2.jsonl"),
+        ("found", "found.2"),
+        ("found.v1.jsonl", "found.v1.2.jsonl"),
+    ];
+
+    // Mint addresses and the file --mint saves their keypair to when no --output is given
+    const MINT_FILE_VECTORS: &[(&str, &str)] = &[
+        (
+            USDC_MINT,
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v-mint.json",
+        ),
+        (
+            WSOL_MINT,
+            "So11111111111111111111111111111111111111112-mint.json",
+        ),
+    ];
+
+    // Token programs, a mint keypair file, and the label and spl-token command --mint prints for them
+    const TOKEN_PROGRAM_VECTORS: &[(TokenProgram, &str, &str, &str)] = &[
+        (
+            TokenProgram::Token,
+            "MintQ3-mint.json",
+            "Found mint address",
+            "spl-token create-token MintQ3-mint.json",
+        ),
+        (
+            TokenProgram::Token2022,
+            "MintQ3-mint.json",
+            "Found Token-2022 mint address",
+            "spl-token --program-2022 create-token MintQ3-mint.json",
+        ),
+        (
+            TokenProgram::Token2022,
+            "keys/mint.json",
+            "Found Token-2022 mint address",
+            "spl-token --program-2022 create-token keys/mint.json",
+        ),
+    ];
+
+    // Keypair seeds and whether stdout shows secrets (no --no-secret). With -o --append a found keypair goes to both
+    // the file and stdout; the stdout record must be the file's line, less the secret fields without include_secret.
+    const TEE_VECTORS: &[(u8, bool)] = &[(1, true), (2, false)];
+
+    // Rotation limits (--rotate-size, --rotate-count), how many keypairs are streamed into found.jsonl and the
+    // number of lines each numbered file must end up with. Halfway through the writer is opened again, as a second run
+    // appending to the same files would, and has to continue where the first one stopped.
+    type RotateLimits = (Option<u64>, Option<u64>);
+    const ROTATE_VECTORS: &[(RotateLimits, u8, &[usize])] = &[
+        ((None, Some(2)), 5, &[2, 2, 1]),
+        ((None, Some(3)), 3, &[3]),
+        // every line is longer than a byte, so each gets a file of its own
+        ((Some(1), None), 3, &[1, 1, 1]),
+        ((Some(1 << 20), None), 4, &[4]),
+        ((Some(1 << 20), Some(3)), 4, &[3, 1]),
+    ];
+
+    fn found_keypair(pattern: &str, seed: u8, attempts: u64, elapsed_secs: f64) -> FoundKeypair {
+        FoundKeypair {
+            pattern: pattern.to_string(),
+            keypair: Keypair::new_from_array([seed; 32]),
+            mnemonic: None,
+            attempts,
+            elapsed_secs,
+            commit_nonce: None,
+        }
+    }
+
+    #[test]
+    fn raw_output_reads_back() {
+        for &seed in RAW_VECTORS {
+            let keypair = Keypair::new_from_array(seed);
+            let mut bytes = Vec::new();
+            write_raw(&mut bytes, &keypair).unwrap();
+            assert_eq!(
+                bytes.len(),
+                RAW_KEYPAIR_LEN,
+                "raw output for seed {:?}",
+                seed[0]
+            );
+            assert_eq!(bytes[..32], seed);
+            assert_eq!(bytes[32..], keypair.pubkey().to_bytes());
+            assert_eq!(
+                read_raw_keypair(&bytes).map(|read| read.to_bytes()),
+                Ok(keypair.to_bytes()),
+                "raw output for seed {:?}",
+                seed[0]
+            );
+            bytes[RAW_KEYPAIR_LEN - 1] ^= 1;
+            assert!(
+                read_raw_keypair(&bytes).is_err(),
+                "raw input for seed {:?} with a corrupted public key was accepted",
+                seed[0]
+            );
+        }
+    }
+
+    #[test]
+    fn env_output_assigns_every_field() {
+        for &(seed, include_secret, expected) in ENV_VECTORS {
+            let found = FoundKeypair {
+                keypair: Keypair::new_from_array(seed),
+                ..found_keypair("", 0, 0, 0.0)
+            };
+            assert_eq!(
+                env_assignments(&found, include_secret),
+                expected,
+                "env output for seed {:?} (secret: {})",
+                seed[0],
+                include_secret
+            );
+        }
+    }
+
+    #[test]
+    fn templates_render_or_are_rejected() {
+        let found = found_keypair("SoL", 1, 1234, 2.5);
+        for &(template, expected) in TEMPLATE_VECTORS {
+            let line = check_template(template)
+                .ok()
+                .map(|_| render_template(template, &found));
+            assert_eq!(line.as_deref(), expected, "template {:?}", template);
+        }
+    }
+
+    #[test]
+    fn grouped_preview_highlights_the_match() {
+        for &(start, len, group, expected_span, expected) in GROUP_VECTORS {
+            let span = grouped_span(start, len, group);
+            let previewed = format_highlighted(&group_address(USDC_MINT, group), span.0, span.1);
+            assert_eq!(
+                (span, previewed.as_str()),
+                (expected_span, expected),
+                "match of {} at {} grouped by {}",
+                len,
+                start,
+                group
+            );
+        }
+    }
+
+    #[test]
+    fn additional_formats_write_a_file_each() {
+        // one keypair emitted in every format at once, as --format json,paper,csv,env,raw,qr-png does
+        let found = found_keypair("AKnL", 1, 1, 0.0);
+        let address = found.keypair.pubkey().to_string();
+        for &(format, expected_name, text) in FORMAT_FILE_VECTORS {
+            assert_eq!(format_file_name(&address, format), expected_name);
+            let rendered = render_format(&found, format);
+            let contents_ok = match &rendered {
+                None => format == OutputFormat::QrPng,
+                Some(bytes) if text => {
+                    let text = String::from_utf8_lossy(bytes);
+                    text.contains(&address) && text.ends_with('\n')
+                }
+                Some(bytes) => bytes.as_slice() == found.keypair.to_bytes().as_slice(),
+            };
+            assert!(
+                contents_ok,
+                "{:?} as an additional format (text: {}): got {:?}",
+                format,
+                text,
+                rendered.map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            );
+        }
+        assert!(render_format(&found, OutputFormat::Human).is_none());
+    }
+
+    #[test]
+    fn derivation_shows_the_public_key() {
+        for &seed in DERIVATION_VECTORS {
+            let keypair = Keypair::new_from_array([seed; 32]);
+            let derivation = Derivation::new(&keypair);
+            let expected = keypair.pubkey().to_bytes();
+            assert_eq!(derivation.seed, [seed; 32]);
+            assert_eq!(derivation.pubkey, expected);
+            assert_eq!(derivation.address, keypair.pubkey().to_string());
+            assert!(
+                derivation.lines().contains(&to_hex(&expected)),
+                "derivation of seed {}:\n{}",
+                seed,
+                derivation.lines()
+            );
+        }
+    }
+
+    #[test]
+    fn rotated_files_are_numbered_before_the_extension() {
+        for &(base, expected) in ROTATED_PATH_VECTORS {
+            assert_eq!(
+                rotated_path(Path::new(base), 2),
+                Path::new(expected),
+                "rotated path 2 of {}",
+                base
+            );
+        }
+    }
+
+    #[test]
+    fn mint_keypair_is_saved_under_its_address() {
+        for &(address, expected) in MINT_FILE_VECTORS {
+            assert_eq!(
+                mint_keypair_file_name(address),
+                expected,
+                "mint keypair file for {}",
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn token_programs_print_their_create_command() {
+        for &(program, path, expected_label, expected_command) in TOKEN_PROGRAM_VECTORS {
+            assert_eq!(
+                (
+                    program.address_label(),
+                    program.create_command(Path::new(path))
+                ),
+                (expected_label, expected_command.to_string()),
+                "{:?} mint at {}",
+                program,
+                path
+            );
+        }
+    }
+
+    // Appends the keypair of the seed to a temporary --output file and compares its line with the record --format json
+    // prints; returns the fields where stdout differs from the file
+    fn tee_differences(seed: u8, include_secret: bool) -> Vec<String> {
+        let found = found_keypair("Tee", seed, 42, 1.5);
+        let path = temp_path(&format!("tee-{}.jsonl", seed));
+        let written = append_jsonl(&path, [&found]).and_then(|()| std::fs::read_to_string(&path));
+        let _ = std::fs::remove_file(&path);
+        let file: serde_json::Value = serde_json::from_str(written.unwrap().trim()).unwrap();
+        let stdout = json_record(&found, include_secret);
+        let (file, stdout) = (file.as_object().unwrap(), stdout.as_object().unwrap());
+        // the file always keeps the secret; stdout leaves out only the keypair without include_secret
+        let mut differences: Vec<String> = file
+            .iter()
+            .filter(|(key, value)| {
+                let hidden = !include_secret && key.as_str() == "keypair";
+                stdout.get(key.as_str()) != (!hidden).then_some(*value)
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        differences.extend(
+            stdout
+                .keys()
+                .filter(|key| !file.contains_key(*key))
+                .cloned(),
+        );
+        differences
+    }
+
+    #[test]
+    fn output_file_matches_stdout_records() {
+        for &(seed, include_secret) in TEE_VECTORS {
+            assert_eq!(
+                tee_differences(seed, include_secret),
+                Vec::<String>::new(),
+                "stdout and --output records of seed {} (secret shown: {})",
+                seed,
+                include_secret
+            );
+        }
+    }
+
+    // Streams `keypairs` keypairs (seeds [1; 32], [2; 32], ...) into found.jsonl in a fresh temporary directory, rotated
+    // by the limits, and returns the addresses read back from each numbered file
+    fn rotate_files(
+        dir: &Path,
+        max_size: Option<u64>,
+        max_count: Option<u64>,
+        keypairs: u8,
+    ) -> io::Result<Vec<Vec<String>>> {
+        let base = dir.join("found.jsonl");
+        let mut rotation = RotatingJsonl::open(&base, max_size, max_count)?;
+        for seed in 1..=keypairs {
+            if seed == keypairs / 2 + 1 {
+                rotation = RotatingJsonl::open(&base, max_size, max_count)?;
+            }
+            let found = found_keypair("", seed, seed as u64, 0.0);
+            append_jsonl(&rotation.next_path(&found), [&found])?;
+        }
+        let mut files = Vec::new();
+        while rotated_path(&base, files.len() as u32).exists() {
+            files.push(stored_pubkeys(&rotated_path(&base, files.len() as u32))?);
+        }
+        Ok(files)
+    }
+
+    #[test]
+    fn output_rotates_by_size_and_count() {
+        for (i, &((max_size, max_count), keypairs, expected)) in ROTATE_VECTORS.iter().enumerate() {
+            let dir = temp_path(&format!("rotate-{}", i));
+            let files = std::fs::create_dir_all(&dir)
+                .and_then(|()| rotate_files(&dir, max_size, max_count, keypairs));
+            let _ = std::fs::remove_dir_all(&dir);
+            let files = files.unwrap();
+            let addresses: Vec<String> = (1..=keypairs)
+                .map(|seed| Keypair::new_from_array([seed; 32]).pubkey().to_string())
+                .collect();
+            assert_eq!(
+                files.iter().map(Vec::len).collect::<Vec<_>>(),
+                expected,
+                "rotating {} keypairs by size {:?} and count {:?}",
+                keypairs,
+                max_size,
+                max_count
+            );
+            assert_eq!(files.concat(), addresses);
+        }
+    }
+}
@@ -0,0 +1,384 @@
+use serde_json::json;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+
+// A rate this far below the baseline counts as degraded: 0.30 means more than 30% slower than the search started.
+// Scheduling noise and other programs move the rate by a few percent; a third of the throughput going away for
+// several samples in a row is what a CPU clocking down under heat looks like.
+pub const THROTTLE_DROP: f64 = 0.30;
+
+// Consecutive degraded samples needed before warning, so a short burst of other work doesn't trigger it
+pub const THROTTLE_SAMPLES: usize = 3;
+
+// Samples averaged into the baseline rate. The very first sample is left out because it includes thread startup.
+pub const BASELINE_SAMPLES: usize = 3;
+
+// Rate over one sampling interval, and whether this sample is the one that found the rate degraded
+pub struct RateSample {
+    pub rate: f64,
+    pub throttled: bool,
+}
+
+// Turns periodic attempt totals into rates and watches for a sustained drop below the rate measured early on
+pub struct RateMonitor {
+    last_attempts: u64,
+    last_time: Instant,
+    samples: usize,
+    baseline_sum: f64,
+    baseline: Option<f64>,
+    degraded_run: usize,
+    warned: bool,
+}
+
+impl RateMonitor {
+    pub fn new(start: Instant) -> Self {
+        RateMonitor {
+            last_attempts: 0,
+            last_time: start,
+            samples: 0,
+            baseline_sum: 0.0,
+            baseline: None,
+            degraded_run: 0,
+            warned: false,
+        }
+    }
+
+    // Records the attempt total at `now`. throttled is true at most once per monitor: on the sample that completes
+    // THROTTLE_SAMPLES degraded ones in a row.
+    pub fn sample(&mut self, attempts: u64, now: Instant) -> RateSample {
+        let secs = now.duration_since(self.last_time).as_secs_f64();
+        let rate = if secs > 0.0 {
+            attempts.saturating_sub(self.last_attempts) as f64 / secs
+        } else {
+            0.0
+        };
+        self.last_attempts = attempts;
+        self.last_time = now;
+        self.samples += 1;
+
+        let mut throttled = false;
+        match self.baseline {
+            None if self.samples > 1 => {
+                self.baseline_sum += rate;
+                if self.samples > BASELINE_SAMPLES {
+                    self.baseline = Some(self.baseline_sum / BASELINE_SAMPLES as f64);
+                }
+            }
+            None => {}
+            Some(baseline) => {
+                if rate < baseline * (1.0 - THROTTLE_DROP) {
+                    self.degraded_run += 1;
+                } else {
+                    self.degraded_run = 0;
+                }
+                if self.degraded_run >= THROTTLE_SAMPLES && !self.warned {
+                    self.warned = true;
+                    throttled = true;
+                }
+            }
+        }
+        RateSample { rate, throttled }
+    }
+
+    // Starts the next interval at `now` without taking a sample, e.g. after a pause, so time nothing was searched in
+    // doesn't show up as a drop in the rate. A run of degraded samples is broken off.
+    pub fn restart(&mut self, attempts: u64, now: Instant) {
+        self.last_attempts = attempts;
+        self.last_time = now;
+        self.degraded_run = 0;
+    }
+
+    // Average rate of the baseline samples, once there are enough of them
+    pub fn baseline(&self) -> Option<f64> {
+        self.baseline
+    }
+}
+
+// Prefix of every exported metric name
+pub const METRICS_PREFIX: &str = "solana_vanity";
+
+// The attempt total, the rate over the last report interval and the time since the search started in the
+// Prometheus text exposition format, ready for a node_exporter textfile collector
+pub fn prometheus_metrics(attempts: u64, rate: f64, elapsed_secs: f64) -> String {
+    let metrics: [(&str, &str, &str, String); 3] = [
+        (
+            "attempts_total",
+            "counter",
+            "Candidate keypairs checked since the search started.",
+            attempts.to_string(),
+        ),
+        (
+            "rate",
+            "gauge",
+            "Candidate keypairs checked per second over the last report interval.",
+            rate.to_string(),
+        ),
+        (
+            "elapsed_seconds",
+            "gauge",
+            "Seconds since the search started.",
+            elapsed_secs.to_string(),
+        ),
+    ];
+    metrics
+        .iter()
+        .map(|(name, kind, help, value)| {
+            format!(
+                "# HELP {prefix}_{name} {help}\n# TYPE {prefix}_{name} {kind}\n{prefix}_{name} {value}\n",
+                prefix = METRICS_PREFIX
+            )
+        })
+        .collect()
+}
+
+// Replaces the metrics file through a temporary file next to it, so a collector never reads a half written one
+pub fn write_metrics_file(path: &Path, metrics: &str) -> io::Result<()> {
+    let mut temp = OsString::from(path.as_os_str());
+    temp.push(".tmp");
+    fs::write(&temp, metrics)?;
+    fs::rename(&temp, path)
+}
+
+// Seconds a match is expected to take at this rate. Every candidate is an independent draw, so this doesn't shrink
+// as the search goes on. None while nothing was searched yet or when no match is possible.
+pub fn eta_secs(expected_attempts: f64, rate: f64) -> Option<f64> {
+    let eta = expected_attempts / rate;
+    (rate > 0.0 && eta.is_finite()).then_some(eta)
+}
+
+// One --progress-json line: the attempt total, the rate over the last report interval, the seconds since the search
+// started and the expected seconds to a match (null when unknown), as a single JSON object without a newline
+pub fn progress_json(tried: u64, rate: f64, elapsed_secs: f64, eta: Option<f64>) -> String {
+    json!({
+        "tried": tried,
+        "rate": rate,
+        "elapsed": elapsed_secs,
+        "eta": eta,
+    })
+    .to_string()
+}
+
+// Why a search run ended, as its run summary reports it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExitReason {
+    // every pattern got an address
+    Found,
+    // the closest miss was accepted at the --timeout prompt
+    AcceptedPartial,
+    // a --best-effort run used up its attempts
+    AttemptLimit,
+    Timeout,
+    Interrupted,
+    // the coordinator or a job directory ended the search, or a batch ended without every pattern
+    Stopped,
+    // a worker's generator failed
+    Failed,
+}
+
+impl ExitReason {
+    // Name in the run summary, e.g. "accepted_partial"
+    pub fn name(self) -> &'static str {
+        match self {
+            ExitReason::Found => "found",
+            ExitReason::AcceptedPartial => "accepted_partial",
+            ExitReason::AttemptLimit => "attempt_limit",
+            ExitReason::Timeout => "timeout",
+            ExitReason::Interrupted => "interrupted",
+            ExitReason::Stopped => "stopped",
+            ExitReason::Failed => "failed",
+        }
+    }
+}
+
+// The record --summary writes once a run ends, however it ended: the attempt total, the seconds it took, the average
+// rate over them (0 when no time passed), the patterns that got an address and those that didn't, and the exit
+// reason, as a single JSON object without a newline
+pub fn run_summary(
+    attempts: u64,
+    elapsed_secs: f64,
+    found: &[String],
+    unfound: &[String],
+    reason: ExitReason,
+) -> String {
+    let rate = if elapsed_secs > 0.0 {
+        attempts as f64 / elapsed_secs
+    } else {
+        0.0
+    };
+    json!({
+        "attempts": attempts,
+        "elapsed": elapsed_secs,
+        "rate": rate,
+        "found": found,
+        "unfound": unfound,
+        "exit_reason": reason.name(),
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Parses Prometheus text exposition (without labels, which the metrics don't use) and returns each sample's metric
+    // name and value, failing on anything a scraper would reject: malformed names or values, a sample or a second TYPE
+    // after the metric's first sample, an unknown type, a missing final newline
+    fn parse_exposition(text: &str) -> Result<Vec<(String, f64)>, String> {
+        let valid_name = |name: &str| {
+            name.chars().enumerate().all(|(i, c)| {
+                c.is_ascii_alphabetic() || c == '_' || c == ':' || (i > 0 && c.is_ascii_digit())
+            }) && !name.is_empty()
+        };
+        if !text.ends_with('\n') {
+            return Err("missing final newline".to_string());
+        }
+
+        let mut typed: Vec<&str> = Vec::new();
+        let mut sampled: Vec<&str> = Vec::new();
+        let mut samples = Vec::new();
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut parts = comment.splitn(3, ' ');
+                let (keyword, name, rest) = (parts.next(), parts.next(), parts.next());
+                match (keyword, name) {
+                    (Some("HELP"), Some(name)) if valid_name(name) => {}
+                    (Some("TYPE"), Some(name)) if valid_name(name) => {
+                        if !matches!(
+                            rest,
+                            Some("counter" | "gauge" | "histogram" | "summary" | "untyped")
+                        ) {
+                            return Err(format!("unknown type in {:?}", line));
+                        }
+                        if typed.contains(&name) || sampled.contains(&name) {
+                            return Err(format!("TYPE after samples or repeated: {:?}", line));
+                        }
+                        typed.push(name);
+                    }
+                    _ => return Err(format!("malformed comment {:?}", line)),
+                }
+                continue;
+            }
+            let (name, value) = line
+                .split_once(' ')
+                .ok_or_else(|| format!("malformed sample {:?}", line))?;
+            if !valid_name(name) {
+                return Err(format!("invalid metric name in {:?}", line));
+            }
+            let value = match value {
+                "+Inf" => f64::INFINITY,
+                "-Inf" => f64::NEG_INFINITY,
+                "NaN" => f64::NAN,
+                _ => value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|v| v.is_finite())
+                    .ok_or_else(|| format!("invalid value in {:?}", line))?,
+            };
+            sampled.push(name);
+            samples.push((name.to_string(), value));
+        }
+        Ok(samples)
+    }
+
+    // Attempt totals, rates and elapsed seconds exported as metrics; each must come out as valid Prometheus text that
+    // reads back to the same three values
+    const METRICS_VECTORS: &[(u64, f64, f64)] = &[
+        (0, 0.0, 0.0),
+        (123456, 7012.5, 5.004),
+        (u64::MAX, 1.5e9, 86400.25),
+    ];
+
+    #[test]
+    fn metrics_are_valid_prometheus_text() {
+        for &(attempts, rate, elapsed) in METRICS_VECTORS {
+            let text = prometheus_metrics(attempts, rate, elapsed);
+            let expected = [
+                (
+                    format!("{}_attempts_total", METRICS_PREFIX),
+                    attempts as f64,
+                ),
+                (format!("{}_rate", METRICS_PREFIX), rate),
+                (format!("{}_elapsed_seconds", METRICS_PREFIX), elapsed),
+            ];
+            let samples = parse_exposition(&text).unwrap_or_else(|e| {
+                panic!(
+                    "metrics for {} attempts are not valid Prometheus text ({}): {:?}",
+                    attempts, e, text
+                )
+            });
+            assert_eq!(samples, expected, "metrics for {} attempts", attempts);
+        }
+    }
+
+    // Patterns a run found, or didn't
+    type Patterns = &'static [&'static str];
+
+    // Attempts, elapsed seconds, found and unfound patterns and exit reason of a run, with the rate its summary must
+    // report. The summary has to be one line of JSON with exactly these six fields.
+    const RUN_SUMMARY_VECTORS: &[(u64, f64, Patterns, Patterns, ExitReason, f64)] = &[
+        // a successful run
+        (1_200_000, 2.5, &["SoL"], &[], ExitReason::Found, 480_000.0),
+        (5000, 4.0, &["ab"], &["xyz"], ExitReason::Timeout, 1250.0),
+        // nothing searched yet, so no rate
+        (0, 0.0, &[], &["SoL"], ExitReason::Interrupted, 0.0),
+    ];
+
+    // Attempt totals, rates, elapsed seconds and expected attempts of a --progress-json line, with the ETA it must carry.
+    // Each line must parse as a JSON object holding exactly tried, rate, elapsed and eta.
+    const PROGRESS_JSON_VECTORS: &[(u64, f64, f64, f64, Option<f64>)] = &[
+        (0, 0.0, 0.0, 1000.0, None),
+        (123456, 7012.5, 5.004, 14025.0, Some(2.0)),
+        (u64::MAX, 1.5e9, 86400.25, f64::INFINITY, None),
+        (10, 4.0, 2.5, f64::NAN, None),
+    ];
+
+    #[test]
+    fn run_summary_is_one_line_of_json() {
+        let to_strings =
+            |patterns: &[&str]| -> Vec<String> { patterns.iter().map(|p| p.to_string()).collect() };
+        for &(attempts, elapsed, found, unfound, reason, rate) in RUN_SUMMARY_VECTORS {
+            let line = run_summary(
+                attempts,
+                elapsed,
+                &to_strings(found),
+                &to_strings(unfound),
+                reason,
+            );
+            assert!(!line.contains('\n'), "run summary {:?}", line);
+            let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(
+                value,
+                json!({
+                    "attempts": attempts,
+                    "elapsed": elapsed,
+                    "rate": rate,
+                    "found": found,
+                    "unfound": unfound,
+                    "exit_reason": reason.name(),
+                }),
+                "run summary for {} attempts in {} seconds",
+                attempts,
+                elapsed
+            );
+        }
+    }
+
+    #[test]
+    fn progress_json_carries_the_eta() {
+        for &(tried, rate, elapsed, expected_attempts, eta) in PROGRESS_JSON_VECTORS {
+            let line = progress_json(tried, rate, elapsed, eta_secs(expected_attempts, rate));
+            assert!(!line.contains('\n'), "progress JSON {:?}", line);
+            let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(
+                value,
+                json!({ "tried": tried, "rate": rate, "elapsed": elapsed, "eta": eta }),
+                "progress JSON for {} attempts at {} per second",
+                tried,
+                rate
+            );
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+// How long fetching a pattern list may take in total before the run gives up
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Largest pattern list accepted; a list of every pattern anyone would search for is far smaller
+pub const PATTERN_LIST_LIMIT: u64 = 1024 * 1024;
+
+// Whether the URL may serve a pattern list: https anywhere, plain http only from this machine, where nobody on the
+// way can swap the patterns
+pub fn allowed_url(url: &str) -> bool {
+    let Some(rest) = url.strip_prefix("http://") else {
+        return url.starts_with("https://");
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+// Downloads a pattern list, failing on anything but a successful response. Nothing is cached, so every run sees
+// the list as it is now.
+pub fn fetch_pattern_list(url: &str) -> Result<String, String> {
+    if !allowed_url(url) {
+        return Err(format!(
+            "Pattern list URL {} must use https (plain http only for localhost)",
+            url
+        ));
+    }
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(FETCH_TIMEOUT))
+        .build()
+        .into();
+    let mut response = agent
+        .get(url)
+        .call()
+        .map_err(|e| format!("Cannot fetch pattern list from {}: {}", url, e))?;
+    response
+        .body_mut()
+        .with_config()
+        .limit(PATTERN_LIST_LIMIT)
+        .read_to_string()
+        .map_err(|e| format!("Cannot read pattern list from {}: {}", url, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --patterns-url values and whether a pattern list may be fetched from them
+    const PATTERNS_URL_VECTORS: &[(&str, bool)] = &[
+        ("https://example.com/patterns.txt", true),
+        ("http://localhost:8080/patterns.txt", true),
+        ("http://127.0.0.1/patterns.txt", true),
+        ("http://[::1]:8080/patterns.txt", true),
+        ("http://example.com/patterns.txt", false),
+        // the host is example.com, whatever the user part says
+        ("http://localhost@example.com/patterns.txt", false),
+        ("http://localhost.example.com/patterns.txt", false),
+        ("ftp://example.com/patterns.txt", false),
+        ("patterns.txt", false),
+    ];
+
+    #[test]
+    fn plain_http_is_allowed_from_this_machine_only() {
+        for &(url, expected) in PATTERNS_URL_VECTORS {
+            assert_eq!(allowed_url(url), expected, "patterns URL '{}'", url);
+        }
+    }
+}
@@ -0,0 +1,866 @@
+use crate::source::{KeypairSource, OsRngSource};
+use crate::throttle::CpuThrottle;
+use bip39::Mnemonic;
+use rayon::prelude::*;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+// Decides whether a candidate is a hit; pubkey_bytes are the raw 32 public key bytes of the candidate
+pub trait Matcher {
+    fn is_match(&self, pubkey_bytes: &[u8]) -> bool;
+}
+
+// The matching keypair (with its seed phrase when the source has one) and how many candidates were tried in total
+pub struct SearchOutcome {
+    pub found: Option<(Keypair, Option<Mnemonic>)>,
+    pub attempts: u64,
+}
+
+// How many candidates a worker tries before publishing its count to the shared total
+const FLUSH_INTERVAL: u64 = 1024;
+
+// How many candidates a worker tries back to back before looking at the shared stop flag again. Small enough that a
+// stopped search ends within a few candidates, large enough that the flag is read a fraction as often. It divides
+// FLUSH_INTERVAL, so a batch never straddles a chunk boundary.
+pub const STOP_CHECK_BATCH: u64 = 8;
+
+// Shared handle to a running search, so other threads can stop it or read its progress
+#[derive(Default)]
+pub struct SearchControl {
+    stop: AtomicBool,
+    attempts: AtomicU64,
+    attempt_limit: Option<u64>,
+    reserved: AtomicU64,
+    // share of a core each worker may use, in percent
+    cpu_limit: Option<u8>,
+    // while set, workers sleep between chunks of candidates instead of searching
+    pause_flag: Option<&'static AtomicBool>,
+    // candidates between checks of stop, when not STOP_CHECK_BATCH
+    check_batch: Option<u64>,
+    // a file whose appearance stops the search, e.g. the done marker of a job directory
+    stop_file: Option<PathBuf>,
+    // why a worker gave up, if one did
+    failure: Mutex<Option<String>>,
+    // candidates tried per worker, by rayon thread index; only kept once with_worker_stats asks for it
+    worker_attempts: Option<Mutex<BTreeMap<usize, u64>>>,
+}
+
+impl SearchControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Stops the search after exactly this many candidates in total across all workers
+    pub fn with_attempt_limit(limit: u64) -> Self {
+        SearchControl {
+            attempt_limit: Some(limit),
+            ..Self::default()
+        }
+    }
+
+    // Makes every worker pause between chunks of candidates so it only uses about this percentage of a core
+    pub fn with_cpu_limit(mut self, percent: u8) -> Self {
+        self.cpu_limit = Some(percent);
+        self
+    }
+
+    // Makes every worker hold still between chunks of candidates while the flag is set, e.g. by a signal handler,
+    // keeping its progress so the search carries on where it was once the flag is cleared
+    pub fn with_pause_flag(mut self, flag: &'static AtomicBool) -> Self {
+        self.pause_flag = Some(flag);
+        self
+    }
+
+    // Checks the stop flag every `batch` candidates instead of every STOP_CHECK_BATCH, e.g. 1 to compare against the
+    // unbatched loop in a benchmark. Clamped to 1..=1024, the chunk size workers publish their counts in.
+    pub fn with_check_batch(mut self, batch: u64) -> Self {
+        self.check_batch = Some(batch.clamp(1, FLUSH_INTERVAL));
+        self
+    }
+
+    // Stops the search once the file exists, e.g. when another process sharing a job directory has finished the job.
+    // Workers look for it each time they take a chunk of FLUSH_INTERVAL candidates, so the file system is asked
+    // once per chunk rather than for every candidate.
+    pub fn with_stop_file(mut self, path: PathBuf) -> Self {
+        self.stop_file = Some(path);
+        self
+    }
+
+    // Whether the stop file has appeared; false without one
+    fn stop_file_exists(&self) -> bool {
+        self.stop_file.as_ref().is_some_and(|path| path.exists())
+    }
+
+    // Keeps a count per worker thread next to the total, for spotting a worker that lags behind the others. It is
+    // updated where workers publish their counts anyway, every FLUSH_INTERVAL candidates.
+    pub fn with_worker_stats(mut self) -> Self {
+        self.worker_attempts = Some(Mutex::default());
+        self
+    }
+
+    // (rayon thread index, candidates tried) for every worker that published a count so far, by index; empty
+    // unless with_worker_stats was used
+    pub fn worker_attempts(&self) -> Vec<(usize, u64)> {
+        self.worker_attempts
+            .as_ref()
+            .map_or_else(Vec::new, |stats| {
+                stats
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(&thread, &attempts)| (thread, attempts))
+                    .collect()
+            })
+    }
+
+    // Adds a worker's candidates to the total (and its own count) and returns the new total
+    fn publish(&self, thread: usize, count: u64) -> u64 {
+        if let Some(stats) = &self.worker_attempts {
+            *stats.lock().unwrap().entry(thread).or_default() += count;
+        }
+        self.attempts.fetch_add(count, Ordering::Relaxed) + count
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause_flag
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    // Hands a worker up to `wanted` more candidates to try, fewer once the attempt limit is close.
+    // Returns 0 (and stops the search) when the limit is used up.
+    fn reserve(&self, wanted: u64) -> u64 {
+        let Some(limit) = self.attempt_limit else {
+            return wanted;
+        };
+        let previous = self
+            .reserved
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |reserved| {
+                Some(reserved + wanted.min(limit.saturating_sub(reserved)))
+            })
+            .unwrap();
+        let granted = wanted.min(limit.saturating_sub(previous));
+        if granted == 0 {
+            self.stop();
+        }
+        granted
+    }
+
+    // Asks the workers to finish; they notice within one batch of candidates
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    // Stops the search because a worker can't go on; only the first reason is kept
+    fn fail(&self, reason: String) {
+        self.failure.lock().unwrap().get_or_insert(reason);
+        self.stop();
+    }
+
+    // Why the search was aborted, if a worker failed (e.g. the OS random number generator stayed unavailable).
+    // A search that returns without a match may have been aborted rather than stopped, so callers check this.
+    pub fn failure(&self) -> Option<String> {
+        self.failure.lock().unwrap().clone()
+    }
+
+    // Live attempt total; while running it can lag behind by up to FLUSH_INTERVAL per worker
+    pub fn attempts(&self) -> u64 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+}
+
+// Runs one worker per thread on the current rayon pool, handing every match to on_match along with the source
+// that produced it and the attempts made so far, until on_match returns true, the control is stopped or its
+// attempt limit is used up. A source that fails for good aborts the search and leaves the reason in the control.
+// Each worker gets its own source from make_source, called with the worker index.
+// Returns the total number of candidates tried. It only returns once every worker has left its loop and added its
+// last partial count, so the total is exact rather than the live, lagging value of SearchControl::attempts.
+// on_match may be called from several workers at once.
+pub fn search_each<M, S, F, C>(
+    matcher: &M,
+    threads: usize,
+    make_source: F,
+    control: &SearchControl,
+    on_match: C,
+) -> u64
+where
+    M: Matcher + Sync,
+    S: KeypairSource,
+    F: Fn(usize) -> S + Sync,
+    C: Fn(Keypair, &S, u64) -> bool + Sync,
+{
+    let stop = &control.stop;
+    let check_batch = control.check_batch.unwrap_or(STOP_CHECK_BATCH);
+    (0..threads).into_par_iter().for_each(|worker| {
+        let mut source = make_source(worker);
+        // the index rayon knows the thread by, or the worker number when run outside a pool
+        let thread = rayon::current_thread_index().unwrap_or(worker);
+        // count locally and publish in chunks to keep the hot loop free of shared writes
+        let mut local_attempts = 0u64;
+        // candidates this worker may still try before asking the control for more
+        let mut budget = 0u64;
+        let mut throttle = control
+            .cpu_limit
+            .map(|percent| CpuThrottle::new(percent, Instant::now()));
+        'search: while !stop.load(Ordering::Relaxed) {
+            if budget == 0 {
+                if control.is_paused() {
+                    wait_while_paused(control);
+                    // the paused time wasn't work, so it must not count against the CPU limit either way
+                    if let Some(throttle) = &mut throttle {
+                        *throttle =
+                            CpuThrottle::new(control.cpu_limit.unwrap_or(100), Instant::now());
+                    }
+                }
+                if let Some(throttle) = &mut throttle {
+                    pause(throttle, stop);
+                }
+                if control.stop_file_exists() {
+                    control.stop();
+                    break;
+                }
+                budget = control.reserve(FLUSH_INTERVAL);
+                if budget == 0 {
+                    break;
+                }
+            }
+            // a whole batch without looking at the stop flag; only this worker's own match ends it early
+            let batch = budget.min(check_batch);
+            budget -= batch;
+            for _ in 0..batch {
+                let keypair = match source.try_next() {
+                    Ok(keypair) => keypair,
+                    Err(e) => {
+                        control.fail(e);
+                        break 'search;
+                    }
+                };
+                local_attempts += 1;
+
+                if matcher.is_match(keypair.pubkey().as_ref()) {
+                    let total = control.publish(thread, local_attempts);
+                    local_attempts = 0;
+                    if on_match(keypair, &source, total) {
+                        stop.store(true, Ordering::Relaxed);
+                        break 'search;
+                    }
+                } else if local_attempts == FLUSH_INTERVAL {
+                    control.publish(thread, local_attempts);
+                    local_attempts = 0;
+                }
+            }
+        }
+        control.publish(thread, local_attempts);
+    });
+
+    // for_each has joined every worker, so all their final counts are in
+    control.attempts.load(Ordering::Relaxed)
+}
+
+// Sleeps as long as the throttle asks, in short naps so a stopped search isn't held up
+fn pause(throttle: &mut CpuThrottle, stop: &AtomicBool) {
+    let until = Instant::now() + throttle.pause(Instant::now());
+    loop {
+        let now = Instant::now();
+        if now >= until || stop.load(Ordering::Relaxed) {
+            break;
+        }
+        std::thread::sleep((until - now).min(Duration::from_millis(10)));
+    }
+    throttle.resume(Instant::now());
+}
+
+// Sleeps until the control is no longer paused or is stopped, in short naps so either is noticed quickly
+fn wait_while_paused(control: &SearchControl) {
+    while control.is_paused() && !control.stop.load(Ordering::Relaxed) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+// Runs until the first generated keypair satisfies the matcher or the control is stopped
+pub fn search<M, S, F>(
+    matcher: &M,
+    threads: usize,
+    make_source: F,
+    control: &SearchControl,
+) -> SearchOutcome
+where
+    M: Matcher + Sync,
+    S: KeypairSource,
+    F: Fn(usize) -> S + Sync,
+{
+    // several workers can hit at the same time; only the first one is kept
+    let found = Mutex::new(None);
+    let attempts = search_each(
+        matcher,
+        threads,
+        make_source,
+        control,
+        |keypair, source, _| {
+            found
+                .lock()
+                .unwrap()
+                .get_or_insert_with(|| (keypair, source.last_mnemonic().cloned()));
+            true
+        },
+    );
+
+    SearchOutcome {
+        found: found.into_inner().unwrap(),
+        attempts,
+    }
+}
+
+// Convenience wrapper for the common case of plain random keypairs
+pub fn search_keypair<M: Matcher + Sync>(matcher: &M, threads: usize) -> Option<Keypair> {
+    search(matcher, threads, |_| OsRngSource, &SearchControl::new())
+        .found
+        .map(|(keypair, _)| keypair)
+}
+
+// Searches on a rayon pool of its own, started once and reused by every search, for embedders that run many searches:
+// no search pays for starting threads, and none needs (or takes over) the global pool. Dropping it stops the pool
+// and waits for its threads to exit.
+pub struct VanitySearcher {
+    pool: Option<rayon::ThreadPool>,
+    threads: usize,
+    // one message per pool thread as it exits
+    exited: Mutex<Receiver<()>>,
+}
+
+impl VanitySearcher {
+    // Starts a pool of the requested size, or of the first smaller count that can be started, like the CLI does
+    pub fn new(threads: usize) -> Result<Self, String> {
+        let mut started = None;
+        let threads = build_with_fallback(threads, |count| {
+            // a channel per attempt, so threads of a pool that failed to start can't count as exits of this one
+            let (exit_tx, exited) = mpsc::channel();
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(count)
+                .exit_handler(move |_| {
+                    let _ = exit_tx.send(());
+                })
+                .build()
+                .map(|pool| started = Some((pool, exited)))
+        })
+        .map_err(|e| e.to_string())?;
+        let (pool, exited) = started.expect("a pool was started");
+        Ok(VanitySearcher {
+            pool: Some(pool),
+            threads,
+            exited: Mutex::new(exited),
+        })
+    }
+
+    // Worker count of the pool, which can be lower than requested
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    // Searches plain random keypairs until the matcher accepts one, or returns why the generator gave up
+    pub fn find<M: Matcher + Sync>(&self, matcher: &M) -> Result<Keypair, String> {
+        let control = SearchControl::new();
+        match self.search(matcher, |_| OsRngSource, &control).found {
+            Some((keypair, _)) => Ok(keypair),
+            None => Err(control
+                .failure()
+                .unwrap_or_else(|| "search stopped without a match".to_string())),
+        }
+    }
+
+    // search, run on this searcher's pool
+    pub fn search<M, S, F>(
+        &self,
+        matcher: &M,
+        make_source: F,
+        control: &SearchControl,
+    ) -> SearchOutcome
+    where
+        M: Matcher + Sync,
+        S: KeypairSource,
+        F: Fn(usize) -> S + Sync,
+    {
+        let pool = self.pool.as_ref().expect("the pool lives until drop");
+        let (threads, make_source) = (self.threads, &make_source);
+        pool.install(|| search(matcher, threads, make_source, control))
+    }
+}
+
+impl Drop for VanitySearcher {
+    fn drop(&mut self) {
+        // dropping a rayon pool only asks its threads to finish; wait for them so none outlives the searcher
+        drop(self.pool.take());
+        let exited = self.exited.get_mut().unwrap();
+        for _ in 0..self.threads {
+            if exited.recv().is_err() {
+                break;
+            }
+        }
+    }
+}
+
+// Worker counts to try when a pool of the requested size can't be started, e.g. under a process limit: the request
+// itself, then halving down to a single thread
+pub fn fallback_thread_counts(requested: usize) -> Vec<usize> {
+    let mut counts = vec![requested.max(1)];
+    while let Some(&last) = counts.last().filter(|&&last| last > 1) {
+        counts.push(last / 2);
+    }
+    counts
+}
+
+// Builds a pool with the first of fallback_thread_counts that build manages and returns that count, or the error of
+// the single thread attempt if even that failed
+pub fn build_with_fallback<E>(
+    requested: usize,
+    mut build: impl FnMut(usize) -> Result<(), E>,
+) -> Result<usize, E> {
+    let mut last_error = None;
+    for threads in fallback_thread_counts(requested) {
+        match build(threads) {
+            Ok(()) => return Ok(threads),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.expect("at least one thread count is tried"))
+}
+
+// Starts a local pool of count threads, then stops it again and waits for its threads to exit, so a pool started
+// right after can use them; Ok when every thread started
+pub fn probe_pool(count: usize) -> Result<(), rayon::ThreadPoolBuildError> {
+    let (exit_tx, exited) = mpsc::channel();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(count)
+        .exit_handler(move |_| {
+            let _ = exit_tx.send(());
+        })
+        .build()?;
+    drop(pool);
+    for _ in 0..count {
+        if exited.recv().is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// Starts the global pool with the first of fallback_thread_counts that probe manages and returns that count. Rayon
+// sets the global pool up only once, even when that fails, so the counts are tried with probe (probe_pool outside of
+// tests) and the global pool is built a single time, with the count that worked.
+pub fn start_global_pool<E: std::fmt::Display>(
+    requested: usize,
+    probe: impl FnMut(usize) -> Result<(), E>,
+) -> Result<usize, String> {
+    let threads = build_with_fallback(requested, probe).map_err(|e| e.to_string())?;
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build_global()
+        .map_err(|e| e.to_string())?;
+    Ok(threads)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::{MatchType, PatternMatcher};
+    use crate::output::DistinctAddresses;
+    use crate::source::SequenceSource;
+    use crate::vectors::KEYPAIR_VECTORS;
+
+    // Worker counts and how many keypairs each worker's source yields before its generator fails for good. The search
+    // must stop with the failure recorded instead of panicking.
+    const ABORT_VECTORS: &[(usize, u64)] = &[(1, 100), (3, 100)];
+
+    // Seeds whose address prefixes are searched one after another on a single VanitySearcher; each search must find the
+    // planted seed on the pool the earlier searches used
+    const SEARCHER_VECTORS: &[u8] = &[1, 2, 3];
+
+    // Seed bytes a source cycles through (seed [b; 32] for each b), how many distinct addresses to collect, and how many
+    // repeated addresses the collector must skip on the way
+    const DISTINCT_VECTORS: &[(&[u8], u64, u64)] =
+        &[(&[1, 1, 7], 2, 1), (&[7, 7, 7, 1], 2, 2), (&[1, 7], 2, 0)];
+
+    // Requested threads, the most a simulated process limit lets a pool start, and the count the pool falls back to;
+    // None when not even one thread can be started
+    const FALLBACK_VECTORS: &[(usize, usize, Option<usize>)] = &[
+        (8, 64, Some(8)),
+        (8, 8, Some(8)),
+        (8, 5, Some(4)),
+        (6, 2, Some(1)),
+        (16, 15, Some(8)),
+        (1, 1, Some(1)),
+        (4, 0, None),
+    ];
+
+    // Worker counts and attempt limits of searches that keep per-thread counts (or not): the counts must add up to the
+    // total exactly, and stay empty when they weren't asked for
+    const WORKER_STATS_VECTORS: &[(usize, u64, bool)] =
+        &[(1, 3000, true), (3, 5000, true), (2, 3000, false)];
+
+    // Worker counts and attempt limits of searches started paused: none may try a candidate until resumed, and each
+    // must then run to its limit
+    const PAUSE_VECTORS: &[(usize, u64)] = &[(1, 2500), (3, 5000)];
+
+    // Worker counts and attempt limits for searches whose reported attempt total must equal the number of candidates
+    // the workers actually generated. Without a limit the search stops at a planted match.
+    const SEARCH_VECTORS: &[(usize, Option<u64>)] = &[(1, None), (4, None), (3, Some(2500))];
+
+    // Stop check batch sizes and attempt limits of single worker searches, and the attempts they must report. Without a
+    // limit the planted match is the 152nd candidate, and the search has to end right there whatever the batch size;
+    // with one it has to end at the limit even when that falls inside a batch.
+    const BATCH_VECTORS: &[(u64, Option<u64>, u64)] = &[
+        (1, None, 152),
+        (STOP_CHECK_BATCH, None, 152),
+        (5, None, 152),
+        (1024, None, 152),
+        (STOP_CHECK_BATCH, Some(1001), 1001),
+    ];
+
+    // A search for a prefix no candidate has
+    fn unmatchable() -> PatternMatcher {
+        PatternMatcher::new(vec!["zzzzzz".to_string()], MatchType::Prefix, true, false)
+    }
+
+    // A source whose generator breaks down after a number of keypairs
+    struct FailingSource {
+        inner: SequenceSource,
+        remaining: u64,
+    }
+
+    impl KeypairSource for FailingSource {
+        fn next(&mut self) -> Keypair {
+            self.try_next().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        fn try_next(&mut self) -> Result<Keypair, String> {
+            if self.remaining == 0 {
+                return Err("simulated generator failure".to_string());
+            }
+            self.remaining -= 1;
+            Ok(self.inner.next())
+        }
+    }
+
+    #[test]
+    fn failing_generator_aborts_the_search() {
+        for &(threads, keys) in ABORT_VECTORS {
+            let control = SearchControl::new();
+            let outcome = search(
+                &unmatchable(),
+                threads,
+                |_| FailingSource {
+                    inner: SequenceSource::new(vec![[1; 32]]),
+                    remaining: keys,
+                },
+                &control,
+            );
+            assert!(
+                control.failure().is_some() && outcome.attempts <= threads as u64 * keys,
+                "generator failing after {} keypairs with {} worker(s): got failure {:?} after {} attempts",
+                keys,
+                threads,
+                control.failure(),
+                outcome.attempts
+            );
+        }
+    }
+
+    #[test]
+    fn searcher_pool_is_reused_across_searches() {
+        let searcher = VanitySearcher::new(2).unwrap();
+        for &seed in SEARCHER_VECTORS {
+            let address = Keypair::new_from_array([seed; 32]).pubkey().to_string();
+            let matcher = PatternMatcher::new(
+                vec![address[..4].to_string()],
+                MatchType::Prefix,
+                true,
+                false,
+            );
+            let outcome = searcher.search(
+                &matcher,
+                |_| SequenceSource::new(vec![[0; 32], [seed; 32]]),
+                &SearchControl::new(),
+            );
+            let found = outcome
+                .found
+                .map(|(keypair, _)| keypair.pubkey().to_string());
+            assert_eq!(found, Some(address), "searcher search for seed {}", seed);
+        }
+    }
+
+    // Accepts every candidate, so each keypair a source yields counts as a match
+    struct AnyAddress;
+
+    impl Matcher for AnyAddress {
+        fn is_match(&self, _pubkey_bytes: &[u8]) -> bool {
+            true
+        }
+    }
+
+    // Collects matches like --stream --count does until count distinct addresses were seen, and returns the addresses
+    // collected and the repeats skipped
+    fn collect_distinct(seed_bytes: &[u8], count: u64) -> (Vec<String>, u64) {
+        let seeds: Vec<[u8; 32]> = seed_bytes.iter().map(|&b| [b; 32]).collect();
+        let collected = Mutex::new((DistinctAddresses::default(), Vec::new()));
+        search_each(
+            &AnyAddress,
+            1,
+            |_| SequenceSource::new(seeds.clone()),
+            &SearchControl::new(),
+            |keypair, _, _| {
+                let mut collected = collected.lock().unwrap();
+                let (distinct, addresses) = &mut *collected;
+                if distinct.insert(&keypair) {
+                    addresses.push(keypair.pubkey().to_string());
+                }
+                addresses.len() as u64 >= count
+            },
+        );
+        let (distinct, addresses) = collected.into_inner().unwrap();
+        (addresses, distinct.duplicates())
+    }
+
+    #[test]
+    fn collecting_skips_repeated_addresses() {
+        for &(seed_bytes, count, expected_duplicates) in DISTINCT_VECTORS {
+            let (addresses, duplicates) = collect_distinct(seed_bytes, count);
+            let unique: std::collections::HashSet<&String> = addresses.iter().collect();
+            assert_eq!(
+                (addresses.len() as u64, unique.len(), duplicates),
+                (count, addresses.len(), expected_duplicates),
+                "collecting {} distinct from seeds {:?}",
+                count,
+                seed_bytes
+            );
+        }
+    }
+
+    #[test]
+    fn slow_consumer_blocks_the_workers() {
+        // every candidate matches, so without the bounded channel the workers would queue up thousands
+        let (buffer, threads) = (4, 3);
+        let (sender, receiver) = mpsc::sync_channel::<Keypair>(buffer);
+        let handed_over = AtomicU64::new(0);
+        let control = SearchControl::new();
+        let most_in_flight = std::thread::scope(|scope| {
+            let searching = scope.spawn(|| {
+                search_each(
+                    &AnyAddress,
+                    threads,
+                    |_| SequenceSource::new(vec![[1; 32], [2; 32]]),
+                    &control,
+                    |keypair, _, _| {
+                        // in flight from here: a worker blocked in send still holds its match
+                        handed_over.fetch_add(1, Ordering::SeqCst);
+                        sender.send(keypair).is_err()
+                    },
+                )
+            });
+            let mut received = 0;
+            let mut most_in_flight = 0;
+            for _ in 0..20 {
+                std::thread::sleep(Duration::from_millis(10));
+                receiver.recv().unwrap();
+                received += 1;
+                most_in_flight = most_in_flight.max(handed_over.load(Ordering::SeqCst) - received);
+            }
+            control.stop();
+            drop(receiver);
+            searching.join().unwrap();
+            most_in_flight
+        });
+        assert!(
+            most_in_flight <= (buffer + threads) as u64,
+            "{} matches in flight with a buffer of {} and {} worker(s)",
+            most_in_flight,
+            buffer,
+            threads
+        );
+    }
+
+    #[test]
+    fn pool_falls_back_to_fewer_threads() {
+        for &(requested, limit, expected) in FALLBACK_VECTORS {
+            let mut tried = Vec::new();
+            let started = build_with_fallback(requested, |threads| {
+                tried.push(threads);
+                if threads <= limit {
+                    Ok(())
+                } else {
+                    Err(threads)
+                }
+            })
+            .ok();
+            assert_eq!(
+                started, expected,
+                "{} threads with room for {}",
+                requested, limit
+            );
+            assert!(
+                fallback_thread_counts(requested).starts_with(&tried),
+                "{} threads with room for {}: tried {:?}",
+                requested,
+                limit,
+                tried
+            );
+        }
+    }
+
+    #[test]
+    fn per_thread_counts_add_up_to_the_total() {
+        for &(threads, attempt_limit, stats) in WORKER_STATS_VECTORS {
+            let control = SearchControl::with_attempt_limit(attempt_limit);
+            let control = if stats {
+                control.with_worker_stats()
+            } else {
+                control
+            };
+            let outcome = search(
+                &unmatchable(),
+                threads,
+                |_| SequenceSource::new(vec![[1; 32], [2; 32]]),
+                &control,
+            );
+            let per_thread = control.worker_attempts();
+            let summed: u64 = per_thread.iter().map(|&(_, attempts)| attempts).sum();
+            let consistent = if stats {
+                summed == outcome.attempts && !per_thread.is_empty()
+            } else {
+                per_thread.is_empty()
+            };
+            assert!(
+                consistent,
+                "per-thread counts of {} worker(s), limit {} (kept: {}): got {:?} for a total of {}",
+                threads, attempt_limit, stats, per_thread, outcome.attempts
+            );
+        }
+    }
+
+    // Pause flag of the PAUSE_VECTORS searches, which run one at a time
+    static PAUSE_VECTOR_FLAG: AtomicBool = AtomicBool::new(false);
+
+    // Starts a search that can't match while paused, and returns (attempts while paused, attempts after resuming)
+    fn paused_attempts(threads: usize, attempt_limit: u64) -> (u64, u64) {
+        let matcher = unmatchable();
+        PAUSE_VECTOR_FLAG.store(true, Ordering::Relaxed);
+        let control =
+            SearchControl::with_attempt_limit(attempt_limit).with_pause_flag(&PAUSE_VECTOR_FLAG);
+        std::thread::scope(|scope| {
+            let searching = scope.spawn(|| {
+                search(
+                    &matcher,
+                    threads,
+                    |_| SequenceSource::new(vec![[1; 32], [2; 32]]),
+                    &control,
+                )
+            });
+            // long enough for every worker to reach its first chunk boundary, where a paused one waits
+            std::thread::sleep(Duration::from_millis(200));
+            let while_paused = control.attempts();
+            PAUSE_VECTOR_FLAG.store(false, Ordering::Relaxed);
+            (while_paused, searching.join().unwrap().attempts)
+        })
+    }
+
+    #[test]
+    fn paused_search_tries_nothing_until_resumed() {
+        for &(threads, attempt_limit) in PAUSE_VECTORS {
+            assert_eq!(
+                paused_attempts(threads, attempt_limit),
+                (0, attempt_limit),
+                "search paused from the start with {} worker(s), limit {}",
+                threads,
+                attempt_limit
+            );
+        }
+    }
+
+    // Wraps a source and counts every candidate it hands out into a total shared by all workers
+    struct CountingSource<'a> {
+        inner: SequenceSource,
+        generated: &'a AtomicU64,
+    }
+
+    impl KeypairSource for CountingSource<'_> {
+        fn next(&mut self) -> Keypair {
+            self.generated.fetch_add(1, Ordering::Relaxed);
+            self.inner.next()
+        }
+    }
+
+    // Searches for the address of the last KEYPAIR_VECTORS seed, which every worker reaches after the same run of
+    // seeds that don't match, and returns (reported attempts, candidates generated)
+    fn search_attempts(
+        threads: usize,
+        attempt_limit: Option<u64>,
+        check_batch: Option<u64>,
+    ) -> (u64, u64) {
+        let (planted_seed, planted_address) = KEYPAIR_VECTORS[KEYPAIR_VECTORS.len() - 1];
+        let matcher = PatternMatcher::new(
+            vec![planted_address[..8].to_string()],
+            MatchType::Prefix,
+            true,
+            false,
+        );
+        let mut seeds: Vec<[u8; 32]> = (100..=250u8).map(|i| [i; 32]).collect();
+        if attempt_limit.is_none() {
+            seeds.push(planted_seed);
+        }
+        let control = match attempt_limit {
+            Some(limit) => SearchControl::with_attempt_limit(limit),
+            None => SearchControl::new(),
+        };
+        let control = match check_batch {
+            Some(batch) => control.with_check_batch(batch),
+            None => control,
+        };
+        let generated = AtomicU64::new(0);
+        let outcome = search(
+            &matcher,
+            threads,
+            |_| CountingSource {
+                inner: SequenceSource::new(seeds.clone()),
+                generated: &generated,
+            },
+            &control,
+        );
+        (outcome.attempts, generated.load(Ordering::Relaxed))
+    }
+
+    #[test]
+    fn attempt_total_counts_every_candidate() {
+        for &(threads, attempt_limit) in SEARCH_VECTORS {
+            let (attempts, generated) = search_attempts(threads, attempt_limit, None);
+            assert_eq!(
+                attempts, generated,
+                "attempt total with {} worker(s), limit {:?}",
+                threads, attempt_limit
+            );
+            if let Some(limit) = attempt_limit {
+                assert_eq!(attempts, limit, "attempt total with {} worker(s)", threads);
+            }
+        }
+    }
+
+    #[test]
+    fn search_stops_exactly_whatever_the_check_batch() {
+        for &(check_batch, attempt_limit, expected) in BATCH_VECTORS {
+            assert_eq!(
+                search_attempts(1, attempt_limit, Some(check_batch)),
+                (expected, expected),
+                "search checking for a stop every {} candidate(s), limit {:?}",
+                check_batch,
+                attempt_limit
+            );
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use crate::matcher::to_hex;
+use crate::search::Matcher;
+use clap::ValueEnum;
+
+// Another way of displaying a public key, e.g. the encoding a different ed25519 based system shows addresses in.
+// Implement this to search against an encoding the tool doesn't ship.
+pub trait DisplayEncoding: Send + Sync {
+    // Every character encode can produce
+    fn alphabet(&self) -> &str;
+
+    // The display string of the raw 32 public key bytes
+    fn encode(&self, pubkey_bytes: &[u8]) -> String;
+}
+
+// Lower case hex, two digits per byte
+pub struct Hex;
+
+impl DisplayEncoding for Hex {
+    fn alphabet(&self) -> &str {
+        "0123456789abcdef"
+    }
+
+    fn encode(&self, pubkey_bytes: &[u8]) -> String {
+        to_hex(pubkey_bytes)
+    }
+}
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// RFC 4648 base32 without padding, the upper case alphabet Stellar and Algorand addresses are written in. Those
+// chains add a version byte and checksum around the key; this is the bare key.
+pub struct Base32;
+
+impl DisplayEncoding for Base32 {
+    fn alphabet(&self) -> &str {
+        std::str::from_utf8(BASE32_ALPHABET).unwrap()
+    }
+
+    fn encode(&self, pubkey_bytes: &[u8]) -> String {
+        let mut out = String::with_capacity(pubkey_bytes.len().div_ceil(5) * 8);
+        let mut buffer = 0u16;
+        let mut bits = 0;
+        for &byte in pubkey_bytes {
+            buffer = (buffer << 8) | byte as u16;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                out.push(BASE32_ALPHABET[(buffer >> bits) as usize & 31] as char);
+            }
+        }
+        if bits > 0 {
+            out.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 31] as char);
+        }
+        out
+    }
+}
+
+// Encodings selectable from the command line
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SecondaryEncoding {
+    Hex,
+    Base32,
+}
+
+impl SecondaryEncoding {
+    pub fn encoding(self) -> Box<dyn DisplayEncoding> {
+        match self {
+            SecondaryEncoding::Hex => Box::new(Hex),
+            SecondaryEncoding::Base32 => Box::new(Base32),
+        }
+    }
+}
+
+// Looks for a pattern anywhere in the secondary display string of a public key
+pub struct SecondaryMatcher {
+    encoding: Box<dyn DisplayEncoding>,
+    pattern: String,
+    case_sensitive: bool,
+}
+
+impl SecondaryMatcher {
+    // Fails if the pattern has a character the encoding never produces, ignoring case unless case_sensitive
+    pub fn new(
+        encoding: Box<dyn DisplayEncoding>,
+        pattern: &str,
+        case_sensitive: bool,
+    ) -> Result<Self, String> {
+        if pattern.is_empty() {
+            return Err("Secondary pattern cannot be empty".to_string());
+        }
+        let alphabet = encoding.alphabet();
+        for ch in pattern.chars() {
+            let produced = if case_sensitive {
+                alphabet.contains(ch)
+            } else {
+                alphabet.contains(ch.to_ascii_lowercase())
+                    || alphabet.contains(ch.to_ascii_uppercase())
+            };
+            if !produced {
+                return Err(format!(
+                    "Invalid character '{}' in secondary pattern. The encoding only produces: {}",
+                    ch, alphabet
+                ));
+            }
+        }
+        Ok(SecondaryMatcher {
+            encoding,
+            pattern: pattern.to_string(),
+            case_sensitive,
+        })
+    }
+
+    // The secondary display string of a public key
+    pub fn display(&self, pubkey_bytes: &[u8]) -> String {
+        self.encoding.encode(pubkey_bytes)
+    }
+
+    pub fn matches(&self, pubkey_bytes: &[u8]) -> bool {
+        let display = self.display(pubkey_bytes);
+        let pattern = self.pattern.as_bytes();
+        display.as_bytes().windows(pattern.len()).any(|window| {
+            if self.case_sensitive {
+                window == pattern
+            } else {
+                window.eq_ignore_ascii_case(pattern)
+            }
+        })
+    }
+}
+
+// Accepts a candidate only when the primary matcher and the secondary encoding both do. The primary runs first, so
+// the secondary string is only built for the rare candidates that already look right in base58.
+pub struct DualMatcher<'a, M> {
+    pub primary: &'a M,
+    pub secondary: &'a SecondaryMatcher,
+}
+
+impl<M: Matcher> Matcher for DualMatcher<'_, M> {
+    fn is_match(&self, pubkey_bytes: &[u8]) -> bool {
+        self.primary.is_match(pubkey_bytes) && self.secondary.matches(pubkey_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::{MatchType, PatternMatcher};
+
+    // Raw public keys, a secondary encoding, a pattern, and whether that encoding of the key contains the pattern
+    // (ignoring case)
+    const SECONDARY_VECTORS: &[([u8; 32], SecondaryEncoding, &str, bool)] = &[
+        ([255; 32], SecondaryEncoding::Hex, "ffff", true),
+        ([255; 32], SecondaryEncoding::Hex, "FFFF", true),
+        ([255; 32], SecondaryEncoding::Hex, "fe", false),
+        ([255; 32], SecondaryEncoding::Base32, "777Q", true),
+        ([255; 32], SecondaryEncoding::Base32, "Q7", false),
+        ([1; 32], SecondaryEncoding::Base32, "aeaqcaib", true),
+    ];
+
+    // Base58 prefix and DecimalBytes pattern a dual search for the key of all 255 bytes
+    // (JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG) must satisfy together, and whether it does
+    const DUAL_VECTORS: &[(&str, &str, bool)] = &[
+        ("JEK", "255.255", true),
+        ("JEK", "256", false),
+        ("JEX", "255.255", false),
+    ];
+
+    #[test]
+    fn secondary_encodings_contain_the_pattern() {
+        for &(bytes, encoding, pattern, expected) in SECONDARY_VECTORS {
+            let matched = SecondaryMatcher::new(encoding.encoding(), pattern, false)
+                .is_ok_and(|secondary| secondary.matches(&bytes));
+            assert_eq!(
+                matched, expected,
+                "{:?} form of {:02x}.. containing '{}'",
+                encoding, bytes[0], pattern
+            );
+        }
+    }
+
+    // A made-up encoding for the dual search vectors: every byte in decimal, separated by dots
+    struct DecimalBytes;
+
+    impl DisplayEncoding for DecimalBytes {
+        fn alphabet(&self) -> &str {
+            "0123456789."
+        }
+
+        fn encode(&self, pubkey_bytes: &[u8]) -> String {
+            pubkey_bytes
+                .iter()
+                .map(|b| b.to_string())
+                .collect::<Vec<_>>()
+                .join(".")
+        }
+    }
+
+    #[test]
+    fn dual_search_needs_both_forms() {
+        for &(prefix, secondary_pattern, expected) in DUAL_VECTORS {
+            let primary =
+                PatternMatcher::new(vec![prefix.to_string()], MatchType::Prefix, true, false);
+            let matched = SecondaryMatcher::new(Box::new(DecimalBytes), secondary_pattern, true)
+                .is_ok_and(|secondary| {
+                    DualMatcher {
+                        primary: &primary,
+                        secondary: &secondary,
+                    }
+                    .is_match(&[255; 32])
+                });
+            assert_eq!(
+                matched, expected,
+                "dual search for '{}' and decimal bytes '{}'",
+                prefix, secondary_pattern
+            );
+        }
+    }
+}
@@ -0,0 +1,603 @@
+use crate::estimate::{
+    attempt_deviations, attempt_probability, autotune_thread_counts, byte_sum_probability,
+    chance_within, estimate_attempts, expected_energy_kwh, flexible_overhead, format_energy,
+    luck_ratio, match_probability, recommend_threads,
+};
+use crate::matcher::{
+    ByteSum, HexPrefixMatcher, MatchType, NumericPrefixMatcher, PatternMatcher, accepted_chars,
+    ambiguous_chars, anagram_start, byte_sum, count_occurrences, encode_base58, like_patterns,
+    match_comparison, prefix_possible, shannon_entropy, split_match_type, starts_with_digit,
+    to_decimal,
+};
+use crate::search::Matcher;
+use crate::vectors::{
+    AFTER_ONES_ESTIMATE_VECTORS, AFTER_ONES_VECTORS, AMBIGUITY_VECTORS,
+    ANAGRAM_PROBABILITY_VECTORS, ANAGRAM_VECTORS, AUTOTUNE_VECTORS, BYTE_SUM_MATCH_VECTORS,
+    BYTE_SUM_PROBABILITY_VECTORS, BYTE_SUM_VECTORS, CHAR_VECTORS, CLOSEST_VECTORS,
+    COLLAPSE_VECTORS, COMPARISON_VECTORS, COUNT_VECTORS, DECIMAL_VECTORS, DEVIATION_VECTORS,
+    DISTANCE_ESTIMATE_VECTORS, DISTANCE_VECTORS, ENCODING_VECTORS, ENERGY_FORMAT_VECTORS,
+    ENERGY_VECTORS, ENTROPY_LIMIT_VECTORS, ENTROPY_VECTORS, HEX_VECTORS, KEYPAIR_VECTORS,
+    LEADING_DIGIT_VECTORS, LEADING_ONES_ADDRESSES, LIKE_VECTORS, LONGEST_PARTIAL_VECTORS,
+    LUCK_CHANCE_VECTORS, LUCK_VECTORS, MATCH_VECTORS, NUMERIC_PREFIX_VECTORS, OCCURRENCE_VECTORS,
+    OVERHEAD_VECTORS, PREFIX_POSSIBLE_VECTORS, PROBABILITY_VECTORS, THREAD_COUNT_VECTORS,
+    UNVALIDATED_VECTORS, USDC_MINT,
+};
+use five8::BASE58_ENCODED_32_MAX_LEN;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+// Runs the matcher and estimate vectors and returns a description of each one that failed
+pub fn run_vectors() -> Vec<String> {
+    let mut failures = Vec::new();
+    check_matcher(&mut failures);
+    check_estimate(&mut failures);
+    failures
+}
+
+// Matcher, base58 encoding and key derivation vectors
+fn check_matcher(failures: &mut Vec<String>) {
+    for v in MATCH_VECTORS {
+        let matched = v.matcher().matched_pattern(v.address.as_bytes()).is_some();
+        if matched != v.expected {
+            failures.push(format!(
+                "match {}: expected {}, got {}",
+                v.describe(),
+                v.expected,
+                matched
+            ));
+        }
+    }
+
+    for &(target, case_sensitive, flex_level, expected) in CHAR_VECTORS {
+        let accepted = accepted_chars(target, case_sensitive, flex_level);
+        if accepted != expected.as_bytes() {
+            failures.push(format!(
+                "characters accepted for '{}' (case sensitive: {}, flex level: {}): expected \"{}\", got \"{}\"",
+                target as char,
+                case_sensitive,
+                flex_level,
+                expected,
+                String::from_utf8_lossy(&accepted)
+            ));
+        }
+    }
+
+    for &(pattern, expected) in AMBIGUITY_VECTORS {
+        let ambiguous: String = ambiguous_chars(pattern)
+            .into_iter()
+            .map(|(c, _)| c as char)
+            .collect();
+        if ambiguous != expected {
+            failures.push(format!(
+                "easily confused characters in '{}': expected '{}', got '{}'",
+                pattern, expected, ambiguous
+            ));
+        }
+    }
+
+    for &(pattern, address, expected) in AFTER_ONES_VECTORS {
+        let address = LEADING_ONES_ADDRESSES[address];
+        let start = PatternMatcher::new(
+            vec![pattern.to_string()],
+            MatchType::PrefixAfterOnes,
+            true,
+            false,
+        )
+        .match_start(address.as_bytes(), pattern);
+        if start != expected {
+            failures.push(format!(
+                "prefix-after-ones '{}' on {}: expected a match at {:?}, got {:?}",
+                pattern, address, expected, start
+            ));
+        }
+    }
+
+    for &(pattern, match_type, expected) in UNVALIDATED_VECTORS {
+        let matched = PatternMatcher::new(vec![pattern.to_string()], match_type, true, false)
+            .matched_pattern(USDC_MINT.as_bytes())
+            .is_some();
+        if matched != expected {
+            failures.push(format!(
+                "unvalidated {:?} pattern '{}' on {}: expected {}, got {}",
+                match_type, pattern, USDC_MINT, expected, matched
+            ));
+        }
+    }
+
+    for &(matched, pattern, case_sensitive, flex_level, expected) in COMPARISON_VECTORS {
+        let comparison = match_comparison(
+            matched.as_bytes(),
+            pattern.as_bytes(),
+            case_sensitive,
+            flex_level,
+        );
+        if comparison != expected {
+            failures.push(format!(
+                "comparison of '{}' matched by '{}': expected {:?}, got {:?}",
+                pattern, matched, expected, comparison
+            ));
+        }
+    }
+
+    for &(text, expected) in ENTROPY_VECTORS {
+        let entropy = shannon_entropy(text.as_bytes());
+        if (entropy - expected).abs() > 1e-12 {
+            failures.push(format!(
+                "entropy of '{}': expected {} bits per character, got {}",
+                text, expected, entropy
+            ));
+        }
+    }
+
+    for &(address, min, max, expected) in ENTROPY_LIMIT_VECTORS {
+        let matcher = PatternMatcher::new(vec!["a".to_string()], MatchType::Prefix, true, false)
+            .with_entropy_limits(min, max);
+        let accepted = matcher.matched_pattern(address.as_bytes()).is_some();
+        if accepted != expected {
+            failures.push(format!(
+                "'{}' with entropy between {:?} and {:?}: expected accepted {}, got {}",
+                address, min, max, expected, accepted
+            ));
+        }
+    }
+
+    for &(address, pattern, match_type, no_leading_digit, expected) in LEADING_DIGIT_VECTORS {
+        let matcher = PatternMatcher::new(vec![pattern.to_string()], match_type, true, false)
+            .with_no_leading_digit(no_leading_digit);
+        let accepted = matcher.matched_pattern(address.as_bytes()).is_some();
+        // with the flag, an address the pattern matches is accepted exactly when it starts with a letter
+        let digit_first = starts_with_digit(address.as_bytes());
+        if accepted != expected || (no_leading_digit && digit_first == accepted) {
+            failures.push(format!(
+                "{} matched by '{}' (no leading digit: {}): expected accepted {}, got {}",
+                address, pattern, no_leading_digit, expected, accepted
+            ));
+        }
+    }
+
+    for &(pattern, match_type, expected) in LONGEST_PARTIAL_VECTORS {
+        let matcher = PatternMatcher::new(vec![pattern.to_string()], match_type, true, false);
+        let matched = matcher.longest_partial(USDC_MINT.as_bytes());
+        if matched != Some((pattern, expected)) {
+            failures.push(format!(
+                "longest partial match of '{}' ({:?}) in {}: expected {}, got {:?}",
+                pattern, match_type, USDC_MINT, expected, matched
+            ));
+        }
+    }
+
+    for &(patterns, match_type, expected_pattern, expected_right) in CLOSEST_VECTORS {
+        let matcher = PatternMatcher::new(
+            patterns.iter().map(|p| p.to_string()).collect(),
+            match_type,
+            true,
+            false,
+        );
+        let closest = matcher.closest_pattern(USDC_MINT.as_bytes());
+        if closest != Some((expected_pattern, expected_right)) {
+            failures.push(format!(
+                "closest {:?} pattern of {:?} to {}: expected {:?}, got {:?}",
+                match_type,
+                patterns,
+                USDC_MINT,
+                (expected_pattern, expected_right),
+                closest
+            ));
+        }
+    }
+
+    for &(pattern, match_type, max_distance, expected) in DISTANCE_VECTORS {
+        let matched = PatternMatcher::new(vec![pattern.to_string()], match_type, true, false)
+            .with_max_distance(max_distance)
+            .matched_pattern(USDC_MINT.as_bytes())
+            .is_some();
+        if matched != expected {
+            failures.push(format!(
+                "{:?} '{}' within {} wrong character(s) of {}: expected {}, got {}",
+                match_type, pattern, max_distance, USDC_MINT, expected, matched
+            ));
+        }
+    }
+
+    for &(reference, len, match_type, expected) in LIKE_VECTORS {
+        let derived = like_patterns(reference, len, match_type).ok();
+        let matches_reference = derived.as_ref().is_none_or(|entries| {
+            let (patterns, match_types): (Vec<String>, Vec<MatchType>) = entries
+                .iter()
+                .map(|entry| {
+                    let (pattern, match_type) = split_match_type(entry);
+                    (pattern.to_string(), match_type.unwrap_or(MatchType::Prefix))
+                })
+                .unzip();
+            let matcher = PatternMatcher::new(patterns, MatchType::Prefix, true, false)
+                .with_match_types(match_types);
+            (0..entries.len()).all(|i| matcher.matches_index(reference.as_bytes(), i))
+        });
+        let expected_entries: Option<Vec<String>> =
+            expected.map(|entries| entries.iter().map(|entry| entry.to_string()).collect());
+        if derived != expected_entries || !matches_reference {
+            failures.push(format!(
+                "--like {} with {} character(s) and {:?} matching: expected {:?}, got {:?}",
+                reference, len, match_type, expected, derived
+            ));
+        }
+    }
+
+    for &(text, pattern, case_sensitive, expected) in COUNT_VECTORS {
+        let count = count_occurrences(text.as_bytes(), pattern.as_bytes(), case_sensitive, 0);
+        if count != expected {
+            failures.push(format!(
+                "occurrences of '{}' in {} (case sensitive: {}): expected {}, got {}",
+                pattern, text, case_sensitive, expected, count
+            ));
+        }
+    }
+
+    for &(address, pattern, match_type, min_occurrences, expected) in OCCURRENCE_VECTORS {
+        let matched = PatternMatcher::new(vec![pattern.to_string()], match_type, true, false)
+            .with_fingerprint_len(address.len())
+            .with_min_occurrences(min_occurrences)
+            .matched_pattern(address.as_bytes())
+            .is_some();
+        if matched != expected {
+            failures.push(format!(
+                "{:?} '{}' at least {} time(s) in {}: expected {}, got {}",
+                match_type, pattern, min_occurrences, address, expected, matched
+            ));
+        }
+    }
+
+    for &(address, pattern, match_type, collapsed, folded) in COLLAPSE_VECTORS {
+        let matcher = PatternMatcher::new(vec![pattern.to_string()], match_type, false, false);
+        let got = (
+            matcher
+                .clone()
+                .with_collapse_case(true)
+                .matched_pattern(address.as_bytes())
+                .is_some(),
+            matcher.matched_pattern(address.as_bytes()).is_some(),
+        );
+        if got != (collapsed, folded) {
+            failures.push(format!(
+                "pattern '{}' ({:?}) in {}: expected collapsed/folded matches {:?}, got {:?}",
+                pattern,
+                match_type,
+                address,
+                (collapsed, folded),
+                got
+            ));
+        }
+    }
+
+    for &(letters, case_sensitive, max_distance, expected) in ANAGRAM_VECTORS {
+        let start = anagram_start(
+            USDC_MINT.as_bytes(),
+            letters.as_bytes(),
+            case_sensitive,
+            max_distance,
+        );
+        if start != expected {
+            failures.push(format!(
+                "anagram '{}' (case sensitive: {}, max distance: {}) in {}: expected {:?}, got {:?}",
+                letters, case_sensitive, max_distance, USDC_MINT, expected, start
+            ));
+        }
+    }
+
+    for (bytes, prefix, expected) in HEX_VECTORS {
+        let matched = HexPrefixMatcher::new(prefix).is_match(bytes);
+        if matched != *expected {
+            failures.push(format!(
+                "hex prefix '{}' on {:02x}..: expected {}, got {}",
+                prefix, bytes[0], expected, matched
+            ));
+        }
+    }
+
+    for (bytes, expected) in DECIMAL_VECTORS {
+        let decimal = to_decimal(bytes);
+        if decimal != *expected {
+            failures.push(format!(
+                "decimal of {:02x}..{:02x}: expected {}, got {}",
+                bytes[0], bytes[31], expected, decimal
+            ));
+        }
+        // the matcher agrees with the conversion on the full number and on a prefix of it
+        let matcher = NumericPrefixMatcher::new(&expected[..expected.len().min(5)]);
+        if !matcher.is_match(bytes) {
+            failures.push(format!(
+                "numeric prefix '{}' on {}: expected a match",
+                matcher.prefix, expected
+            ));
+        }
+    }
+
+    for &(prefix, expected) in NUMERIC_PREFIX_VECTORS {
+        let probability = 1.0 / NumericPrefixMatcher::new(prefix).expected_attempts();
+        if ((probability - expected) / expected).abs() > 1e-9 {
+            failures.push(format!(
+                "chance of numeric prefix '{}': expected {}, got {}",
+                prefix, expected, probability
+            ));
+        }
+    }
+
+    for (bytes, expected) in ENCODING_VECTORS {
+        let mut buf = [0u8; BASE58_ENCODED_32_MAX_LEN];
+        let encoded = encode_base58(bytes, &mut buf);
+        if encoded != expected.as_bytes() {
+            failures.push(format!(
+                "encoding: expected {}, got {}",
+                expected,
+                String::from_utf8_lossy(encoded)
+            ));
+        }
+    }
+
+    for (seed, expected) in KEYPAIR_VECTORS {
+        let address = Keypair::new_from_array(*seed).pubkey().to_string();
+        if address != *expected {
+            failures.push(format!(
+                "keypair from seed {:?}: expected {}, got {}",
+                seed[0], expected, address
+            ));
+        }
+    }
+
+    for &(bytes, expected) in BYTE_SUM_VECTORS {
+        let sum = byte_sum(bytes);
+        if sum != expected {
+            failures.push(format!(
+                "byte sum of {:?}: expected {}, got {}",
+                bytes, expected, sum
+            ));
+        }
+    }
+
+    let mut usdc_bytes = [0u8; 32];
+    five8::decode_32(USDC_MINT, &mut usdc_bytes).expect("USDC mint is a valid address");
+    for &(pattern, target, modulus, expected) in BYTE_SUM_MATCH_VECTORS {
+        let matcher =
+            PatternMatcher::new(vec![pattern.to_string()], MatchType::Prefix, true, false)
+                .with_byte_sum(Some(ByteSum { target, modulus }));
+        if matcher.is_match(&usdc_bytes) != expected {
+            failures.push(format!(
+                "byte sum {} (modulus {:?}) with prefix '{}' on {}: expected {}",
+                target, modulus, pattern, USDC_MINT, expected
+            ));
+        }
+    }
+
+    for &(pattern, case_sensitive, expected) in PREFIX_POSSIBLE_VECTORS {
+        let possible = prefix_possible(pattern, case_sensitive, 0);
+        if possible != expected {
+            failures.push(format!(
+                "prefix '{}' (case sensitive {}): expected possible {}, got {}",
+                pattern, case_sensitive, expected, possible
+            ));
+        }
+    }
+}
+
+// Estimate, luck and thread count vectors
+fn check_estimate(failures: &mut Vec<String>) {
+    for &(pattern, case_sensitive, flex_level, expected) in AFTER_ONES_ESTIMATE_VECTORS {
+        let matcher = PatternMatcher::new(
+            vec![pattern.to_string()],
+            MatchType::PrefixAfterOnes,
+            case_sensitive,
+            false,
+        )
+        .with_flex_level(flex_level);
+        let attempts = estimate_attempts(&matcher);
+        if ((attempts - expected) / expected).abs() > 1e-9 {
+            failures.push(format!(
+                "expected attempts of prefix-after-ones '{}' (case sensitive: {}, flex level: {}): expected {}, got {}",
+                pattern, case_sensitive, flex_level, expected, attempts
+            ));
+        }
+    }
+
+    for &(max_distance, expected) in DISTANCE_ESTIMATE_VECTORS {
+        let matcher = PatternMatcher::new(vec!["EPjF".to_string()], MatchType::Prefix, true, false)
+            .with_max_distance(max_distance);
+        let attempts = estimate_attempts(&matcher);
+        if ((attempts - expected) / expected).abs() > 1e-9 {
+            failures.push(format!(
+                "expected attempts of prefix 'EPjF' within {} wrong character(s): expected {}, got {}",
+                max_distance, expected, attempts
+            ));
+        }
+    }
+
+    for &(pattern, attempts, expected) in PROBABILITY_VECTORS {
+        let matcher =
+            PatternMatcher::new(vec![pattern.to_string()], MatchType::Prefix, true, false);
+        let probability = match_probability(&matcher, attempts);
+        if (probability - expected).abs() > 1e-9 {
+            failures.push(format!(
+                "chance of prefix '{}' within {} attempts: expected {}, got {}",
+                pattern, attempts, expected, probability
+            ));
+        }
+    }
+
+    for &(letters, case_sensitive, max_distance, at_position) in ANAGRAM_PROBABILITY_VECTORS {
+        let matcher = PatternMatcher::new(
+            vec![letters.to_string()],
+            MatchType::Anagram,
+            case_sensitive,
+            false,
+        )
+        .with_max_distance(max_distance);
+        let expected = 1.0 - (1.0 - at_position).powi(45 - letters.len() as i32);
+        let probability = attempt_probability(&matcher);
+        if ((probability - expected) / expected).abs() > 1e-9 {
+            failures.push(format!(
+                "chance of anagram '{}' (case sensitive: {}, max distance: {}): expected {}, got {}",
+                letters, case_sensitive, max_distance, expected, probability
+            ));
+        }
+    }
+
+    for &(logical, expected) in THREAD_COUNT_VECTORS {
+        let counts = autotune_thread_counts(logical);
+        if counts != expected {
+            failures.push(format!(
+                "autotune thread counts for {} cores: expected {:?}, got {:?}",
+                logical, expected, counts
+            ));
+        }
+    }
+
+    for &(rates, expected) in AUTOTUNE_VECTORS {
+        let recommended = recommend_threads(rates);
+        if recommended != expected {
+            failures.push(format!(
+                "recommended threads for {:?}: expected {}, got {}",
+                rates, expected, recommended
+            ));
+        }
+    }
+
+    for &(exact, flexible, expected) in OVERHEAD_VECTORS {
+        let overhead = flexible_overhead(exact, flexible);
+        if (overhead - expected).abs() > 1e-9 {
+            failures.push(format!(
+                "flexible overhead at {} vs {} addresses/second: expected {}%, got {}%",
+                exact, flexible, expected, overhead
+            ));
+        }
+    }
+
+    for &(target, modulus, expected) in BYTE_SUM_PROBABILITY_VECTORS {
+        let p = byte_sum_probability(&ByteSum { target, modulus });
+        if (p - expected).abs() > expected * 1e-9 {
+            failures.push(format!(
+                "chance of byte sum {} (modulus {:?}): expected {:e}, got {:e}",
+                target, modulus, expected, p
+            ));
+        }
+    }
+
+    for &(expected_attempts, rate, watts, expected) in ENERGY_VECTORS {
+        let kwh = expected_energy_kwh(expected_attempts, rate, watts);
+        let close = match (kwh, expected) {
+            (Some(kwh), Some(expected)) => (kwh - expected).abs() < 1e-9,
+            (kwh, expected) => kwh.is_none() && expected.is_none(),
+        };
+        if !close {
+            failures.push(format!(
+                "energy for {} attempts at {} addresses/second and {} W: expected {:?} kWh, got {:?}",
+                expected_attempts, rate, watts, expected, kwh
+            ));
+        }
+    }
+
+    for &(kwh, expected) in ENERGY_FORMAT_VECTORS {
+        let formatted = format_energy(kwh);
+        if formatted != expected {
+            failures.push(format!(
+                "energy {} kWh: expected '{}', got '{}'",
+                kwh, expected, formatted
+            ));
+        }
+    }
+
+    for &(attempts, expected_attempts, expected) in LUCK_VECTORS {
+        let ratio = luck_ratio(attempts, expected_attempts);
+        if ratio != expected {
+            failures.push(format!(
+                "luck after {} of {} expected attempts: expected {:?}, got {:?}",
+                attempts, expected_attempts, expected, ratio
+            ));
+        }
+    }
+
+    for &(attempts, expected_attempts, expected) in DEVIATION_VECTORS {
+        let deviations = attempt_deviations(attempts, expected_attempts);
+        let close = match (deviations, expected) {
+            (Some(got), Some(expected)) => (got - expected).abs() < 1e-12,
+            (got, expected) => got == expected,
+        };
+        if !close {
+            failures.push(format!(
+                "deviations of {} attempts from {} expected: expected {:?}, got {:?}",
+                attempts, expected_attempts, expected, deviations
+            ));
+        }
+    }
+
+    for &(expected_attempts, attempts, expected) in LUCK_CHANCE_VECTORS {
+        let chance = chance_within(expected_attempts, attempts);
+        if (chance - expected).abs() > 1e-12 {
+            failures.push(format!(
+                "chance of a match within {} of {} expected attempts: expected {}, got {}",
+                attempts, expected_attempts, expected, chance
+            ));
+        }
+    }
+}
+
+// Total number of vectors run_vectors checks
+pub fn vector_count() -> usize {
+    MATCH_VECTORS.len()
+        + CHAR_VECTORS.len()
+        + PROBABILITY_VECTORS.len()
+        + DISTANCE_VECTORS.len()
+        + COLLAPSE_VECTORS.len()
+        + ANAGRAM_VECTORS.len()
+        + ANAGRAM_PROBABILITY_VECTORS.len()
+        + CLOSEST_VECTORS.len()
+        + LONGEST_PARTIAL_VECTORS.len()
+        + COMPARISON_VECTORS.len()
+        + ENTROPY_VECTORS.len()
+        + ENTROPY_LIMIT_VECTORS.len()
+        + LEADING_DIGIT_VECTORS.len()
+        + UNVALIDATED_VECTORS.len()
+        + COUNT_VECTORS.len()
+        + LIKE_VECTORS.len()
+        + OCCURRENCE_VECTORS.len()
+        + AFTER_ONES_VECTORS.len()
+        + AFTER_ONES_ESTIMATE_VECTORS.len()
+        + AMBIGUITY_VECTORS.len()
+        + DISTANCE_ESTIMATE_VECTORS.len()
+        + HEX_VECTORS.len()
+        + DECIMAL_VECTORS.len()
+        + NUMERIC_PREFIX_VECTORS.len()
+        + ENCODING_VECTORS.len()
+        + KEYPAIR_VECTORS.len()
+        + THREAD_COUNT_VECTORS.len()
+        + AUTOTUNE_VECTORS.len()
+        + OVERHEAD_VECTORS.len()
+        + BYTE_SUM_VECTORS.len()
+        + BYTE_SUM_MATCH_VECTORS.len()
+        + BYTE_SUM_PROBABILITY_VECTORS.len()
+        + ENERGY_VECTORS.len()
+        + ENERGY_FORMAT_VECTORS.len()
+        + LUCK_VECTORS.len()
+        + DEVIATION_VECTORS.len()
+        + LUCK_CHANCE_VECTORS.len()
+        + PREFIX_POSSIBLE_VECTORS.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_passes(check: fn(&mut Vec<String>)) {
+        let mut failures = Vec::new();
+        check(&mut failures);
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn matcher_vectors() {
+        assert_passes(check_matcher);
+    }
+
+    #[test]
+    fn estimate_vectors() {
+        assert_passes(check_estimate);
+    }
+}
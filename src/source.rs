@@ -0,0 +1,442 @@
+use bip39::Mnemonic;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+use sha2::{Digest, Sha256};
+use solana_keypair::Keypair;
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Where the search gets its candidates from; each worker owns one source
+pub trait KeypairSource {
+    fn next(&mut self) -> Keypair;
+
+    // Like next, but reports a failure to get randomness instead of panicking; the search uses this one
+    fn try_next(&mut self) -> Result<Keypair, String> {
+        Ok(self.next())
+    }
+
+    // Seed phrase the last keypair was derived from, for sources that have one
+    fn last_mnemonic(&self) -> Option<&Mnemonic> {
+        None
+    }
+}
+
+impl<S: KeypairSource + ?Sized> KeypairSource for Box<S> {
+    fn next(&mut self) -> Keypair {
+        (**self).next()
+    }
+
+    fn try_next(&mut self) -> Result<Keypair, String> {
+        (**self).try_next()
+    }
+
+    fn last_mnemonic(&self) -> Option<&Mnemonic> {
+        (**self).last_mnemonic()
+    }
+}
+
+// How often a failed read of the OS random number generator is retried before giving up
+pub const RNG_RETRIES: u32 = 5;
+
+// Pause before the first retry; each further retry waits one step longer
+const RNG_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+// Fills buf from the OS random number generator, retrying transient failures with a warning on stderr
+pub fn fill_os_random(buf: &mut [u8]) -> Result<(), String> {
+    fill_with_retry(
+        buf,
+        |buf| getrandom::fill(buf).map_err(|e| e.to_string()),
+        |e, retry| {
+            eprintln!(
+                "Warning: OS random number generator failed ({}); retry {} of {}",
+                e, retry, RNG_RETRIES
+            )
+        },
+    )
+}
+
+// Calls fill until it succeeds, pausing a little longer after every failure and telling on_retry about each retry.
+// Gives up with an error once RNG_RETRIES retries have failed as well, since a generator failing that persistently
+// isn't coming back.
+pub fn fill_with_retry<F, R>(buf: &mut [u8], mut fill: F, mut on_retry: R) -> Result<(), String>
+where
+    F: FnMut(&mut [u8]) -> Result<(), String>,
+    R: FnMut(&str, u32),
+{
+    let mut retries = 0;
+    loop {
+        let Err(e) = fill(buf) else {
+            return Ok(());
+        };
+        if retries == RNG_RETRIES {
+            return Err(format!(
+                "OS random number generator failed {} times in a row: {}",
+                retries + 1,
+                e
+            ));
+        }
+        retries += 1;
+        on_retry(&e, retries);
+        std::thread::sleep(RNG_RETRY_DELAY * retries);
+    }
+}
+
+// Default source: fresh keypairs from the OS random number generator. The secret is read with getrandom directly,
+// exactly as Keypair::new would, so a transient failure can be retried instead of panicking.
+pub struct OsRngSource;
+
+impl KeypairSource for OsRngSource {
+    fn next(&mut self) -> Keypair {
+        self.try_next().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_next(&mut self) -> Result<Keypair, String> {
+        let mut secret = [0u8; 32];
+        fill_os_random(&mut secret)?;
+        Ok(Keypair::new_from_array(secret))
+    }
+}
+
+// Keypairs generated ahead of time by a background thread, for embedders that call search repeatedly with easy
+// patterns: the first candidates of each search come straight from the pool, so a trivial match returns almost
+// instantly. The pool holds at most `capacity` keypairs and refills in the background; every pooled keypair is
+// handed out exactly once. The generator thread exits once the pool and all its sources are dropped.
+pub struct WarmPool {
+    keypairs: Arc<Mutex<Receiver<Keypair>>>,
+}
+
+impl WarmPool {
+    pub fn start(capacity: usize) -> Self {
+        let (sender, receiver) = sync_channel(capacity);
+        std::thread::spawn(move || fill_pool(sender));
+        WarmPool {
+            keypairs: Arc::new(Mutex::new(receiver)),
+        }
+    }
+
+    // A source for one search worker that drains the pool before generating keypairs itself
+    pub fn source(&self) -> PooledSource {
+        PooledSource {
+            keypairs: self.keypairs.clone(),
+        }
+    }
+}
+
+// Blocks whenever the pool is full; stops when nobody can receive anymore or the OS generator keeps failing, in
+// which case the sources generate (and report failures) themselves
+fn fill_pool(sender: SyncSender<Keypair>) {
+    while let Ok(keypair) = OsRngSource.try_next()
+        && sender.send(keypair).is_ok()
+    {}
+}
+
+pub struct PooledSource {
+    keypairs: Arc<Mutex<Receiver<Keypair>>>,
+}
+
+impl KeypairSource for PooledSource {
+    fn next(&mut self) -> Keypair {
+        self.try_next().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_next(&mut self) -> Result<Keypair, String> {
+        // never wait on the pool: if another worker holds it or it is empty, generate a fresh keypair
+        let pooled = self
+            .keypairs
+            .try_lock()
+            .ok()
+            .and_then(|keypairs| keypairs.try_recv().ok());
+        match pooled {
+            Some(keypair) => Ok(keypair),
+            None => OsRngSource.try_next(),
+        }
+    }
+}
+
+// Keypairs generated after which ChaChaSource pulls a fresh seed from the OS
+pub const CHACHA_RESEED_INTERVAL: u64 = 1 << 20;
+
+// Userspace CSPRNG source: a ChaCha20 stream per worker, seeded from the OS and reseeded every
+// CHACHA_RESEED_INTERVAL keypairs so a compromised state only ever exposes a bounded run of keys.
+// The first seed is read along with the first keypair, so a failing OS generator surfaces through try_next.
+pub struct ChaChaSource {
+    rng: Option<ChaCha20Rng>,
+    generated: u64,
+}
+
+impl ChaChaSource {
+    pub fn new() -> Self {
+        ChaChaSource {
+            rng: None,
+            generated: 0,
+        }
+    }
+}
+
+impl Default for ChaChaSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeypairSource for ChaChaSource {
+    fn next(&mut self) -> Keypair {
+        self.try_next().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_next(&mut self) -> Result<Keypair, String> {
+        if self.rng.is_none() || self.generated == CHACHA_RESEED_INTERVAL {
+            let mut seed = [0u8; 32];
+            fill_os_random(&mut seed)?;
+            self.rng = Some(ChaCha20Rng::from_seed(seed));
+            self.generated = 0;
+        }
+        let rng = self.rng.as_mut().expect("seeded above");
+        self.generated += 1;
+        let mut secret = [0u8; 32];
+        rng.fill_bytes(&mut secret);
+        Ok(Keypair::new_from_array(secret))
+    }
+}
+
+// Deterministic source that yields keypairs for a fixed list of secret seeds, cycling once exhausted.
+// Meant for exercising the search loop with a planted match; never use it for real keys.
+pub struct SequenceSource {
+    seeds: Vec<[u8; 32]>,
+    index: usize,
+}
+
+impl SequenceSource {
+    pub fn new(seeds: Vec<[u8; 32]>) -> Self {
+        assert!(!seeds.is_empty(), "SequenceSource needs at least one seed");
+        SequenceSource { seeds, index: 0 }
+    }
+}
+
+impl KeypairSource for SequenceSource {
+    fn next(&mut self) -> Keypair {
+        let seed = self.seeds[self.index];
+        self.index = (self.index + 1) % self.seeds.len();
+        Keypair::new_from_array(seed)
+    }
+}
+
+// The secret seed --debug-counter derives from a counter value: its little-endian bytes followed by zeros
+pub fn counter_seed(counter: u64) -> [u8; 32] {
+    let mut seed = [0u8; 32];
+    seed[..8].copy_from_slice(&counter.to_le_bytes());
+    seed
+}
+
+// Debugging source that derives every secret from a counter, so a search can be replayed keypair for keypair.
+// Worker w of n takes the counters start + w, start + w + n, start + w + 2n, ... so workers never repeat each
+// other. Anyone can regenerate these keys; never use them for real funds.
+pub struct CounterSource {
+    counter: u64,
+    step: u64,
+}
+
+impl CounterSource {
+    pub fn new(start: u64, worker: usize, workers: usize) -> Self {
+        CounterSource {
+            counter: start.wrapping_add(worker as u64),
+            step: workers.max(1) as u64,
+        }
+    }
+}
+
+impl KeypairSource for CounterSource {
+    fn next(&mut self) -> Keypair {
+        let seed = counter_seed(self.counter);
+        self.counter = self.counter.wrapping_add(self.step);
+        Keypair::new_from_array(seed)
+    }
+}
+
+// The seed --seed-from-file derives from the file's contents: their SHA-256
+pub fn file_seed(contents: &[u8]) -> [u8; 32] {
+    Sha256::digest(contents).into()
+}
+
+// Reads the file --seed-from-file names and derives the seed from it
+pub fn read_file_seed(path: &std::path::Path) -> Result<[u8; 32], String> {
+    std::fs::read(path)
+        .map(|contents| file_seed(&contents))
+        .map_err(|e| format!("Cannot read seed file {}: {}", path.display(), e))
+}
+
+// Reproducible source for auditable searches: the secrets come from a ChaCha20 stream keyed with a fixed seed, and
+// worker w reads stream number w of it, so workers never repeat each other and anyone holding the seed can replay
+// every candidate. Nothing about the keys is secret from them, so never use these keys for real funds.
+pub struct SeededSource {
+    rng: ChaCha20Rng,
+}
+
+impl SeededSource {
+    pub fn new(seed: [u8; 32], worker: usize) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        rng.set_stream(worker as u64);
+        SeededSource { rng }
+    }
+}
+
+impl KeypairSource for SeededSource {
+    fn next(&mut self) -> Keypair {
+        let mut secret = [0u8; 32];
+        self.rng.fill_bytes(&mut secret);
+        Keypair::new_from_array(secret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::to_hex;
+    use solana_signer::Signer;
+
+    // How many times in a row the random number generator fails before it works, and whether reading it must succeed
+    const RNG_VECTORS: &[(u32, bool)] = &[
+        (0, true),
+        (2, true),
+        (RNG_RETRIES, true),
+        (RNG_RETRIES + 1, false),
+    ];
+
+    // --debug-counter values and the address the keypair derived from each must have, on every run
+    const COUNTER_VECTORS: &[(u64, &str)] = &[
+        (0, "4zvwRjXUKGfvwnParsHAS3HuSVzV5cA4McphgmoCtajS"),
+        (1, "EvFUfisEScFuZSqDXagC17m3bpP32B74dseMHtzQ5TNb"),
+        (u64::MAX, "CFtLhcLvwwHU11r5cv5RQXYE8rMiRSAqUaza2KxmKgEn"),
+    ];
+
+    // CounterSource (start, worker, workers): its keypairs must come from the counters start + worker, then every
+    // workers-th one after, and a second source built the same way must repeat them
+    const COUNTER_SOURCE_VECTORS: &[(u64, usize, usize)] =
+        &[(0, 0, 1), (0, 2, 4), (1000, 1, 3), (u64::MAX, 1, 2)];
+
+    // --seed-from-file contents, their SHA-256 and the first address of a worker seeded from them. Each file is
+    // written twice and both copies must give that address.
+    const SEED_FILE_VECTORS: &[(&[u8], &str, usize, &str)] = &[
+        (
+            b"",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            0,
+            "8TDTHm4Vg45yoYNsR8v7YT89c1ZKHAuEMaNDQTkBjFNn",
+        ),
+        (
+            b"abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            0,
+            "FhSrvbtjfeaa8tbngTBNzPJDbKkojThWuuL2nawai15v",
+        ),
+        // another worker reads another stream of the same seed
+        (
+            b"abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            1,
+            "DVkSL2M3XwAYXxnXgVeFdCTvm5MLj11SYJvm5aHPM7xb",
+        ),
+    ];
+
+    // Reads randomness from a simulated generator that fails the given number of times first
+    fn simulated_fill(failures: u32) -> Result<(), String> {
+        let mut remaining = failures;
+        fill_with_retry(
+            &mut [0u8; 32],
+            |_| {
+                if remaining == 0 {
+                    return Ok(());
+                }
+                remaining -= 1;
+                Err("simulated failure".to_string())
+            },
+            |_, _| {},
+        )
+    }
+
+    fn address(keypair: Keypair) -> String {
+        keypair.pubkey().to_string()
+    }
+
+    #[test]
+    fn random_reads_are_retried_a_limited_number_of_times() {
+        for &(generator_failures, expected) in RNG_VECTORS {
+            assert_eq!(
+                simulated_fill(generator_failures).is_ok(),
+                expected,
+                "random number generator failing {} time(s) in a row",
+                generator_failures
+            );
+        }
+    }
+
+    #[test]
+    fn debug_counter_keys_are_fixed() {
+        for &(counter, expected) in COUNTER_VECTORS {
+            assert_eq!(
+                address(Keypair::new_from_array(counter_seed(counter))),
+                expected,
+                "debug counter {}",
+                counter
+            );
+        }
+    }
+
+    #[test]
+    fn counter_source_splits_the_counters_between_workers() {
+        for &(start, worker, workers) in COUNTER_SOURCE_VECTORS {
+            let addresses = |mut source: CounterSource| -> Vec<String> {
+                (0..3).map(|_| address(source.next())).collect()
+            };
+            let expected: Vec<String> = (0..3u64)
+                .map(|k| {
+                    let counter = start
+                        .wrapping_add(worker as u64)
+                        .wrapping_add(k * workers as u64);
+                    address(Keypair::new_from_array(counter_seed(counter)))
+                })
+                .collect();
+            for run in 0..2 {
+                assert_eq!(
+                    addresses(CounterSource::new(start, worker, workers)),
+                    expected,
+                    "counter source from {} for worker {} of {} (run {})",
+                    start,
+                    worker,
+                    workers,
+                    run
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn seed_file_gives_the_same_keys_every_time() {
+        for (i, &(contents, hash, worker, expected)) in SEED_FILE_VECTORS.iter().enumerate() {
+            for copy in 0..2 {
+                let path = std::env::temp_dir().join(format!(
+                    "vanity-source-{}-seed-{}-{}",
+                    std::process::id(),
+                    i,
+                    copy
+                ));
+                std::fs::write(&path, contents).unwrap();
+                let seed = read_file_seed(&path);
+                let _ = std::fs::remove_file(&path);
+                let seed = seed.unwrap();
+                assert_eq!(
+                    (
+                        to_hex(&seed),
+                        address(SeededSource::new(seed, worker).next())
+                    ),
+                    (hash.to_string(), expected.to_string()),
+                    "seed file {:?} (copy {}), worker {}",
+                    contents,
+                    copy,
+                    worker
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::matcher::{PatternMatcher, to_hex};
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+// Totals of one search configuration over every run recorded in a stats file
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LifetimeStats {
+    pub runs: u64,
+    pub attempts: u64,
+    pub elapsed_secs: f64,
+}
+
+// Key of a search configuration in a stats file: SHA-256, as hex, over the patterns and every setting that decides
+// which addresses match. Runs share totals only when they search for exactly the same thing; thread counts, the
+// entropy source and output options don't count.
+pub fn config_key(matcher: &PatternMatcher) -> String {
+    let config = json!({
+        "patterns": matcher.patterns,
+        "match_types": matcher
+            .match_types
+            .iter()
+            .map(|t| format!("{:?}", t))
+            .collect::<Vec<_>>(),
+        "case_sensitive": matcher.case_sensitive,
+        "flex_level": matcher.flex_level,
+        "fingerprint_len": matcher.fingerprint_len,
+        "at_range": [matcher.at_range.start, matcher.at_range.end],
+        "window": [matcher.window.start, matcher.window.end],
+        "excludes": matcher.excludes,
+        "min_distinct": matcher.min_distinct,
+        "min_entropy": matcher.min_entropy,
+        "max_entropy": matcher.max_entropy,
+        "no_leading_digit": matcher.no_leading_digit,
+        "canonical_case": matcher.canonical_case,
+        "collapse_case": matcher.collapse_case,
+        "max_distance": matcher.max_distance,
+        "min_occurrences": matcher.min_occurrences,
+        "byte_sum": matcher.byte_sum.map(|sum| (sum.target, sum.modulus)),
+    });
+    to_hex(&Sha256::digest(config.to_string()))
+}
+
+// Adds a run to the totals stored under key and returns the new totals. The file is a JSON object from key to
+// {"patterns", "runs", "attempts", "elapsed"}; a missing file starts from nothing, and the entries of other
+// configurations are kept as they are. It is replaced through a temporary file, so a write that fails halfway leaves
+// the earlier totals intact.
+pub fn record_run(
+    path: &Path,
+    key: &str,
+    patterns: &[String],
+    attempts: u64,
+    elapsed_secs: f64,
+) -> Result<LifetimeStats, String> {
+    let mut entries = match std::fs::read_to_string(path) {
+        Ok(contents) => match serde_json::from_str::<Value>(&contents) {
+            Ok(Value::Object(entries)) => entries,
+            _ => return Err(format!("{} is not a stats file", path.display())),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Map::new(),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let before = entries.get(key);
+    let field = |name: &str| before.and_then(|entry| entry.get(name));
+    let stats = LifetimeStats {
+        runs: field("runs").and_then(Value::as_u64).unwrap_or(0) + 1,
+        attempts: field("attempts")
+            .and_then(Value::as_u64)
+            .unwrap_or(0)
+            .saturating_add(attempts),
+        elapsed_secs: field("elapsed").and_then(Value::as_f64).unwrap_or(0.0) + elapsed_secs,
+    };
+    entries.insert(
+        key.to_string(),
+        json!({
+            "patterns": patterns,
+            "runs": stats.runs,
+            "attempts": stats.attempts,
+            "elapsed": stats.elapsed_secs,
+        }),
+    );
+
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    std::fs::write(&temp, format!("{}\n", Value::Object(entries)))
+        .and_then(|()| std::fs::rename(&temp, path))
+        .map_err(|e| e.to_string())?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::MatchType;
+
+    type StatsSearch = (&'static str, MatchType, bool);
+
+    // Pairs of searches, as pattern, match type and case sensitivity, and whether they share totals in a stats file
+    const STATS_KEY_VECTORS: &[(StatsSearch, StatsSearch, bool)] = &[
+        (
+            ("SoL", MatchType::Prefix, false),
+            ("SoL", MatchType::Prefix, false),
+            true,
+        ),
+        (
+            ("SoL", MatchType::Prefix, false),
+            ("SoL", MatchType::Prefix, true),
+            false,
+        ),
+        (
+            ("SoL", MatchType::Prefix, false),
+            ("SoL", MatchType::Suffix, false),
+            false,
+        ),
+        (
+            ("SoL", MatchType::Prefix, false),
+            ("SoLa", MatchType::Prefix, false),
+            false,
+        ),
+    ];
+
+    // Runs recorded one after another in a fresh stats file, as attempts and elapsed seconds; the totals read back after
+    // the last must add them all up, whatever other configurations the file holds
+    const STATS_VECTORS: &[&[(u64, f64)]] = &[
+        &[(1000, 2.0)],
+        // two runs of the same search accumulate
+        &[(1000, 2.0), (250, 0.5)],
+        &[(0, 0.0), (7, 1.25), (u64::MAX - 7, 3.0)],
+    ];
+
+    #[test]
+    fn searches_share_totals_only_when_configured_alike() {
+        let key = |(pattern, match_type, case_sensitive): StatsSearch| {
+            config_key(&PatternMatcher::new(
+                vec![pattern.to_string()],
+                match_type,
+                case_sensitive,
+                true,
+            ))
+        };
+        for &(a, b, same) in STATS_KEY_VECTORS {
+            assert_eq!(key(a) == key(b), same, "stats keys of {:?} and {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn stats_file_adds_up_runs() {
+        for (i, runs) in STATS_VECTORS.iter().enumerate() {
+            let path = std::env::temp_dir().join(format!(
+                "vanity-stats-{}-{}.json",
+                std::process::id(),
+                i
+            ));
+            let patterns = vec!["SoL".to_string()];
+            // another configuration in the same file must neither count nor be lost
+            let other = record_run(&path, "other", &patterns, 99, 9.0);
+            let mut last = Ok(LifetimeStats::default());
+            for &(attempts, elapsed) in runs.iter() {
+                last = record_run(&path, "key", &patterns, attempts, elapsed);
+            }
+            let other_after = record_run(&path, "other", &patterns, 0, 0.0);
+            let _ = std::fs::remove_file(&path);
+            let expected = LifetimeStats {
+                runs: runs.len() as u64,
+                attempts: runs.iter().map(|&(attempts, _)| attempts).sum(),
+                elapsed_secs: runs.iter().map(|&(_, elapsed)| elapsed).sum(),
+            };
+            assert_eq!(last, Ok(expected), "stats file after runs {:?}", runs);
+            assert_eq!(other.map(|other| other.attempts), Ok(99));
+            assert_eq!(
+                other_after.map(|other| (other.runs, other.attempts, other.elapsed_secs)),
+                Ok((2, 99, 9.0)),
+                "other configuration after runs {:?}",
+                runs
+            );
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::{Duration, Instant};
+
+// Length of the windows a worker's busy share is measured over. Long enough that one chunk of candidates is a small
+// part of it, short enough that the load looks steady to other programs and monitoring tools.
+pub const CPU_WINDOW: Duration = Duration::from_millis(500);
+
+// Keeps one worker busy for about a given share of wall clock time by pausing between chunks of candidates.
+// Each pause is sized from what was measured so far in the current window, so oversleeping or a slow chunk is
+// made up for by the next pause rather than accumulating.
+pub struct CpuThrottle {
+    share: f64,
+    window_start: Instant,
+    busy: Duration,
+    resumed: Instant,
+}
+
+impl CpuThrottle {
+    // percent is the share of one core the worker may use, from 1 to 100
+    pub fn new(percent: u8, now: Instant) -> Self {
+        CpuThrottle {
+            share: percent.clamp(1, 100) as f64 / 100.0,
+            window_start: now,
+            busy: Duration::ZERO,
+            resumed: now,
+        }
+    }
+
+    // Counts the work since the last resume and returns how long to pause now
+    pub fn pause(&mut self, now: Instant) -> Duration {
+        self.busy += now.duration_since(self.resumed);
+        pause_duration(self.share, self.busy, now.duration_since(self.window_start))
+    }
+
+    // Marks the end of a pause, starting a new window once the current one is CPU_WINDOW long
+    pub fn resume(&mut self, now: Instant) {
+        self.resumed = now;
+        if now.duration_since(self.window_start) >= CPU_WINDOW {
+            self.window_start = now;
+            self.busy = Duration::ZERO;
+        }
+    }
+}
+
+// Pause that brings a window with this much busy time out of this much elapsed time down to the target share:
+// the window has to last busy / share in total. Nothing when it is already at or below the share.
+pub fn pause_duration(share: f64, busy: Duration, elapsed: Duration) -> Duration {
+    busy.div_f64(share).saturating_sub(elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // CPU limit in percent, busy and elapsed milliseconds of a throttle window, and the pause in milliseconds that
+    // brings the window to the limit
+    const THROTTLE_VECTORS: &[(u8, u64, u64, u64)] = &[
+        (50, 100, 100, 100),
+        (25, 100, 100, 300),
+        (25, 100, 250, 150),
+        (50, 100, 250, 0),
+        (100, 100, 100, 0),
+    ];
+
+    #[test]
+    fn pause_brings_the_window_to_the_limit() {
+        for &(percent, busy, elapsed, expected) in THROTTLE_VECTORS {
+            let paused = pause_duration(
+                percent as f64 / 100.0,
+                Duration::from_millis(busy),
+                Duration::from_millis(elapsed),
+            );
+            assert_eq!(
+                paused,
+                Duration::from_millis(expected),
+                "pause at {}% after {}ms busy of {}ms",
+                percent,
+                busy,
+                elapsed
+            );
+        }
+    }
+}
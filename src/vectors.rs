@@ -0,0 +1,802 @@
+// Known answers for the matcher and the estimates, checked by the selftest subcommand and by its unit tests. Each
+// table documents what its rows hold and what the code under test must produce for them.
+use crate::matcher::{DEFAULT_FLEX_LEVEL, MatchType, PatternMatcher};
+use std::ops::Range;
+
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+// A known address and whether a search configured like this must accept it
+pub struct MatchVector {
+    pub address: &'static str,
+    pub pattern: &'static str,
+    pub match_type: MatchType,
+    pub case_sensitive: bool,
+    pub flexible_chars: bool,
+    // lookalike level used when flexible_chars is on
+    pub flex_level: u8,
+    pub fingerprint_len: usize,
+    pub at_range: Range<usize>,
+    pub window: Range<usize>,
+    pub excludes: &'static [&'static str],
+    pub min_distinct: usize,
+    pub canonical_case: bool,
+    pub expected: bool,
+}
+
+impl MatchVector {
+    pub fn matcher(&self) -> PatternMatcher {
+        PatternMatcher::new(
+            vec![self.pattern.to_string()],
+            self.match_type,
+            self.case_sensitive,
+            self.flexible_chars,
+        )
+        .with_flex_level(if self.flexible_chars {
+            self.flex_level
+        } else {
+            0
+        })
+        .with_fingerprint_len(self.fingerprint_len)
+        .with_at_range(self.at_range.clone())
+        .with_window(self.window.clone())
+        .with_excludes(self.excludes.iter().map(|e| e.to_string()).collect())
+        .with_min_distinct(self.min_distinct)
+        .with_canonical_case(self.canonical_case)
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{:?} '{}' on {} (case sensitive: {}, flexible: {}, flex level: {}, canonical case: {})",
+            self.match_type,
+            self.pattern,
+            self.address,
+            self.case_sensitive,
+            self.flexible_chars,
+            self.flex_level,
+            self.canonical_case
+        )
+    }
+}
+
+pub const fn vector(
+    address: &'static str,
+    pattern: &'static str,
+    match_type: MatchType,
+    case_sensitive: bool,
+    flexible_chars: bool,
+    expected: bool,
+) -> MatchVector {
+    MatchVector {
+        address,
+        pattern,
+        match_type,
+        case_sensitive,
+        flexible_chars,
+        flex_level: DEFAULT_FLEX_LEVEL,
+        fingerprint_len: 8,
+        at_range: 0..1,
+        window: 0..44,
+        excludes: &[],
+        min_distinct: 0,
+        canonical_case: false,
+        expected,
+    }
+}
+
+// Matcher behaviour across every match type and character rule
+pub const MATCH_VECTORS: &[MatchVector] = &[
+    vector(USDC_MINT, "EPjF", MatchType::Prefix, true, false, true),
+    vector(USDC_MINT, "epjf", MatchType::Prefix, true, false, false),
+    vector(USDC_MINT, "epjf", MatchType::Prefix, false, false, true),
+    vector(USDC_MINT, "EPJF", MatchType::Prefix, false, false, true),
+    vector(USDC_MINT, "3PjF", MatchType::Prefix, false, true, true),
+    vector(USDC_MINT, "3PjF", MatchType::Prefix, false, false, false),
+    vector(USDC_MINT, "3PjF", MatchType::Prefix, true, true, false),
+    vector(USDC_MINT, "Dt1v", MatchType::Suffix, true, false, true),
+    vector(USDC_MINT, "dt1v", MatchType::Suffix, false, false, true),
+    vector(USDC_MINT, "DtLv", MatchType::Suffix, false, true, true),
+    vector(USDC_MINT, "DtLv", MatchType::Suffix, false, false, false),
+    vector(USDC_MINT, "EPjF", MatchType::Suffix, false, true, false),
+    vector(USDC_MINT, "Dt1v", MatchType::Either, true, false, true),
+    vector(USDC_MINT, "EPjF", MatchType::Either, true, false, true),
+    vector(USDC_MINT, "zzz", MatchType::Either, false, true, false),
+    vector(WSOL_MINT, "So1", MatchType::Prefix, true, false, true),
+    vector(WSOL_MINT, "soL", MatchType::Prefix, false, true, true),
+    vector(WSOL_MINT, "soL", MatchType::Prefix, false, false, false),
+    vector(USDC_MINT, "jFWd", MatchType::Fingerprint, true, false, true),
+    MatchVector {
+        fingerprint_len: 5,
+        ..vector(
+            USDC_MINT,
+            "jFWd",
+            MatchType::Fingerprint,
+            true,
+            false,
+            false,
+        )
+    },
+    vector(
+        USDC_MINT,
+        "Dt1v",
+        MatchType::Fingerprint,
+        true,
+        false,
+        false,
+    ),
+    MatchVector {
+        at_range: 2..4,
+        ..vector(USDC_MINT, "FWdd", MatchType::Range, true, false, true)
+    },
+    MatchVector {
+        at_range: 0..3,
+        ..vector(USDC_MINT, "FWdd", MatchType::Range, true, false, false)
+    },
+    // "Wdd5" sits at positions 4..8 and "ybap" at 23..27 of the USDC mint
+    MatchVector {
+        window: 4..8,
+        ..vector(USDC_MINT, "Wdd5", MatchType::Window, true, false, true)
+    },
+    MatchVector {
+        window: 0..15,
+        ..vector(USDC_MINT, "wdd5", MatchType::Window, false, false, true)
+    },
+    MatchVector {
+        window: 5..15,
+        ..vector(USDC_MINT, "Wdd5", MatchType::Window, true, false, false)
+    },
+    MatchVector {
+        window: 0..7,
+        ..vector(USDC_MINT, "Wdd5", MatchType::Window, true, false, false)
+    },
+    MatchVector {
+        window: 5..15,
+        ..vector(USDC_MINT, "ybap", MatchType::Window, true, false, false)
+    },
+    MatchVector {
+        window: 20..30,
+        ..vector(USDC_MINT, "ybap", MatchType::Window, true, false, true)
+    },
+    // the wrapped SOL mint has 43 characters, so a window reaching the 44th position ends with the address
+    MatchVector {
+        window: 38..44,
+        ..vector(WSOL_MINT, "112", MatchType::Window, true, false, true)
+    },
+    MatchVector {
+        window: 42..44,
+        ..vector(WSOL_MINT, "12", MatchType::Window, true, false, false)
+    },
+    // "yba" sits at positions 23..26 of the USDC mint; an anagram only folds case, so "5" never stands in for "S"
+    vector(USDC_MINT, "pabY", MatchType::Anagram, true, false, false),
+    vector(USDC_MINT, "pabY", MatchType::Anagram, false, true, true),
+    vector(USDC_MINT, "ddS", MatchType::Anagram, false, true, false),
+    MatchVector {
+        canonical_case: true,
+        ..vector(USDC_MINT, "pabY", MatchType::Anagram, false, false, false)
+    },
+    MatchVector {
+        canonical_case: true,
+        ..vector(USDC_MINT, "paby", MatchType::Anagram, false, false, true)
+    },
+    MatchVector {
+        excludes: &["ybap"],
+        ..vector(USDC_MINT, "EPjF", MatchType::Prefix, true, false, false)
+    },
+    MatchVector {
+        excludes: &["YBAP"],
+        ..vector(USDC_MINT, "EPjF", MatchType::Prefix, true, false, true)
+    },
+    // the USDC mint address has exactly 34 distinct characters
+    MatchVector {
+        min_distinct: 34,
+        ..vector(USDC_MINT, "EPjF", MatchType::Prefix, true, false, true)
+    },
+    MatchVector {
+        min_distinct: 35,
+        ..vector(USDC_MINT, "EPjF", MatchType::Prefix, true, false, false)
+    },
+    // word boundaries in EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v: the start, j|F (upper after lower),
+    // d|5 (digit after letter) and t|1; but not F|W (upper after upper) or P|j (lower after a letter)
+    vector(USDC_MINT, "EPjF", MatchType::Word, true, false, true),
+    vector(USDC_MINT, "FWdd", MatchType::Word, true, false, true),
+    vector(USDC_MINT, "fwdd", MatchType::Word, false, false, true),
+    vector(USDC_MINT, "Wdd5", MatchType::Word, true, false, false),
+    vector(USDC_MINT, "5Auf", MatchType::Word, true, false, true),
+    vector(USDC_MINT, "jFWd", MatchType::Word, true, false, false),
+    vector(USDC_MINT, "Dt1v", MatchType::Word, true, false, false),
+    vector(USDC_MINT, "1v", MatchType::Word, true, false, true),
+    // canonical case keeps case folding from changing a letter's case, but lookalikes still count
+    MatchVector {
+        canonical_case: true,
+        ..vector(USDC_MINT, "EPjF", MatchType::Prefix, false, false, true)
+    },
+    MatchVector {
+        canonical_case: true,
+        ..vector(USDC_MINT, "EPJF", MatchType::Prefix, false, false, false)
+    },
+    MatchVector {
+        canonical_case: true,
+        ..vector(USDC_MINT, "epjf", MatchType::Prefix, false, true, false)
+    },
+    MatchVector {
+        canonical_case: true,
+        ..vector(USDC_MINT, "3PjF", MatchType::Prefix, false, true, true)
+    },
+    MatchVector {
+        canonical_case: true,
+        ..vector(USDC_MINT, "dt1v", MatchType::Suffix, false, false, false)
+    },
+    MatchVector {
+        canonical_case: true,
+        ..vector(USDC_MINT, "Dt1v", MatchType::Suffix, false, false, true)
+    },
+    // each flex level accepts the lookalikes of the levels below it and no looser ones
+    MatchVector {
+        flex_level: 0,
+        ..vector(WSOL_MINT, "soL", MatchType::Prefix, false, true, false)
+    },
+    MatchVector {
+        flex_level: 1,
+        ..vector(WSOL_MINT, "soL", MatchType::Prefix, false, true, true)
+    },
+    MatchVector {
+        flex_level: 1,
+        ..vector(USDC_MINT, "3PjF", MatchType::Prefix, false, true, false)
+    },
+    MatchVector {
+        flex_level: 3,
+        ..vector(USDC_MINT, "3PjF", MatchType::Prefix, false, true, true)
+    },
+    vector(USDC_MINT, "Dt1u", MatchType::Suffix, false, true, false),
+    MatchVector {
+        flex_level: 3,
+        ..vector(USDC_MINT, "Dt1u", MatchType::Suffix, false, true, true)
+    },
+    MatchVector {
+        flex_level: 3,
+        ..vector(USDC_MINT, "Dt1u", MatchType::Suffix, true, true, false)
+    },
+];
+
+// A pattern character, case sensitivity and flex level, and every base58 character it accepts in alphabet order.
+// 'i' and 'o' exist only in lower case and 'L' only in upper case, so ignoring case must not give them a second form.
+pub const CHAR_VECTORS: &[(u8, bool, u8, &str)] = &[
+    (b'a', false, 0, "Aa"),
+    (b'A', false, 0, "Aa"),
+    (b'a', true, 0, "a"),
+    (b'i', false, 0, "i"),
+    (b'o', false, 0, "o"),
+    (b'L', false, 0, "L"),
+    (b'i', true, 0, "i"),
+    (b'L', true, 2, "L"),
+    (b'i', false, 2, "1i"),
+    (b'o', false, 2, "o"),
+    (b'L', false, 2, "1L"),
+    (b'1', false, 2, "1Li"),
+    (b'o', false, 3, "Aao"),
+];
+
+// Patterns and the characters in them --strict-base58 objects to without lookalikes, each listed once in order
+pub const AMBIGUITY_VECTORS: &[(&str, &str)] = &[
+    ("Punk", ""),
+    ("So1", "S1"),
+    ("B8ad", "B8"),
+    ("zzZ2", "zZ2"),
+    ("Lime", "Li"),
+];
+
+// Encodings of [0; z] followed by [7; 32 - z] for z = 0 to 3 leading zero bytes, one leading '1' per zero byte
+pub const LEADING_ONES_ADDRESSES: [&str; 4] = [
+    "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+    "17DUeBUtEcb7nujVZRJmeBju3X1mo6PpnWNtJ9EBhdY",
+    "112QftUJC9yYkE7xr4ikuFmaHKhLCxkpK5RL1J2QqC2",
+    "111KWCfMfKoLt2cZbE5UcrZknqeER1Q4vVBx4Epu58",
+];
+
+// Case sensitive prefix-after-ones patterns, the LEADING_ONES_ADDRESSES entry they are matched against, and where
+// the match must start. The pattern has to begin right after the last leading '1'.
+pub const AFTER_ONES_VECTORS: &[(&str, usize, Option<usize>)] = &[
+    ("US5", 0, Some(0)),
+    ("7DU", 1, Some(1)),
+    ("2Qf", 2, Some(2)),
+    ("KWC", 3, Some(3)),
+    ("1KW", 3, None),
+    ("Qft", 2, None),
+    ("KWC", 0, None),
+];
+
+// Prefix-after-ones patterns, case sensitivity, flex level and expected attempts. The character after the leading
+// '1's is one of the other 57, so a '1' the pattern character would accept no longer counts.
+pub const AFTER_ONES_ESTIMATE_VECTORS: &[(&str, bool, u8, f64)] = &[
+    ("A", true, 0, 57.0),
+    ("AB", true, 0, 3306.0),
+    // 'i' and its lookalike '1', of which only 'i' can follow the leading '1's
+    ("i", false, 1, 57.0),
+];
+
+// Case sensitive patterns matched against USDC_MINT with up to max_distance wrong characters ('x' never matches)
+pub const DISTANCE_VECTORS: &[(&str, MatchType, usize, bool)] = &[
+    ("EPjF", MatchType::Prefix, 0, true),
+    ("EPxF", MatchType::Prefix, 0, false),
+    ("EPxF", MatchType::Prefix, 1, true),
+    ("xPxF", MatchType::Prefix, 1, false),
+    ("xPxF", MatchType::Prefix, 2, true),
+    ("xxxF", MatchType::Prefix, 2, false),
+    ("Dx1v", MatchType::Suffix, 1, true),
+    ("xx1v", MatchType::Suffix, 2, true),
+    ("xx1v", MatchType::Either, 1, false),
+];
+
+// Case sensitive patterns -f rejects, which --no-validate hands to the matcher as they are, and whether USDC_MINT
+// must match them. Characters outside base58 never match, and a pattern longer than the address must not panic.
+pub const UNVALIDATED_VECTORS: &[(&str, MatchType, bool)] = &[
+    ("0", MatchType::Prefix, false),
+    ("EPjF0", MatchType::Fingerprint, false),
+    // past the 18 character limit, but still in the address
+    ("EPjFWdd5AufqSSqeM2qN1x", MatchType::Prefix, true),
+    ("qSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", MatchType::Suffix, true),
+    (
+        "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1vv",
+        MatchType::Prefix,
+        false,
+    ),
+    (
+        "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1vv",
+        MatchType::Either,
+        false,
+    ),
+    (
+        "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1vv",
+        MatchType::Word,
+        false,
+    ),
+];
+
+// Case sensitive patterns, their match type, and the pattern USDC_MINT comes closest to with how many of its
+// characters are right where the match type looks; ties go to the pattern given first
+pub const CLOSEST_VECTORS: &[(&[&str], MatchType, &str, usize)] = &[
+    (&["EPjF"], MatchType::Prefix, "EPjF", 4),
+    (&["EPjx"], MatchType::Prefix, "EPjx", 3),
+    (&["epjf"], MatchType::Prefix, "epjf", 1),
+    (&["xxxx", "EPxx"], MatchType::Prefix, "EPxx", 2),
+    (&["EPjx", "EPjy"], MatchType::Prefix, "EPjx", 3),
+    (&["Dt1x"], MatchType::Suffix, "Dt1x", 3),
+    (&["xxx1v"], MatchType::Either, "xxx1v", 2),
+    // "Auf" follows the digit 5, so it starts a word; "Wdd" follows "F" and does not
+    (&["Aufx"], MatchType::Word, "Aufx", 3),
+    (&["Wddx"], MatchType::Word, "Wddx", 1),
+];
+
+// Patterns, their match type, and how many leading pattern characters in a row longest_partial must find right in
+// USDC_MINT. Unlike CLOSEST_VECTORS, a wrong character ends the count.
+pub const LONGEST_PARTIAL_VECTORS: &[(&str, MatchType, usize)] = &[
+    ("EPjF", MatchType::Prefix, 4),
+    ("EPjx", MatchType::Prefix, 3),
+    ("ExjF", MatchType::Prefix, 1),
+    ("xPjF", MatchType::Prefix, 0),
+    ("TDxx", MatchType::Suffix, 0),
+    ("Dt1x", MatchType::Suffix, 3),
+    ("ybax", MatchType::Window, 3),
+];
+
+// Matched characters, the pattern, case sensitivity and flex level, with the side by side comparison --compare-match
+// must print for them
+pub const COMPARISON_VECTORS: &[(&str, &str, bool, u8, &str)] = &[
+    (
+        "5oL",
+        "SoL",
+        false,
+        DEFAULT_FLEX_LEVEL,
+        "  pattern  S o L\n  address  5 o L\n           * = =\n  S as 5 (lookalike)",
+    ),
+    (
+        "5oLA",
+        "SoLa",
+        false,
+        DEFAULT_FLEX_LEVEL,
+        "  pattern  S o L a\n  address  5 o L A\n           * = = ~\n  S as 5 (lookalike), a as A (case)",
+    ),
+    (
+        "SoL",
+        "SoL",
+        true,
+        0,
+        "  pattern  S o L\n  address  S o L\n           = = =\n  no substitutions",
+    ),
+    // only a match within --max-distance has a wrong character
+    (
+        "SoX",
+        "SoL",
+        false,
+        0,
+        "  pattern  S o L\n  address  S o X\n           = = x\n  L as X (wrong)",
+    ),
+];
+
+// Texts and their Shannon entropy in bits per character
+pub const ENTROPY_VECTORS: &[(&str, f64)] = &[
+    ("", 0.0),
+    ("aaaa", 0.0),
+    ("abab", 1.0),
+    ("aabb", 1.0),
+    ("abcd", 2.0),
+    // case counts, so these are four different characters
+    ("aAbB", 2.0),
+    ("abcdefgh", 3.0),
+    (USDC_MINT, 4.970573095811685),
+    (WSOL_MINT, 0.47563396876036546),
+];
+
+// Addresses, a pattern and its match type, whether --no-leading-digit is set, and whether the address must be
+// accepted. The pattern always matches, so only the first character decides.
+pub const LEADING_DIGIT_VECTORS: &[(&str, &str, MatchType, bool, bool)] = &[
+    (USDC_MINT, "Dt1v", MatchType::Suffix, true, true),
+    (WSOL_MINT, "So1", MatchType::Prefix, true, true),
+    (
+        "4wCoBwgYNucmuQMGCbNN83mWkbSAgpC1ZDRE1io5VBKv",
+        "VBKv",
+        MatchType::Suffix,
+        false,
+        true,
+    ),
+    (
+        "4wCoBwgYNucmuQMGCbNN83mWkbSAgpC1ZDRE1io5VBKv",
+        "VBKv",
+        MatchType::Suffix,
+        true,
+        false,
+    ),
+    (
+        "9RKBpcNJUS4rttgdwnmj3tCKswiHkS4XSBozYobnG2ZZ",
+        "2ZZ",
+        MatchType::Either,
+        true,
+        false,
+    ),
+    // the system program: a leading '1' is a digit too
+    (
+        "11111111111111111111111111111111",
+        "111",
+        MatchType::Prefix,
+        true,
+        false,
+    ),
+];
+
+// Addresses, entropy bounds and whether a matcher for prefix "a" with those bounds accepts them. "abab" has exactly
+// 1 bit per character and "abcd" 2, so the bounds are inclusive at exactly those values.
+pub const ENTROPY_LIMIT_VECTORS: &[(&str, Option<f64>, Option<f64>, bool)] = &[
+    ("abab", None, None, true),
+    ("abab", Some(1.0), None, true),
+    ("abab", Some(1.000001), None, false),
+    ("abab", None, Some(1.0), true),
+    ("abab", None, Some(0.999999), false),
+    ("abab", Some(1.0), Some(1.0), true),
+    ("abcd", Some(0.0), Some(2.0), true),
+    ("abcd", Some(2.0), Some(5.0), true),
+    ("abcd", Some(2.000001), Some(5.0), false),
+    ("aaaa", None, Some(0.0), true),
+];
+
+// Text, pattern, case sensitivity and how many non-overlapping occurrences count_occurrences must find. Where
+// occurrences overlap only the leftmost of them counts.
+pub const COUNT_VECTORS: &[(&str, &str, bool, usize)] = &[
+    ("soXso", "so", true, 2),
+    ("aaaa", "aa", true, 2),
+    ("aaa", "aa", true, 1),
+    ("sosos", "sos", true, 1),
+    ("SOso", "so", true, 1),
+    ("SOso", "so", false, 2),
+    ("so", "sos", true, 0),
+    (WSOL_MINT, "111", true, 13),
+];
+
+// Reference address, --like-len and match type, and the pattern entries --like derives; None when the reference or
+// the match type is refused. The reference itself must match every pattern it yields.
+pub const LIKE_VECTORS: &[(&str, usize, MatchType, Option<&[&str]>)] = &[
+    (USDC_MINT, 4, MatchType::Prefix, Some(&["EPjF:prefix"])),
+    (USDC_MINT, 3, MatchType::Suffix, Some(&["t1v:suffix"])),
+    (
+        USDC_MINT,
+        2,
+        MatchType::Either,
+        Some(&["EP:prefix", "1v:suffix"]),
+    ),
+    (
+        "11111111111111111111111111111112",
+        1,
+        MatchType::PrefixAfterOnes,
+        Some(&["2:prefix-after-ones"]),
+    ),
+    (WSOL_MINT, 3, MatchType::Word, None),
+    // not 32 bytes once decoded
+    ("EPjF", 2, MatchType::Prefix, None),
+    // '0' is no base58 character
+    (
+        "0PjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+        2,
+        MatchType::Prefix,
+        None,
+    ),
+    (USDC_MINT, 45, MatchType::Prefix, None),
+];
+
+// Address, case sensitive pattern, match type and --min-occurrences, and whether the address is accepted. "q"
+// occurs exactly 3 times in the USDC mint, "E" twice with one of them at the start, and "111" 13 times without
+// overlaps in the wrapped SOL mint. Fingerprint mode covers the whole address here.
+pub const OCCURRENCE_VECTORS: &[(&str, &str, MatchType, usize, bool)] = &[
+    (USDC_MINT, "q", MatchType::Fingerprint, 2, true),
+    (USDC_MINT, "q", MatchType::Fingerprint, 3, true),
+    (USDC_MINT, "q", MatchType::Fingerprint, 4, false),
+    (USDC_MINT, "E", MatchType::Prefix, 2, true),
+    (USDC_MINT, "E", MatchType::Prefix, 3, false),
+    (USDC_MINT, "E", MatchType::Suffix, 1, false),
+    (USDC_MINT, "v", MatchType::Suffix, 1, true),
+    (USDC_MINT, "v", MatchType::Suffix, 2, false),
+    (WSOL_MINT, "111", MatchType::Fingerprint, 13, true),
+    (WSOL_MINT, "111", MatchType::Fingerprint, 14, false),
+];
+
+// Max distance and expected attempts of the case sensitive prefix "EPjF": 58^4 over the ways to get at most that
+// many of the 4 characters wrong, each wrong one 57 times as likely as a right one
+pub const DISTANCE_ESTIMATE_VECTORS: &[(usize, f64)] = &[
+    (0, 11316496.0),
+    // 58^4 / (1 + 4 * 57)
+    (1, 49417.01310043668),
+    // 58^4 / (1 + 4 * 57 + 6 * 57^2)
+    (2, 573.7715357704203),
+];
+
+// A case sensitive prefix, a number of attempts and the chance of a match within them
+pub const PROBABILITY_VECTORS: &[(&str, u64, f64)] = &[
+    ("A", 0, 0.0),
+    ("A", 1, 1.0 / 58.0),
+    // 1 - (57/58)^2
+    ("A", 2, 0.034185493460166),
+    // 1 - (1 - 1/58^2)^3364, about 1 - 1/e
+    ("AB", 3364, 0.632175244472695),
+];
+
+// Addresses, patterns and match types, with whether collapsed case matching accepts them and whether case-insensitive
+// matching without lookalikes does. The two agree on every base58 pattern; 'O' and 'I' aren't base58 (they only get
+// through --no-validate) and collapse to themselves, so they match nothing, while case folding takes them for 'o'
+// and 'i'.
+pub const COLLAPSE_VECTORS: &[(&str, &str, MatchType, bool, bool)] = &[
+    (USDC_MINT, "epjf", MatchType::Prefix, true, true),
+    (USDC_MINT, "EPJFWDD5", MatchType::Prefix, true, true),
+    (USDC_MINT, "ePjFwDd5aUfQ", MatchType::Prefix, true, true),
+    (USDC_MINT, "zwytdt1v", MatchType::Suffix, true, true),
+    (USDC_MINT, "xzyBAP", MatchType::Either, false, false),
+    (USDC_MINT, "xzyBAP", MatchType::Window, true, true),
+    (USDC_MINT, "yab", MatchType::Anagram, true, true),
+    (USDC_MINT, "sqqs", MatchType::Prefix, false, false),
+    (WSOL_MINT, "so11", MatchType::Prefix, true, true),
+    (WSOL_MINT, "S011", MatchType::Prefix, false, false),
+    (WSOL_MINT, "SO11", MatchType::Prefix, false, true),
+];
+
+// Anagram letters, case sensitivity and max distance, and where anagram_start must find them in USDC_MINT
+pub const ANAGRAM_VECTORS: &[(&str, bool, usize, Option<usize>)] = &[
+    ("jPE", true, 0, Some(0)),
+    ("WFjP", true, 0, Some(1)),
+    ("dd", true, 0, Some(5)),
+    ("SqqS", true, 0, Some(11)),
+    ("bay", true, 0, Some(23)),
+    ("BAY", true, 0, None),
+    ("BAY", false, 0, Some(23)),
+    ("v1tD", true, 0, Some(40)),
+    ("jPx", true, 0, None),
+    ("jPx", true, 1, Some(0)),
+    // one 'G' short at "G4w", the first stretch holding two of the three
+    ("GGw", true, 0, None),
+    ("GGw", true, 1, Some(29)),
+    // longer than the address
+    (
+        "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1vv",
+        true,
+        0,
+        None,
+    ),
+];
+
+// Anagram letters, case sensitivity and max distance, and the chance they are met at one position; a search has
+// 45 - len positions to meet them at
+pub const ANAGRAM_PROBABILITY_VECTORS: &[(&str, bool, usize, f64)] = &[
+    // ab or ba
+    ("ab", true, 0, 2.0 / 3364.0),
+    ("aa", true, 0, 1.0 / 3364.0),
+    // either letter in either case
+    ("ab", false, 0, 8.0 / 3364.0),
+    // anything but two characters that are neither a nor b
+    ("ab", true, 1, 228.0 / 3364.0),
+];
+
+// Raw public keys, a hex prefix and whether it matches
+pub const HEX_VECTORS: &[([u8; 32], &str, bool)] = &[
+    ([255; 32], "fff", true),
+    ([255; 32], "FFFF", true),
+    ([255; 32], "ffe", false),
+    ([0; 32], "0", true),
+    ([0; 32], "00000001", false),
+];
+
+// Raw public keys and their value as a 256-bit big-endian number
+pub const DECIMAL_VECTORS: &[([u8; 32], &str)] = &[
+    ([0; 32], "0"),
+    (
+        [
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 1, 0,
+        ],
+        "256",
+    ),
+    // 2^248
+    (
+        [
+            1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0,
+        ],
+        "452312848583266388373324160190187140051835877600158453279131187530910662656",
+    ),
+    (
+        [1; 32],
+        "454086624460063511464984254936031011189294057512315937409637584344757371137",
+    ),
+    // 2^256 - 1
+    (
+        [255; 32],
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935",
+    ),
+];
+
+// Decimal prefixes and the chance that a uniform 256-bit public key starts with them
+pub const NUMERIC_PREFIX_VECTORS: &[(&str, f64)] = &[
+    ("1", 0.2323405728804938),
+    ("7", 0.09595742838993827),
+    ("11579", 2.7638748453250655e-05),
+];
+
+// Raw public keys and their base58 form
+pub const ENCODING_VECTORS: &[([u8; 32], &str)] = &[
+    ([0; 32], "11111111111111111111111111111111"),
+    (
+        [
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 1,
+        ],
+        "11111111111111111111111111111112",
+    ),
+    ([255; 32], "JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG"),
+];
+
+// Secret seeds and the address of the keypair they derive
+pub const KEYPAIR_VECTORS: &[([u8; 32], &str)] = &[
+    ([1; 32], "AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9"),
+    ([7; 32], "GmaDrppBC7P5ARKV8g3djiwP89vz1jLK23V2GBjuAEGB"),
+];
+
+// Logical core counts and the thread counts --autotune benchmarks on them
+pub const THREAD_COUNT_VECTORS: &[(usize, &[usize])] =
+    &[(1, &[1]), (2, &[1, 2]), (3, &[1, 3]), (8, &[1, 4, 8])];
+
+// Measured (threads, addresses/second) and the thread count --autotune recommends from them
+pub const AUTOTUNE_VECTORS: &[(&[(usize, f64)], usize)] = &[
+    (&[(1, 10000.0)], 1),
+    (&[(1, 10000.0), (4, 39000.0), (8, 76000.0)], 8),
+    // hyper-threads sharing the cores add next to nothing
+    (&[(1, 10000.0), (4, 39000.0), (8, 40500.0)], 4),
+    // oversubscribed: more threads got slower
+    (&[(1, 10000.0), (4, 30000.0), (8, 25000.0)], 4),
+    (&[(1, 10000.0), (2, 10200.0)], 1),
+];
+
+// Exact and flexible rates from estimate --compare-flexible and the overhead it reports, in percent
+pub const OVERHEAD_VECTORS: &[(f64, f64, f64)] = &[
+    (10000.0, 9000.0, 10.0),
+    (10000.0, 10000.0, 0.0),
+    // the flexible run came out faster
+    (8000.0, 10000.0, -25.0),
+    (0.0, 10000.0, 0.0),
+];
+
+// Bytes and their sum
+pub const BYTE_SUM_VECTORS: &[(&[u8], u32)] = &[
+    (&[], 0),
+    (&[1, 2, 3], 6),
+    (&[1; 32], 32),
+    (&[255; 32], 8160),
+];
+
+// --byte-sum (value, modulus) with prefix "EPjF" on the USDC mint, whose public key bytes add up to 4796 (188 mod
+// 256), and whether the address must be accepted
+pub const BYTE_SUM_MATCH_VECTORS: &[(&str, u32, Option<u32>, bool)] = &[
+    ("EPjF", 4796, None, true),
+    ("EPjF", 4797, None, false),
+    ("EPjF", 188, Some(256), true),
+    ("EPjF", 189, Some(256), false),
+    ("EPjF", 0, Some(2), true),
+    // the right sum doesn't make up for the pattern
+    ("zzzz", 4796, None, false),
+];
+
+// --byte-sum (value, modulus) and the chance a random public key meets it
+pub const BYTE_SUM_PROBABILITY_VECTORS: &[(u32, Option<u32>, f64)] = &[
+    // adding a uniform byte leaves every remainder mod 256 equally likely
+    (188, Some(256), 1.0 / 256.0),
+    (0, Some(2), 0.5),
+    // the middle of the distribution, close to 1 / (418 * sqrt(2 pi))
+    (4080, None, 9.498250439565e-4),
+    // only the key of 32 zero bytes
+    (0, None, 8.636168555094445e-78),
+];
+
+// Expected attempts, rate in addresses/second and watts from estimate --watts, and the expected energy in kWh;
+// None without a measured rate
+pub const ENERGY_VECTORS: &[(f64, f64, f64, Option<f64>)] = &[
+    // an hour at 100 W
+    (3_600_000.0, 1000.0, 100.0, Some(0.1)),
+    // a day at 250 W
+    (8.64e10, 1e6, 250.0, Some(6.0)),
+    (1000.0, 0.0, 100.0, None),
+    (1000.0, f64::NAN, 100.0, None),
+];
+
+// Energy in kWh and how estimate prints it
+pub const ENERGY_FORMAT_VECTORS: &[(f64, &str)] = &[
+    (0.1, "100.0 Wh"),
+    (6.0, "6.0 kWh"),
+    (2500.0, "2.5 MWh"),
+    (3.2e7, "32.0 GWh"),
+];
+
+// Attempts spent, expected attempts and the luck ratio of a search; None when the expected attempts are unknown
+pub const LUCK_VECTORS: &[(u64, f64, Option<f64>)] = &[
+    (0, 100.0, Some(0.0)),
+    (150, 100.0, Some(1.5)),
+    (50, 200.0, Some(0.25)),
+    (10, f64::INFINITY, None),
+    (10, f64::NAN, None),
+    (10, 0.0, None),
+];
+
+// Attempts a match took, expected attempts and how many standard deviations of the geometric distribution they lie
+// from them; None when the expected attempts are unknown
+pub const DEVIATION_VECTORS: &[(u64, f64, Option<f64>)] = &[
+    (100, 100.0, Some(0.0)),
+    // the deviation is sqrt(0.99) * 100
+    (200, 100.0, Some(1.005037815259212)),
+    (0, 100.0, Some(-1.005037815259212)),
+    (19900, 10000.0, Some(0.9900495037128093)),
+    (1, 1.0, Some(0.0)),
+    (10, f64::NAN, None),
+    (10, 0.5, None),
+];
+
+// Expected attempts, attempts spent and the share of searches done by then: 1 - (1 - 1/expected)^attempts
+pub const LUCK_CHANCE_VECTORS: &[(f64, u64, f64)] = &[
+    (4.0, 0, 0.0),
+    (1.0, 1, 1.0),
+    (2.0, 1, 0.5),
+    (2.0, 2, 0.75),
+    (4.0, 2, 0.4375),
+];
+
+// Prefix patterns, case sensitivity (no lookalikes), and whether any address starts with the pattern. The largest key
+// encodes as the first one, so 44 character patterns past it or starting above 'J' are impossible, while 43 character
+// ones can start with anything. 31 '1's leave one byte, whose largest value 255 encodes as "5Q".
+pub const PREFIX_POSSIBLE_VECTORS: &[(&str, bool, bool)] = &[
+    ("JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG", true, true),
+    ("JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFH", true, false),
+    ("K1111111111111111111111111111111111111111111", true, false),
+    ("J1111111111111111111111111111111111111111111", true, true),
+    ("zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz", true, true),
+    ("z", true, true),
+    ("1111111111111111111111111111111z", true, true),
+    ("1111111111111111111111111111111zz", true, false),
+    ("11111111111111111111111111111115Q", true, true),
+    ("11111111111111111111111111111115R", true, false),
+    ("11111111111111111111111111111115q", true, false),
+    ("11111111111111111111111111111115q", false, true),
+    ("11111111111111111111111111111111", true, true),
+    ("111111111111111111111111111111111", true, false),
+];
@@ -0,0 +1,412 @@
+// The command line as a user runs it, through the built binary. Searches run over the --debug-counter keys from 0
+// on one thread, so the address each one finds is the first counter address it accepts.
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_vanity_address::source::counter_seed;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Output, Stdio};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_solana_vanity_address"))
+        .args(args)
+        .output()
+        .expect("the binary runs")
+}
+
+// Starts a search over the counter keys with stdout and stderr piped, to read its output while it runs
+fn spawn_counter_search(args: &[&str]) -> Child {
+    Command::new(env!("CARGO_BIN_EXE_solana_vanity_address"))
+        .args(["-t", "1", "--debug-counter", "0"])
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("the binary runs")
+}
+
+// Searches the counter keys and returns what the search printed
+fn counter_search(args: &[&str]) -> String {
+    let mut full = vec!["-t", "1", "--debug-counter", "0"];
+    full.extend_from_slice(args);
+    let output = run(&full);
+    assert!(
+        output.status.success(),
+        "{:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+// The first counter address, from counter 0 on, that the predicate accepts
+fn first_counter_address(accept: impl Fn(&str) -> bool) -> String {
+    (0..)
+        .map(|counter| {
+            Keypair::new_from_array(counter_seed(counter))
+                .pubkey()
+                .to_string()
+        })
+        .find(|address| accept(address))
+        .unwrap()
+}
+
+// The JSON record --format json prints for the match
+fn counter_record(args: &[&str]) -> serde_json::Value {
+    let mut full = args.to_vec();
+    full.extend_from_slice(&["--format", "json"]);
+    serde_json::from_str(counter_search(&full).trim()).unwrap()
+}
+
+#[test]
+fn selftest_runs_with_the_default_thread_count() {
+    // the default must hold on a single core machine too, where two threads would be rejected
+    let output = run(&["selftest"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn find_alternatives_of_differing_lengths() {
+    // match type, --find, and the alternative the first match satisfies: the longer one first, then the shorter
+    let cases = [
+        ("prefix", "Abh|Zq", "Abh"),
+        ("prefix", "Zq|Abhx", "Zq"),
+        ("suffix", "6hr|Ab", "6hr"),
+        ("suffix", "Ab|Jq7hr", "Ab"),
+    ];
+    for (match_type, find, satisfied) in cases {
+        let alternatives: Vec<&str> = find.split('|').collect();
+        let expected = first_counter_address(|address| {
+            alternatives.iter().any(|alternative| match match_type {
+                "prefix" => address.starts_with(alternative),
+                _ => address.ends_with(alternative),
+            })
+        });
+        let record = counter_record(&["-s", "true", "-m", match_type, "-f", find]);
+        assert_eq!(record["pubkey"], expected, "{} '{}'", match_type, find);
+        assert_eq!(record["pattern"], satisfied, "{} '{}'", match_type, find);
+    }
+}
+
+#[test]
+fn find_rejects_an_invalid_alternative() {
+    // '0' isn't base58, so the second alternative can never match
+    let output = run(&["-f", "Ab|S0l"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid character '0'"));
+}
+
+#[test]
+fn batch_reports_duplicate_and_overlapping_patterns() {
+    let path = std::env::temp_dir().join(format!("vanity-cli-batch-{}.txt", std::process::id()));
+    std::fs::write(&path, "Ab\nAb\nAbh\n").unwrap();
+    let printed = counter_search(&["-s", "true", "--patterns-file", path.to_str().unwrap()]);
+    let _ = std::fs::remove_file(&path);
+    // the first counter address starting with Ab happens to start with Abh too
+    let expected = first_counter_address(|address| address.starts_with("Ab"));
+    assert!(expected.starts_with("Abh"));
+
+    assert!(printed.contains("Note: duplicate pattern 'Ab' ignored"));
+    assert!(printed.contains("Note: 'Ab' overlaps 'Abh'"));
+    // the one address found for Abh satisfies Ab as well, so nothing more is searched for
+    assert_eq!(printed.matches("Found address:").count(), 1, "{}", printed);
+    assert!(printed.contains("Matched pattern: Ab|Abh"));
+    assert!(printed.contains(&format!("Found address: {}", expected)));
+}
+
+// The text after "label: " on the first line that starts with it
+fn field<'a>(printed: &'a str, label: &str) -> &'a str {
+    printed
+        .lines()
+        .find_map(|line| line.strip_prefix(label)?.strip_prefix(": "))
+        .unwrap_or_else(|| panic!("no {} line in {}", label, printed))
+}
+
+#[test]
+fn case_insensitive_match_reports_the_address_casing() {
+    let expected = first_counter_address(|address| address.to_lowercase().starts_with("abh"));
+    let printed = counter_search(&["-s", "false", "-l", "false", "-f", "abh"]);
+    assert_eq!(field(&printed, "Found address"), expected);
+
+    // e.g. "Abh at position 0 (pattern: abh)"
+    let matched = field(&printed, "Matched");
+    let (text, rest) = matched.split_once(" at position ").unwrap();
+    let position: usize = rest.split_once(' ').unwrap().0.parse().unwrap();
+    assert_eq!(text, &expected[position..position + 3]);
+    assert_ne!(text, "abh", "the real casing differs from the pattern's");
+}
+
+#[test]
+fn compare_match_shows_an_exact_match() {
+    let printed = counter_search(&["-s", "true", "-f", "Ab", "--compare-match"]);
+    assert_eq!(field(&printed, "Matched"), "Ab at position 0 (pattern: Ab)");
+    assert!(
+        printed.contains("Comparison:"),
+        "no comparison in {}",
+        printed
+    );
+    assert!(printed.contains("no substitutions"), "{}", printed);
+}
+
+#[test]
+fn fingerprint_len_ignores_matches_past_the_window() {
+    let in_window = |address: &str| address[..8].contains("Zq");
+    let expected = first_counter_address(in_window);
+    // the window is what finds it: it doesn't start with the pattern, and an earlier address holds the pattern
+    // past the first 8 characters
+    assert!(!expected.starts_with("Zq"));
+    let earlier = first_counter_address(|address| address.contains("Zq"));
+    assert!(!in_window(&earlier));
+
+    let record = counter_record(&[
+        "-s",
+        "true",
+        "-m",
+        "fingerprint",
+        "--fingerprint-len",
+        "8",
+        "-f",
+        "Zq",
+    ]);
+    assert_eq!(record["pubkey"], expected);
+
+    let output = run(&["-m", "fingerprint", "--fingerprint-len", "2", "-f", "Zqa"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("longer than the 2 character fingerprint")
+    );
+}
+
+#[test]
+fn exclude_rejects_a_prefix_match() {
+    // the first address starting with Ab contains UUs, so the search has to go on to the next one
+    let first = first_counter_address(|address| address.starts_with("Ab"));
+    assert!(first.contains("UUs"));
+    let expected =
+        first_counter_address(|address| address.starts_with("Ab") && !address.contains("UUs"));
+
+    let record = counter_record(&["-s", "true", "-f", "Ab", "--exclude", "UUs"]);
+    assert_eq!(record["pubkey"], expected);
+}
+
+#[test]
+fn missing_output_directory_fails_before_searching() {
+    let dir = std::env::temp_dir().join(format!("vanity-cli-mkdir-{}", std::process::id()));
+    let path = dir.join("nested").join("key.json");
+    // a pattern that would take far longer than the test to find
+    let output = run(&["-f", "zzzzzzzzzz", "--output", path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("does not exist; pass --mkdir"));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Found address"));
+
+    let printed = counter_search(&["-f", "Ab", "--output", path.to_str().unwrap(), "--mkdir"]);
+    let written = solana_keypair::read_keypair_file(&path);
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(
+        written.unwrap().pubkey().to_string(),
+        field(&printed, "Found address")
+    );
+}
+
+#[test]
+fn at_range_bounds_where_the_match_starts() {
+    let starts_in_range = |address: &str| (1..3).any(|start| address[start..].starts_with("Zq"));
+    let expected = first_counter_address(starts_in_range);
+    // an earlier address holds the pattern at a start outside the range
+    let earlier = first_counter_address(|address| address.contains("Zq"));
+    assert!(!starts_in_range(&earlier));
+
+    let record = counter_record(&[
+        "-s",
+        "true",
+        "-m",
+        "range",
+        "--at-range",
+        "1..3",
+        "-f",
+        "Zq",
+    ]);
+    assert_eq!(record["pubkey"], expected);
+
+    let output = run(&["-m", "range", "--at-range", "40..46", "-f", "Zq"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Range must lie within"));
+}
+
+#[test]
+fn verify_after_write_leaves_a_readable_key_file() {
+    let path = std::env::temp_dir().join(format!("vanity-cli-verify-{}.json", std::process::id()));
+    let printed = counter_search(&[
+        "-f",
+        "Ab",
+        "--output",
+        path.to_str().unwrap(),
+        "--verify-after-write",
+    ]);
+    let written = solana_keypair::read_keypair_file(&path);
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(
+        written.unwrap().pubkey().to_string(),
+        field(&printed, "Found address")
+    );
+}
+
+#[test]
+fn command_line_beats_config_file_beats_default() {
+    let path = std::env::temp_dir().join(format!("vanity-cli-config-{}.toml", std::process::id()));
+    std::fs::write(&path, "match-type = \"suffix\"\nflex-level = 1\n").unwrap();
+    let config = path.to_str().unwrap();
+    let default = counter_search(&["-f", "Ab"]);
+    let from_file = counter_search(&["-f", "Ab", "--config", config]);
+    let overridden = counter_search(&[
+        "-f",
+        "Ab",
+        "--config",
+        config,
+        "-m",
+        "either",
+        "--flex-level",
+        "3",
+    ]);
+    let _ = std::fs::remove_file(&path);
+
+    let settings = |printed: &str| {
+        (
+            field(printed, "  Match Type").to_string(),
+            field(printed, "  Flexible Char Set").to_string(),
+        )
+    };
+    let expected =
+        |match_type: &str, level| (match_type.to_string(), format!("true (level {})", level));
+    assert_eq!(settings(&default), expected("Prefix", 2));
+    assert_eq!(settings(&from_file), expected("Suffix", 1));
+    assert_eq!(settings(&overridden), expected("Either", 3));
+}
+
+#[test]
+fn stream_count_reports_different_addresses() {
+    let printed = counter_search(&[
+        "-s", "true", "-f", "A", "--stream", "--count", "5", "--format", "json",
+    ]);
+    let addresses: Vec<String> = printed
+        .lines()
+        .map(|line| {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            record["pubkey"].as_str().unwrap().to_string()
+        })
+        .collect();
+    let distinct: std::collections::HashSet<&String> = addresses.iter().collect();
+    assert_eq!((addresses.len(), distinct.len()), (5, 5), "{:?}", addresses);
+}
+
+#[test]
+fn stream_with_the_largest_count_stops_on_a_closed_pipe() {
+    let mut child = spawn_counter_search(&[
+        "-s",
+        "true",
+        "-f",
+        "A",
+        "--stream",
+        "--count",
+        "18446744073709551615",
+        "--format",
+        "json",
+    ]);
+    // reading three records and dropping the reader closes the pipe
+    let records: Vec<serde_json::Value> = BufReader::new(child.stdout.take().unwrap())
+        .lines()
+        .take(3)
+        .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+        .collect();
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+
+    // one thread from counter 0 tries the counters in order, so the k-th counter took k + 1 attempts
+    let counters: Vec<u64> = (0..)
+        .filter(|&counter| {
+            Keypair::new_from_array(counter_seed(counter))
+                .pubkey()
+                .to_string()
+                .starts_with('A')
+        })
+        .take(3)
+        .collect();
+    for (record, counter) in records.iter().zip(counters) {
+        let address = Keypair::new_from_array(counter_seed(counter))
+            .pubkey()
+            .to_string();
+        assert_eq!(record["pubkey"], address);
+        assert_eq!(record["attempts"], counter + 1);
+    }
+    let found: u64 = field(&stderr, "Found").parse().unwrap();
+    assert!(found >= 3, "{}", stderr);
+}
+
+#[test]
+fn stream_json_lines_arrive_while_searching() {
+    let mut child =
+        spawn_counter_search(&["-s", "true", "-f", "Ab", "--stream", "--format", "json"]);
+    let mut reader = BufReader::new(child.stdout.take().unwrap());
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    // the stream has no end, so the line came through while the search kept going, not when the output was closed
+    let running = child.try_wait().unwrap().is_none();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert!(running);
+    let record: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(
+        record["pubkey"],
+        first_counter_address(|address| address.starts_with("Ab"))
+    );
+}
+
+#[test]
+fn stream_stops_cleanly_when_the_reader_goes_away() {
+    let mut child =
+        spawn_counter_search(&["-s", "true", "-f", "A", "--stream", "--format", "json"]);
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    // a closed pipe is how a consumer says it has enough, not an error
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+    assert!(!stderr.contains("Failed to write"), "{}", stderr);
+}
+
+#[test]
+fn no_validate_warns_about_an_impossible_prefix() {
+    // a 32 byte key encodes to at most 32 leading '1's, so no address starts with 35 of them
+    let pattern = "1".repeat(35);
+    let output = run(&[
+        "-t",
+        "1",
+        "--debug-counter",
+        "0",
+        "-s",
+        "true",
+        "-l",
+        "false",
+        "-f",
+        &pattern,
+        "--no-validate",
+        "--timeout",
+        "1",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&format!("Warning: No address can start with '{}'", pattern)),
+        "{}",
+        stderr
+    );
+    // the search still ran, until the timeout
+    assert!(stderr.contains("Timed out after"), "{}", stderr);
+}
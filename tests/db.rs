@@ -0,0 +1,45 @@
+// Keypairs stored in and read back from a SQLite database
+use rusqlite::Connection;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_vanity_address::db::{StoredKeypair, create_table, insert_keypair, stored_keypairs};
+use solana_vanity_address::output::FoundKeypair;
+
+// Keypairs (seed [b; 32], pattern, attempts, found at) inserted into one --db table in this order. Reading the table
+// back must give every row with the same address, secret and numbers, oldest first.
+const DB_VECTORS: &[(u8, &str, u64, u64)] = &[
+    (1, "AKnL", 42, 1_700_000_000),
+    (7, "", 0, 0),
+    (255, "zz", u32::MAX as u64 + 1, 1_800_000_000),
+];
+
+#[test]
+fn database_round_trip() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    create_table(&conn).unwrap();
+    for &(seed, pattern, attempts, found_at) in DB_VECTORS {
+        let found = FoundKeypair {
+            pattern: pattern.to_string(),
+            keypair: Keypair::new_from_array([seed; 32]),
+            mnemonic: None,
+            attempts,
+            elapsed_secs: 0.0,
+            commit_nonce: None,
+        };
+        insert_keypair(&mut conn, &found, found_at).unwrap();
+    }
+    let expected: Vec<StoredKeypair> = DB_VECTORS
+        .iter()
+        .map(|&(seed, pattern, attempts, found_at)| {
+            let keypair = Keypair::new_from_array([seed; 32]);
+            StoredKeypair {
+                pattern: pattern.to_string(),
+                pubkey: keypair.pubkey().to_string(),
+                secret: keypair.to_base58_string(),
+                found_at,
+                attempts,
+            }
+        })
+        .collect();
+    assert_eq!(stored_keypairs(&conn).unwrap(), expected);
+}
@@ -0,0 +1,19 @@
+// Starting the global worker pool. Rayon sets that pool up once per process, so this file holds a single test.
+use solana_vanity_address::search::start_global_pool;
+
+#[test]
+fn global_pool_falls_back_to_fewer_threads() {
+    // as if only 2 threads could be started: 8 and 4 fail
+    let mut tried = Vec::new();
+    let started = start_global_pool(8, |count| {
+        tried.push(count);
+        if count > 2 {
+            Err(format!("cannot start {} threads", count))
+        } else {
+            Ok(())
+        }
+    });
+    assert_eq!(started, Ok(2));
+    assert_eq!(tried, [8, 4, 2]);
+    assert_eq!(rayon::current_num_threads(), 2);
+}
@@ -0,0 +1,66 @@
+// Pattern lists fetched over HTTP from a server on a local port
+use solana_vanity_address::remote::{PATTERN_LIST_LIMIT, fetch_pattern_list};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+// Responses (status, body repeated n times) a local HTTP server gives to one fetch of a pattern list, and whether
+// the fetch must return the body. Error statuses and lists over PATTERN_LIST_LIMIT must fail.
+const PATTERNS_FETCH_VECTORS: &[(u16, &str, usize, bool)] = &[
+    (200, "Pay\nDev\t# comment\n", 1, true),
+    (200, "", 1, true),
+    (404, "not found", 1, false),
+    (500, "Pay\n", 1, false),
+    (200, "x\n", (PATTERN_LIST_LIMIT / 2) as usize + 1, false),
+];
+
+// Fetches a pattern list from a server on a local port that answers a single request with this response
+fn fetch_from_local_server(status: u16, body: &str) -> Result<String, String> {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!(
+        "http://127.0.0.1:{}/patterns.txt",
+        listener.local_addr().unwrap().port()
+    );
+    let response = format!(
+        "HTTP/1.1 {} Status\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let server = std::thread::spawn(move || -> std::io::Result<()> {
+        let (stream, _) = listener.accept()?;
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 2 {
+            line.clear();
+        }
+        // the client hangs up early when the body is over the limit
+        let _ = reader.get_mut().write_all(response.as_bytes());
+        Ok(())
+    });
+    let fetched = fetch_pattern_list(&url);
+    server.join().unwrap().unwrap();
+    fetched
+}
+
+#[test]
+fn pattern_list_fetch() {
+    for &(status, body, repeat, expected) in PATTERNS_FETCH_VECTORS {
+        let body = body.repeat(repeat);
+        match fetch_from_local_server(status, &body) {
+            Ok(fetched) => {
+                assert!(
+                    expected,
+                    "pattern list served with status {} ({} bytes): expected an error",
+                    status,
+                    body.len()
+                );
+                assert_eq!(fetched, body, "pattern list served with status {}", status);
+            }
+            Err(e) => assert!(
+                !expected,
+                "pattern list served with status {}: {}",
+                status, e
+            ),
+        }
+    }
+}
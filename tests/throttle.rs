@@ -0,0 +1,45 @@
+// How --cpu-limit affects the search rate. Timing dependent, so it only runs when asked for:
+// cargo test --test throttle -- --ignored
+use solana_vanity_address::matcher::{MatchType, PatternMatcher};
+use solana_vanity_address::search::{SearchControl, search};
+use solana_vanity_address::source::SequenceSource;
+use std::time::Duration;
+
+// Searches one worker for a pattern none of the candidates match and returns the attempts made in the time given
+fn attempts_within(duration: Duration, cpu_limit: Option<u8>) -> u64 {
+    let matcher = PatternMatcher::new(vec!["zzzzzzzz".to_string()], MatchType::Prefix, true, false);
+    let control = match cpu_limit {
+        Some(percent) => SearchControl::new().with_cpu_limit(percent),
+        None => SearchControl::new(),
+    };
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(duration);
+            control.stop();
+        });
+        search(
+            &matcher,
+            1,
+            |_| SequenceSource::new(vec![[1; 32], [2; 32]]),
+            &control,
+        )
+        .attempts
+    })
+}
+
+#[test]
+#[ignore = "timing dependent"]
+fn throughput_follows_the_cpu_limit() {
+    let duration = Duration::from_secs(3);
+    let unlimited = attempts_within(duration, None) as f64;
+    for percent in [75, 50, 25] {
+        let share = attempts_within(duration, Some(percent)) as f64 / unlimited;
+        let expected = percent as f64 / 100.0;
+        assert!(
+            (share - expected).abs() < 0.1,
+            "--cpu-limit {} ran at {:.0}% of the unlimited rate",
+            percent,
+            share * 100.0
+        );
+    }
+}